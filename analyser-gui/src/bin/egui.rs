@@ -1,4 +1,13 @@
-use analyser_gui::files::{DumpFile, DumpFiles};
+use analyser_gui::files::{Analysis, AnalysisKind, DumpFile, DumpFiles};
+use dump_analyser::{
+    coe::SdoResult,
+    error_counters::link_segment,
+    identity::Identity,
+    process_image::Signal,
+    registers,
+    significance::{Bootstrap, Comparison},
+    stats::DEFAULT_PERCENTILES,
+};
 use eframe::egui;
 use egui::epaint::Hsva;
 use egui::{Color32, TextStyle, Ui};
@@ -14,10 +23,47 @@ use notify_debouncer_full::{
 };
 use parking_lot::RwLock;
 use std::ffi::OsStr;
-use std::{path::PathBuf, sync::Arc, thread, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+/// Percentile the GUI computes a bootstrap confidence interval of the difference for.
+const SIGNIFICANCE_PERCENTILE: f64 = 99.0;
+
+/// Which analysis is shown in the central panel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+enum View {
+    #[default]
+    Latency,
+    SubDevices,
+    Sdo,
+    ProcessImage,
+    ProcessEvents,
+    Dc,
+    ErrorCounters,
+}
 
 struct MyApp {
     files: Arc<RwLock<DumpFiles>>,
+    view: View,
+    /// Process image signals to plot, by capture file.
+    selected_signals: BTreeSet<(PathBuf, Signal)>,
+    /// Only show process data events for signals containing this text.
+    event_filter: String,
+    /// Significance tests running in the background, by reference and compared capture.
+    running_significance: BTreeSet<(PathBuf, PathBuf)>,
+    /// Results of background significance tests, stored in [`DumpFiles`] on the next update.
+    significance_tx: mpsc::Sender<(PathBuf, PathBuf, Option<Comparison>)>,
+    significance_rx: mpsc::Receiver<(PathBuf, PathBuf, Option<Comparison>)>,
+    /// Analyses being built in the background, by capture.
+    running_analyses: BTreeSet<(PathBuf, AnalysisKind)>,
+    /// Finished background analyses, stored in [`DumpFiles`] on the next update.
+    analysis_tx: mpsc::Sender<(PathBuf, AnalysisKind, Analysis)>,
+    analysis_rx: mpsc::Receiver<(PathBuf, AnalysisKind, Analysis)>,
 }
 
 impl MyApp {
@@ -76,26 +122,150 @@ impl MyApp {
             });
     }
 
-    fn round_trip_stats_list(&mut self, ui: &mut Ui, selected_files: &[&DumpFile]) {
+    /// Store the results of any finished background significance tests.
+    fn receive_significance(&mut self) {
+        let results = self.significance_rx.try_iter().collect::<Vec<_>>();
+
+        if results.is_empty() {
+            return;
+        }
+
+        let mut files = self.files.write();
+
+        for (reference, item, comparison) in results {
+            let key = (reference, item);
+
+            self.running_significance.remove(&key);
+
+            files.insert_significance(key.0, key.1, comparison);
+        }
+    }
+
+    /// Store any finished background analyses.
+    fn receive_analyses(&mut self) {
+        let results = self.analysis_rx.try_iter().collect::<Vec<_>>();
+
+        if results.is_empty() {
+            return;
+        }
+
+        let mut files = self.files.write();
+
+        for (path, kind, analysis) in results {
+            self.running_analyses.remove(&(path.clone(), kind));
+
+            files.insert_analysis(&path, analysis);
+        }
+    }
+
+    /// Build an analysis of every given capture that doesn't have it yet, on a background thread
+    /// which requests a repaint when done.
+    fn load_analysis(&mut self, ctx: &egui::Context, files: &[&DumpFile], kind: AnalysisKind) {
+        for item in files {
+            if item.has_analysis(kind) || !self.running_analyses.insert((item.path.clone(), kind)) {
+                continue;
+            }
+
+            let path = item.path.clone();
+            let tx = self.analysis_tx.clone();
+            let ctx = ctx.clone();
+
+            thread::spawn(move || {
+                let analysis = Analysis::new(kind, &path);
+
+                // The app has gone away if this fails
+                if tx.send((path, kind, analysis)).is_ok() {
+                    ctx.request_repaint();
+                }
+            });
+        }
+    }
+
+    /// Significance of each selected capture's round trip times against the first selected
+    /// capture's, or `None` while it's still being computed.
+    ///
+    /// Tests take a while for large captures, so they're run on a background thread which
+    /// requests a repaint when done.
+    fn significance(
+        &mut self,
+        ctx: &egui::Context,
+        cache: &BTreeMap<PathBuf, BTreeMap<PathBuf, Option<Comparison>>>,
+        selected_files: &[&DumpFile],
+    ) -> Vec<Option<Comparison>> {
+        let Some((reference, rest)) = selected_files.split_first() else {
+            return Vec::new();
+        };
+
+        let mut out = vec![None];
+
+        for item in rest {
+            if let Some(comparison) = cache
+                .get(&reference.path)
+                .and_then(|items| items.get(&item.path))
+            {
+                out.push(*comparison);
+
+                continue;
+            }
+
+            out.push(None);
+
+            if !self
+                .running_significance
+                .insert((reference.path.clone(), item.path.clone()))
+            {
+                continue;
+            }
+
+            let times = |file: &DumpFile| {
+                file.round_trip_times
+                    .iter()
+                    .map(|[_x, y]| *y)
+                    .collect::<Vec<_>>()
+            };
+
+            let reference_times = times(reference);
+            let item_times = times(item);
+            let reference = reference.path.clone();
+            let item = item.path.clone();
+            let tx = self.significance_tx.clone();
+            let ctx = ctx.clone();
+
+            thread::spawn(move || {
+                let comparison = Comparison::new(
+                    &reference_times,
+                    &item_times,
+                    SIGNIFICANCE_PERCENTILE,
+                    &Bootstrap::default(),
+                );
+
+                // The app has gone away if this fails
+                if tx.send((reference, item, comparison)).is_ok() {
+                    ctx.request_repaint();
+                }
+            });
+        }
+
+        out
+    }
+
+    fn round_trip_stats_list(
+        &mut self,
+        ui: &mut Ui,
+        significance: &BTreeMap<PathBuf, BTreeMap<PathBuf, Option<Comparison>>>,
+        selected_files: &[&DumpFile],
+    ) {
         ui.heading("TX/RX statistics");
 
+        let significance = self.significance(ui.ctx(), significance, selected_files);
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             let table = TableBuilder::new(ui)
                 .striped(false)
                 .resizable(true)
                 .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
                 .column(Column::auto().at_least(350.0))
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
-                .column(Column::auto())
+                .columns(Column::auto(), 10 + DEFAULT_PERCENTILES.len())
                 .min_scrolled_height(0.0);
             // .sense(egui::Sense::click());
 
@@ -111,27 +281,38 @@ impl MyApp {
                         ui.strong("Variance");
                     });
 
+                    for percentile in DEFAULT_PERCENTILES {
+                        header.col(|ui| {
+                            ui.strong(format!("P{}", percentile));
+                        });
+                    }
+
                     header.col(|ui| {
-                        ui.strong("P25");
-                    });
-                    header.col(|ui| {
-                        ui.strong("P50");
+                        ui.strong("Min");
                     });
                     header.col(|ui| {
-                        ui.strong("P90");
+                        ui.strong("Mean");
                     });
                     header.col(|ui| {
-                        ui.strong("P99");
+                        ui.strong("Max");
                     });
-
                     header.col(|ui| {
-                        ui.strong("Min");
+                        ui.strong("MW p").on_hover_text(
+                            "Mann-Whitney U test p-value against the first selected file",
+                        );
                     });
                     header.col(|ui| {
-                        ui.strong("Mean");
+                        ui.strong("KS p").on_hover_text(
+                            "Kolmogorov-Smirnov test p-value against the first selected file",
+                        );
                     });
                     header.col(|ui| {
-                        ui.strong("Max");
+                        ui.strong(format!("ΔP{}", SIGNIFICANCE_PERCENTILE))
+                            .on_hover_text(format!(
+                                "Difference of P{} to the first selected file, with its 95% \
+                                 bootstrap confidence interval",
+                                SIGNIFICANCE_PERCENTILE
+                            ));
                     });
                     header.col(|ui| {
                         ui.strong("CPU");
@@ -163,18 +344,11 @@ impl MyApp {
                                 );
                             });
 
-                            row.col(|ui| {
-                                ui.colored_label(c, format!("{:.3} us", item.round_trip_stats.p25));
-                            });
-                            row.col(|ui| {
-                                ui.colored_label(c, format!("{:.3} us", item.round_trip_stats.p50));
-                            });
-                            row.col(|ui| {
-                                ui.colored_label(c, format!("{:.3} us", item.round_trip_stats.p90));
-                            });
-                            row.col(|ui| {
-                                ui.colored_label(c, format!("{:.3} us", item.round_trip_stats.p99));
-                            });
+                            for percentile in item.round_trip_stats.percentiles.iter() {
+                                row.col(|ui| {
+                                    ui.colored_label(c, format!("{:.3} us", percentile.value));
+                                });
+                            }
 
                             row.col(|ui| {
                                 ui.colored_label(c, format!("{:.3} us", item.round_trip_stats.min));
@@ -188,6 +362,46 @@ impl MyApp {
                             row.col(|ui| {
                                 ui.colored_label(c, format!("{:.3} us", item.round_trip_stats.max));
                             });
+
+                            match &significance[idx] {
+                                Some(comparison) => {
+                                    let difference = comparison.percentile_difference;
+
+                                    row.col(|ui| {
+                                        ui.colored_label(
+                                            c,
+                                            format!("{:.3e}", comparison.mann_whitney_u.p_value),
+                                        );
+                                    });
+                                    row.col(|ui| {
+                                        ui.colored_label(
+                                            c,
+                                            format!(
+                                                "{:.3e}",
+                                                comparison.kolmogorov_smirnov.p_value
+                                            ),
+                                        );
+                                    });
+                                    row.col(|ui| {
+                                        ui.colored_label(
+                                            c,
+                                            format!(
+                                                "{:+.3} us [{:+.3}, {:+.3}]",
+                                                difference.estimate,
+                                                difference.lower,
+                                                difference.upper
+                                            ),
+                                        );
+                                    });
+                                }
+                                None => {
+                                    for _ in 0..3 {
+                                        row.col(|ui| {
+                                            ui.colored_label(c, "-");
+                                        });
+                                    }
+                                }
+                            }
                             row.col(|ui| {
                                 ui.colored_label(c, &item.cpu);
                             });
@@ -255,8 +469,7 @@ impl MyApp {
 
         series[display_range]
             .chunks(stride)
-            .into_iter()
-            .map(|chunk| {
+            .flat_map(|chunk| {
                 let ys = chunk.iter().map(|[_x, y]| *y);
                 let xs = chunk.iter().map(|[x, _y]| *x);
 
@@ -273,291 +486,1022 @@ impl MyApp {
                     [x, ys.max_by(|a, b| (*a as u32).cmp(&(*b as u32))).unwrap()],
                 ]
             })
-            .flatten()
             .collect::<Vec<_>>()
     }
-}
 
-impl eframe::App for MyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::SidePanel::left("left_panel")
-            // .resizable(true)
-            // .default_width(200.0)
-            .min_width(200.0)
-            // .width_range(200.0..=500.0)
-            .show(ctx, |ui| {
-                // ui.vertical_centered(|ui| {
-                ui.heading("Captures");
-                // });
+    fn subdevices_view(&mut self, ui: &mut Ui) {
+        let borrow = self.files.read_arc_recursive();
 
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    self.file_list(ui);
+        let files = borrow.selected_paths().collect::<Vec<_>>();
+
+        self.load_analysis(ui.ctx(), &files, AnalysisKind::SubDevices);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (idx, item) in files.iter().enumerate() {
+                let c = idx_to_colour(idx);
+
+                ui.push_id(&item.path, |ui| {
+                    ui.colored_label(c, egui::RichText::new(&item.display_name).heading());
+
+                    let Some(subdevices) = &item.subdevices else {
+                        ui.spinner();
+
+                        return;
+                    };
+
+                    match subdevices.topology.discovered_count {
+                        Some(count) => ui.label(format!("{} SubDevices discovered", count)),
+                        None => ui.label("No discovery broadcast found in capture"),
+                    };
+
+                    TableBuilder::new(ui)
+                        .striped(true)
+                        .resizable(true)
+                        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                        .column(Column::auto().at_least(80.0))
+                        .column(Column::auto().at_least(80.0))
+                        .column(Column::auto().at_least(100.0))
+                        .column(Column::auto().at_least(100.0))
+                        .column(Column::auto().at_least(100.0))
+                        .column(Column::auto().at_least(100.0))
+                        .column(Column::auto().at_least(100.0))
+                        .column(Column::auto().at_least(100.0))
+                        .column(Column::auto().at_least(100.0))
+                        .column(Column::auto().at_least(100.0))
+                        .min_scrolled_height(0.0)
+                        .vscroll(false)
+                        .header(20.0, |mut header| {
+                            header.col(|ui| {
+                                ui.strong("Position");
+                            });
+                            header.col(|ui| {
+                                ui.strong("Address");
+                            });
+                            for port in 0..4 {
+                                header.col(|ui| {
+                                    ui.strong(format!("Port {}", port))
+                                        .on_hover_text(registers::name(0x0110));
+                                });
+                            }
+                            for heading in ["Vendor", "Product", "Revision", "Serial"] {
+                                header.col(|ui| {
+                                    ui.strong(heading);
+                                });
+                            }
+                        })
+                        .body(|mut body| {
+                            for subdevice in subdevices.topology.subdevices.values() {
+                                body.row(18.0, |mut row| {
+                                    row.col(|ui| {
+                                        ui.label(subdevice.position.to_string());
+                                    });
+                                    row.col(|ui| {
+                                        ui.label(
+                                            subdevice
+                                                .configured_address
+                                                .map(|address| format!("{:#06x}", address))
+                                                .unwrap_or_else(|| "-".to_string()),
+                                        );
+                                    });
+
+                                    for port in 0..4 {
+                                        row.col(|ui| {
+                                            let label = ui.label(
+                                                subdevice
+                                                    .dl_status
+                                                    .map(|status| status.port(port).to_string())
+                                                    .unwrap_or_else(|| "-".to_string()),
+                                            );
+
+                                            if let Some(status) = subdevice.dl_status {
+                                                label.on_hover_text(
+                                                    registers::describe_range(
+                                                        0x0110,
+                                                        &status.0.to_le_bytes(),
+                                                    )
+                                                    .join("\n"),
+                                                );
+                                            }
+                                        });
+                                    }
+
+                                    let identity = subdevice
+                                        .configured_address
+                                        .and_then(|address| {
+                                            subdevices.identities.by_address.get(&address)
+                                        })
+                                        .copied()
+                                        .unwrap_or_default();
+
+                                    for field in [
+                                        identity.vendor_id,
+                                        identity.product_code,
+                                        identity.revision,
+                                        identity.serial,
+                                    ] {
+                                        row.col(|ui| {
+                                            ui.label(Identity::display_field(field));
+                                        });
+                                    }
+                                });
+                            }
+                        });
                 });
-            });
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // if ui.button("Save Plot").clicked() {
-            //     ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
-            // }
+                ui.separator();
+            }
+        });
+    }
 
-            let heading_text_size = TextStyle::Heading.resolve(ui.style()).size;
+    fn sdo_view(&mut self, ui: &mut Ui) {
+        let borrow = self.files.read_arc_recursive();
 
-            StripBuilder::new(ui)
-                .size(Size::remainder())
-                .size(Size::remainder())
-                .size(Size::remainder())
-                .vertical(|mut strip| {
-                    let borrow = self.files.read_arc_recursive();
+        let files = borrow.selected_paths().collect::<Vec<_>>();
 
-                    let files = borrow.selected_paths().collect::<Vec<_>>();
+        self.load_analysis(ui.ctx(), &files, AnalysisKind::Sdo);
 
-                    strip.cell(|ui| {
-                        self.round_trip_stats_list(ui, &files);
-                    });
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (idx, item) in files.iter().enumerate() {
+                let c = idx_to_colour(idx);
+
+                ui.push_id(&item.path, |ui| {
+                    ui.colored_label(c, egui::RichText::new(&item.display_name).heading());
+
+                    let Some(transactions) = &item.sdo_transactions else {
+                        ui.spinner();
+
+                        return;
+                    };
+
+                    ui.label(format!("{} SDO transactions", transactions.len()));
+
+                    TableBuilder::new(ui)
+                        .striped(true)
+                        .resizable(true)
+                        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                        .column(Column::auto().at_least(60.0))
+                        .column(Column::auto().at_least(80.0))
+                        .column(Column::auto().at_least(60.0))
+                        .column(Column::auto().at_least(70.0))
+                        .column(Column::auto().at_least(80.0))
+                        .column(Column::auto().at_least(30.0))
+                        .column(Column::auto().at_least(60.0))
+                        .column(Column::auto().at_least(40.0))
+                        .column(Column::auto().at_least(200.0))
+                        .column(Column::remainder().at_least(100.0))
+                        .min_scrolled_height(0.0)
+                        .max_scroll_height(400.0)
+                        .header(20.0, |mut header| {
+                            for heading in [
+                                "Packet",
+                                "Time (s)",
+                                "Address",
+                                "Direction",
+                                "Object",
+                                "CA",
+                                "Segments",
+                                "Size",
+                                "Data",
+                                "Result",
+                            ] {
+                                header.col(|ui| {
+                                    ui.strong(heading);
+                                });
+                            }
+                        })
+                        .body(|body| {
+                            body.rows(18.0, transactions.len(), |mut row| {
+                                let transaction = &transactions[row.index()];
 
-                    // TX/RX round trip time
-                    strip.cell(|ui| {
-                        StripBuilder::new(ui)
-                            // Heading
-                            .size(Size::exact(heading_text_size))
-                            // Chart
-                            .size(Size::remainder())
-                            .vertical(|mut strip| {
-                                strip.cell(|ui| {
-                                    ui.heading("Packet round trip times (us)");
+                                row.col(|ui| {
+                                    ui.label(transaction.start_packet.to_string());
                                 });
-                                strip.cell(|ui| {
-                                    StripBuilder::new(ui)
-                                        .size(Size::relative(0.6))
-                                        .size(Size::remainder())
-                                        .horizontal(|mut strip| {
-                                            strip.cell(|ui| {
-                                                Plot::new("round_trips")
-                                                    .x_axis_label("Packet number")
-                                                    .y_axis_label("TX/RX round trip time (us)")
-                                                    .legend(Legend::default())
-                                                    .show(ui, |plot_ui| {
-                                                        let bounds = self.compute_bounds(plot_ui);
-
-                                                        for (idx, item) in files.iter().enumerate()
-                                                        {
-                                                            let points = self.aggregate(
-                                                                bounds,
-                                                                &item.round_trip_times,
-                                                            );
+                                row.col(|ui| {
+                                    ui.label(format!(
+                                        "{:.6}",
+                                        transaction.start_time.as_secs_f64()
+                                    ));
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:#06x}", transaction.address));
+                                });
+                                row.col(|ui| {
+                                    ui.label(transaction.direction.to_string());
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!(
+                                        "{:#06x}:{}",
+                                        transaction.index, transaction.sub_index
+                                    ));
+                                });
+                                row.col(|ui| {
+                                    ui.label(if transaction.complete_access {
+                                        "yes"
+                                    } else {
+                                        "no"
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.label(transaction.segments.to_string());
+                                });
+                                row.col(|ui| {
+                                    ui.label(transaction.data.len().to_string());
+                                });
+                                row.col(|ui| {
+                                    let data = transaction
+                                        .data
+                                        .iter()
+                                        .map(|byte| format!("{:02x}", byte))
+                                        .collect::<Vec<_>>()
+                                        .join(" ");
+
+                                    ui.label(data);
+                                });
+                                row.col(|ui| {
+                                    let text = transaction.result.to_string();
 
-                                                            plot_ui.line(
-                                                                Line::new(
-                                                                    &item.display_name,
-                                                                    PlotPoints::new(points),
-                                                                )
-                                                                .color(idx_to_colour(idx)),
-                                                            );
-                                                        }
-                                                    });
-                                            });
+                                    match transaction.result {
+                                        SdoResult::Ok => ui.label(text),
+                                        _ => ui.colored_label(Color32::RED, text),
+                                    };
+                                });
+                            });
+                        });
+                });
 
-                                            strip.cell(|ui| {
-                                                Plot::new("round_trips_histo")
-                                                    // Y is just a count of the bucket, so is meaningless
-                                                    .show_y(false)
-                                                    .y_axis_formatter(|_, _| String::new())
-                                                    .x_axis_label("Round trip time (us)")
-                                                    .legend(Legend::default())
-                                                    .show(ui, |plot_ui| {
-                                                        for (idx, item) in files.iter().enumerate()
-                                                        {
-                                                            let c = idx_to_colour(idx);
-
-                                                            let points = item
-                                                                .round_trip_histo
-                                                                .iter_all()
-                                                                .enumerate()
-                                                                .map(|(idx, bucket)| {
-                                                                    [
-                                                                        idx as f64,
-                                                                        bucket.count_at_value()
-                                                                            as f64,
-                                                                    ]
-                                                                })
-                                                                .collect::<Vec<_>>();
-
-                                                            // Mean
-                                                            plot_ui.vline(
-                                                                VLine::new(
-                                                                    String::new(),
-                                                                    item.round_trip_stats.mean,
-                                                                )
-                                                                .style(LineStyle::dashed_dense())
-                                                                .color(
-                                                                    c
-                                                                        // 50% alpha
-                                                                        .gamma_multiply(0.75),
-                                                                ),
-                                                            );
+                ui.separator();
+            }
+        });
+    }
 
-                                                            // Std dev
-                                                            plot_ui.vline(
-                                                                VLine::new(
-                                                                    String::new(),
-                                                                    item.round_trip_stats.mean
-                                                                        - item
-                                                                            .round_trip_stats
-                                                                            .std_dev
-                                                                            / 2.0,
-                                                                )
-                                                                .style(LineStyle::dashed_dense())
-                                                                .color(c.gamma_multiply(0.5)),
-                                                            );
-                                                            plot_ui.vline(
-                                                                VLine::new(
-                                                                    String::new(),
-                                                                    item.round_trip_stats.mean
-                                                                        + item
-                                                                            .round_trip_stats
-                                                                            .std_dev
-                                                                            / 2.0,
-                                                                )
-                                                                .style(LineStyle::dashed_dense())
-                                                                .color(c.gamma_multiply(0.5)),
-                                                            );
+    fn process_image_view(&mut self, ui: &mut Ui) {
+        let borrow = self.files.read_arc_recursive();
 
-                                                            plot_ui.line(
-                                                                Line::new(
-                                                                    &item.display_name,
-                                                                    PlotPoints::new(points),
-                                                                )
-                                                                .color(c),
-                                                            );
-                                                        }
-                                                    });
-                                            });
-                                        });
-                                });
+        let files = borrow.selected_paths().collect::<Vec<_>>();
+
+        self.load_analysis(ui.ctx(), &files, AnalysisKind::ProcessData);
+
+        StripBuilder::new(ui)
+            .size(Size::exact(350.0))
+            .size(Size::remainder())
+            .horizontal(|mut strip| {
+                strip.cell(|ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for (idx, item) in files.iter().enumerate() {
+                            let c = idx_to_colour(idx);
+
+                            ui.push_id(&item.path, |ui| {
+                                ui.colored_label(
+                                    c,
+                                    egui::RichText::new(&item.display_name).strong(),
+                                );
+
+                                let Some(process_data) = &item.process_data else {
+                                    ui.spinner();
+
+                                    return;
+                                };
+
+                                if process_data.series.is_empty() {
+                                    ui.label("No FMMU configuration found in capture");
+                                }
+
+                                for (signal, series) in process_data.series.iter() {
+                                    let key = (item.path.clone(), signal.clone());
+
+                                    let mut checked = self.selected_signals.contains(&key);
+
+                                    let label = format!(
+                                        "{} ({} changes)",
+                                        signal,
+                                        series.samples.len().saturating_sub(1)
+                                    );
+
+                                    if ui.checkbox(&mut checked, label).changed() {
+                                        if checked {
+                                            self.selected_signals.insert(key);
+                                        } else {
+                                            self.selected_signals.remove(&key);
+                                        }
+                                    }
+                                }
                             });
+
+                            ui.separator();
+                        }
                     });
-                    // Cycle to cycle delta
-                    strip.cell(|ui| {
-                        StripBuilder::new(ui)
-                            // Heading
-                            .size(Size::exact(heading_text_size))
-                            // Chart
-                            .size(Size::remainder())
-                            .vertical(|mut strip| {
-                                strip.cell(|ui| {
-                                    ui.heading("Cycle-cycle delta (us)");
+                });
+
+                strip.cell(|ui| {
+                    Plot::new("process_image")
+                        .x_axis_label("Time (s)")
+                        .y_axis_label("Value")
+                        .legend(Legend::default())
+                        .show(ui, |plot_ui| {
+                            for (idx, (path, signal)) in self.selected_signals.iter().enumerate() {
+                                let Some(item) = files.iter().find(|item| &item.path == path)
+                                else {
+                                    continue;
+                                };
+
+                                let Some(series) = item
+                                    .process_data
+                                    .as_ref()
+                                    .and_then(|process_data| process_data.series.get(signal))
+                                else {
+                                    continue;
+                                };
+
+                                // Draw as a step function, holding each value until the next
+                                // change.
+                                let mut points: Vec<[f64; 2]> =
+                                    Vec::with_capacity(series.samples.len() * 2 + 1);
+
+                                for sample in series.samples.iter() {
+                                    let t = sample.time.as_secs_f64();
+
+                                    if let Some([_, prev]) = points.last().copied() {
+                                        points.push([t, prev]);
+                                    }
+
+                                    points.push([t, sample.value as f64]);
+                                }
+
+                                if let Some([_, last]) = points.last().copied() {
+                                    points.push([series.end_time.as_secs_f64(), last]);
+                                }
+
+                                plot_ui.line(
+                                    Line::new(
+                                        format!("{} {}", item.display_name, signal),
+                                        PlotPoints::new(points),
+                                    )
+                                    .color(idx_to_colour(idx)),
+                                );
+                            }
+                        });
+                });
+            });
+    }
+
+    fn process_events_view(&mut self, ui: &mut Ui) {
+        let borrow = self.files.read_arc_recursive();
+
+        let files = borrow.selected_paths().collect::<Vec<_>>();
+
+        self.load_analysis(ui.ctx(), &files, AnalysisKind::ProcessData);
+
+        ui.horizontal(|ui| {
+            ui.label("Filter signals");
+            ui.text_edit_singleline(&mut self.event_filter);
+        });
+
+        let filter = self.event_filter.to_lowercase();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (idx, item) in files.iter().enumerate() {
+                let c = idx_to_colour(idx);
+
+                let Some(process_data) = &item.process_data else {
+                    ui.colored_label(c, egui::RichText::new(&item.display_name).heading());
+                    ui.spinner();
+                    ui.separator();
+
+                    continue;
+                };
+
+                // Match each signal once rather than every event.
+                let signals = process_data
+                    .series
+                    .keys()
+                    .filter(|signal| signal.to_string().to_lowercase().contains(&filter))
+                    .collect::<BTreeSet<_>>();
+
+                let events = process_data
+                    .events
+                    .iter()
+                    .filter(|event| signals.contains(&event.signal))
+                    .collect::<Vec<_>>();
+
+                ui.push_id(&item.path, |ui| {
+                    ui.colored_label(c, egui::RichText::new(&item.display_name).heading());
+
+                    ui.label(format!(
+                        "{} of {} events",
+                        events.len(),
+                        process_data.events.len()
+                    ));
+
+                    TableBuilder::new(ui)
+                        .striped(true)
+                        .resizable(true)
+                        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                        .column(Column::auto().at_least(60.0))
+                        .column(Column::auto().at_least(80.0))
+                        .column(Column::auto().at_least(60.0))
+                        .column(Column::auto().at_least(250.0))
+                        .column(Column::auto().at_least(80.0))
+                        .column(Column::remainder().at_least(80.0))
+                        .min_scrolled_height(0.0)
+                        .max_scroll_height(400.0)
+                        .header(20.0, |mut header| {
+                            for heading in
+                                ["Packet", "Time (s)", "Cycle", "Signal", "Previous", "Value"]
+                            {
+                                header.col(|ui| {
+                                    ui.strong(heading);
                                 });
-                                strip.cell(|ui| {
-                                    StripBuilder::new(ui)
-                                        .size(Size::relative(0.6))
-                                        .size(Size::remainder())
-                                        .horizontal(|mut strip| {
-                                            strip.cell(|ui| {
-                                                Plot::new("cycle_delta")
-                                                    .x_axis_label("Packet number")
-                                                    .y_axis_label("Cycle to cycle delta time (us)")
-                                                    .legend(Legend::default())
-                                                    .show(ui, |plot_ui| {
-                                                        let bounds = self.compute_bounds(plot_ui);
-
-                                                        for (idx, item) in files.iter().enumerate()
-                                                        {
-                                                            let points = self.aggregate(
-                                                                bounds,
-                                                                &item.cycle_delta_times,
-                                                            );
+                            }
+                        })
+                        .body(|body| {
+                            body.rows(18.0, events.len(), |mut row| {
+                                let event = events[row.index()];
 
-                                                            plot_ui.line(
-                                                                Line::new(
-                                                                    &item.display_name,
-                                                                    PlotPoints::new(points),
-                                                                )
-                                                                .color(idx_to_colour(idx)),
-                                                            );
-                                                        }
-                                                    });
-                                            });
+                                row.col(|ui| {
+                                    ui.label(event.sample.packet_number.to_string());
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:.6}", event.sample.time.as_secs_f64()));
+                                });
+                                row.col(|ui| {
+                                    ui.label(event.sample.cycle.to_string());
+                                });
+                                row.col(|ui| {
+                                    ui.label(event.signal.to_string());
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:#x}", event.previous));
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:#x}", event.sample.value));
+                                });
+                            });
+                        });
+                });
 
-                                            strip.cell(|ui| {
-                                                Plot::new("cycle_delta_histo")
-                                                    // Y is just a count of the bucket, so is meaningless
-                                                    .show_y(false)
-                                                    .y_axis_formatter(|_, _| String::new())
-                                                    .x_axis_label("Cycle to cycle delta (us)")
-                                                    .legend(Legend::default())
-                                                    .show(ui, |plot_ui| {
-                                                        for (idx, item) in files.iter().enumerate()
-                                                        {
-                                                            let c = idx_to_colour(idx);
-
-                                                            let points: Vec<[f64; 2]> = item
-                                                                .cycle_delta_histo
-                                                                .iter_all()
-                                                                .enumerate()
-                                                                .map(|(idx, bucket)| {
-                                                                    [
-                                                                        idx as f64,
-                                                                        bucket.count_at_value()
-                                                                            as f64,
-                                                                    ]
-                                                                })
-                                                                .collect::<Vec<_>>();
-
-                                                            // Mean
-                                                            plot_ui.vline(
-                                                                VLine::new(
-                                                                    String::new(),
-                                                                    item.cycle_delta_stats.mean,
-                                                                )
-                                                                .style(LineStyle::dashed_dense())
-                                                                .color(
-                                                                    c
-                                                                        // 50% alpha
-                                                                        .gamma_multiply(0.75),
-                                                                ),
-                                                            );
+                ui.separator();
+            }
+        });
+    }
 
-                                                            // Std dev
-                                                            plot_ui.vline(
-                                                                VLine::new(
-                                                                    String::new(),
-                                                                    item.cycle_delta_stats.mean
-                                                                        - item
-                                                                            .cycle_delta_stats
-                                                                            .std_dev
-                                                                            / 2.0,
-                                                                )
-                                                                .style(LineStyle::dashed_dense())
-                                                                .color(c.gamma_multiply(0.5)),
-                                                            );
-                                                            plot_ui.vline(
-                                                                VLine::new(
-                                                                    String::new(),
-                                                                    item.cycle_delta_stats.mean
-                                                                        + item
-                                                                            .round_trip_stats
-                                                                            .std_dev
-                                                                            / 2.0,
-                                                                )
-                                                                .style(LineStyle::dashed_dense())
-                                                                .color(c.gamma_multiply(0.5)),
-                                                            );
+    fn dc_view(&mut self, ui: &mut Ui) {
+        let borrow = self.files.read_arc_recursive();
+
+        let files = borrow.selected_paths().collect::<Vec<_>>();
+
+        self.load_analysis(ui.ctx(), &files, AnalysisKind::Dc);
+
+        StripBuilder::new(ui)
+            .size(Size::remainder())
+            .size(Size::remainder())
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    Plot::new("dc_offset")
+                        .x_axis_label("Host time (s)")
+                        .y_axis_label("Reference clock offset from host (ns)")
+                        .legend(Legend::default())
+                        .show(ui, |plot_ui| {
+                            for (idx, item) in files.iter().enumerate() {
+                                let Some(dc) = &item.dc else {
+                                    continue;
+                                };
+
+                                plot_ui.line(
+                                    Line::new(&item.display_name, PlotPoints::new(dc.offsets()))
+                                        .color(idx_to_colour(idx)),
+                                );
+                            }
+                        });
+                });
 
-                                                            plot_ui.line(
-                                                                Line::new(
-                                                                    &item.display_name,
-                                                                    PlotPoints::new(points),
+                strip.cell(|ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for (idx, item) in files.iter().enumerate() {
+                            let c = idx_to_colour(idx);
+
+                            ui.push_id(&item.path, |ui| {
+                                ui.colored_label(
+                                    c,
+                                    egui::RichText::new(&item.display_name).heading(),
+                                );
+
+                                let Some(dc) = &item.dc else {
+                                    ui.spinner();
+
+                                    return;
+                                };
+
+                                match dc.reference {
+                                    Some(reference) => ui.label(format!(
+                                        "Reference clock {:#06x}, {} reads, drift {}",
+                                        reference,
+                                        dc.times.len(),
+                                        dc.drift_ppm()
+                                            .map(|drift| format!("{:+.3} ppm", drift))
+                                            .unwrap_or_else(|| "-".to_string())
+                                    )),
+                                    None => ui.label("No reference clock FRMW found in capture"),
+                                };
+
+                                let field = |value: Option<String>| {
+                                    value.unwrap_or_else(|| "-".to_string())
+                                };
+
+                                TableBuilder::new(ui)
+                                    .striped(true)
+                                    .resizable(true)
+                                    .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                                    .columns(Column::auto().at_least(100.0), 7)
+                                    .min_scrolled_height(0.0)
+                                    .vscroll(false)
+                                    .header(20.0, |mut header| {
+                                        // Register each column is read from, shown on hover
+                                        for (heading, register) in [
+                                            ("Address", 0x0010),
+                                            ("Offset (ns)", 0x0920),
+                                            ("Delay (ns)", 0x0928),
+                                            ("Activation", 0x0981),
+                                            ("Start time", 0x0990),
+                                            ("SYNC0 (ns)", 0x09a0),
+                                            ("SYNC1 (ns)", 0x09a4),
+                                        ] {
+                                            header.col(|ui| {
+                                                ui.strong(heading)
+                                                    .on_hover_text(registers::name(register));
+                                            });
+                                        }
+                                    })
+                                    .body(|mut body| {
+                                        for (address, config) in dc.configs.iter() {
+                                            if *config == Default::default() {
+                                                continue;
+                                            }
+
+                                            body.row(18.0, |mut row| {
+                                                for (column, text) in [
+                                                    format!("{:#06x}", address),
+                                                    field(
+                                                        config
+                                                            .system_time_offset
+                                                            .map(|v| v.to_string()),
+                                                    ),
+                                                    field(
+                                                        config
+                                                            .system_time_delay
+                                                            .map(|v| v.to_string()),
+                                                    ),
+                                                    field(
+                                                        config
+                                                            .sync_activation
+                                                            .map(|v| format!("{:#04x}", v)),
+                                                    ),
+                                                    field(
+                                                        config
+                                                            .sync0_start_time
+                                                            .map(|v| v.to_string()),
+                                                    ),
+                                                    field(
+                                                        config
+                                                            .sync0_cycle_time
+                                                            .map(|v| v.to_string()),
+                                                    ),
+                                                    field(
+                                                        config
+                                                            .sync1_cycle_time
+                                                            .map(|v| v.to_string()),
+                                                    ),
+                                                ]
+                                                .into_iter()
+                                                .enumerate()
+                                                {
+                                                    row.col(|ui| {
+                                                        let label = ui.label(text);
+
+                                                        // Decode sync activation flags on hover
+                                                        if let (3, Some(activation)) =
+                                                            (column, config.sync_activation)
+                                                        {
+                                                            label.on_hover_text(
+                                                                registers::describe_range(
+                                                                    0x0981,
+                                                                    &[activation],
                                                                 )
-                                                                .color(c),
+                                                                .join("\n"),
                                                             );
                                                         }
                                                     });
+                                                }
                                             });
-                                        });
-                                });
+                                        }
+                                    });
+                            });
+
+                            ui.separator();
+                        }
+                    });
+                });
+            });
+    }
+
+    fn error_counters_view(&mut self, ui: &mut Ui) {
+        let borrow = self.files.read_arc_recursive();
+
+        let files = borrow.selected_paths().collect::<Vec<_>>();
+
+        self.load_analysis(ui.ctx(), &files, AnalysisKind::ErrorCounters);
+
+        StripBuilder::new(ui)
+            .size(Size::remainder())
+            .size(Size::remainder())
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    Plot::new("error_counters")
+                        .x_axis_label("Time (s)")
+                        .y_axis_label("Error counter increments")
+                        .legend(Legend::default())
+                        .show(ui, |plot_ui| {
+                            let mut idx = 0;
+
+                            for item in files.iter() {
+                                let Some(error_counters) = &item.error_counters else {
+                                    continue;
+                                };
+
+                                for (key, _total) in error_counters.totals() {
+                                    let points = error_counters.series[&key]
+                                        .iter()
+                                        .map(|sample| {
+                                            [sample.time.as_secs_f64(), sample.increments as f64]
+                                        })
+                                        .collect::<Vec<_>>();
+
+                                    plot_ui.line(
+                                        Line::new(
+                                            format!("{} {}", item.display_name, key),
+                                            PlotPoints::new(points),
+                                        )
+                                        .color(idx_to_colour(idx)),
+                                    );
+
+                                    idx += 1;
+                                }
+                            }
+                        });
+                });
+
+                strip.cell(|ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for (idx, item) in files.iter().enumerate() {
+                            let c = idx_to_colour(idx);
+
+                            ui.push_id(&item.path, |ui| {
+                                ui.colored_label(
+                                    c,
+                                    egui::RichText::new(&item.display_name).heading(),
+                                );
+
+                                let Some(error_counters) = &item.error_counters else {
+                                    ui.spinner();
+
+                                    return;
+                                };
+
+                                if error_counters.series.is_empty() {
+                                    ui.label("No error counter reads found in capture");
+                                }
+
+                                let degraded = error_counters.most_degraded();
+
+                                match degraded {
+                                    Some((address, port, total)) => ui.colored_label(
+                                        Color32::RED,
+                                        format!(
+                                            "Most degraded link: {}, {} link error increments",
+                                            link_segment(address, port),
+                                            total
+                                        ),
+                                    ),
+                                    None => ui.label("No link errors counted during capture"),
+                                };
+
+                                for (key, total) in error_counters.totals() {
+                                    let text = format!("{}: {} increments", key, total);
+
+                                    if degraded.is_some_and(|(address, port, _)| {
+                                        key.kind.is_link_error()
+                                            && key.address == address
+                                            && key.port == Some(port)
+                                    }) {
+                                        ui.colored_label(Color32::RED, text);
+                                    } else {
+                                        ui.label(text);
+                                    }
+                                }
                             });
+
+                            ui.separator();
+                        }
                     });
                 });
+            });
+    }
+
+    fn latency_view(&mut self, ui: &mut Ui) {
+        let heading_text_size = TextStyle::Heading.resolve(ui.style()).size;
+
+        StripBuilder::new(ui)
+            .size(Size::remainder())
+            .size(Size::remainder())
+            .size(Size::remainder())
+            .vertical(|mut strip| {
+                let borrow = self.files.read_arc_recursive();
+
+                let files = borrow.selected_paths().collect::<Vec<_>>();
+
+                strip.cell(|ui| {
+                    self.round_trip_stats_list(ui, &borrow.significance, &files);
+                });
+
+                // TX/RX round trip time
+                strip.cell(|ui| {
+                    StripBuilder::new(ui)
+                        // Heading
+                        .size(Size::exact(heading_text_size))
+                        // Chart
+                        .size(Size::remainder())
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                ui.heading("Packet round trip times (us)");
+                            });
+                            strip.cell(|ui| {
+                                StripBuilder::new(ui)
+                                    .size(Size::relative(0.6))
+                                    .size(Size::remainder())
+                                    .horizontal(|mut strip| {
+                                        strip.cell(|ui| {
+                                            Plot::new("round_trips")
+                                                .x_axis_label("Packet number")
+                                                .y_axis_label("TX/RX round trip time (us)")
+                                                .legend(Legend::default())
+                                                .show(ui, |plot_ui| {
+                                                    let bounds = self.compute_bounds(plot_ui);
+
+                                                    for (idx, item) in files.iter().enumerate() {
+                                                        let points = self.aggregate(
+                                                            bounds,
+                                                            &item.round_trip_times,
+                                                        );
+
+                                                        plot_ui.line(
+                                                            Line::new(
+                                                                &item.display_name,
+                                                                PlotPoints::new(points),
+                                                            )
+                                                            .color(idx_to_colour(idx)),
+                                                        );
+                                                    }
+                                                });
+                                        });
+
+                                        strip.cell(|ui| {
+                                            Plot::new("round_trips_histo")
+                                                // Y is just a count of the bucket, so is meaningless
+                                                .show_y(false)
+                                                .y_axis_formatter(|_, _| String::new())
+                                                .x_axis_label("Round trip time (us)")
+                                                .legend(Legend::default())
+                                                .show(ui, |plot_ui| {
+                                                    for (idx, item) in files.iter().enumerate() {
+                                                        let c = idx_to_colour(idx);
+
+                                                        let points = item
+                                                            .round_trip_histo
+                                                            .iter_all()
+                                                            .enumerate()
+                                                            .map(|(idx, bucket)| {
+                                                                [
+                                                                    idx as f64,
+                                                                    bucket.count_at_value() as f64,
+                                                                ]
+                                                            })
+                                                            .collect::<Vec<_>>();
+
+                                                        // Mean
+                                                        plot_ui.vline(
+                                                            VLine::new(
+                                                                String::new(),
+                                                                item.round_trip_stats.mean,
+                                                            )
+                                                            .style(LineStyle::dashed_dense())
+                                                            .color(
+                                                                c
+                                                                    // 50% alpha
+                                                                    .gamma_multiply(0.75),
+                                                            ),
+                                                        );
+
+                                                        // Std dev
+                                                        plot_ui.vline(
+                                                            VLine::new(
+                                                                String::new(),
+                                                                item.round_trip_stats.mean
+                                                                    - item.round_trip_stats.std_dev
+                                                                        / 2.0,
+                                                            )
+                                                            .style(LineStyle::dashed_dense())
+                                                            .color(c.gamma_multiply(0.5)),
+                                                        );
+                                                        plot_ui.vline(
+                                                            VLine::new(
+                                                                String::new(),
+                                                                item.round_trip_stats.mean
+                                                                    + item.round_trip_stats.std_dev
+                                                                        / 2.0,
+                                                            )
+                                                            .style(LineStyle::dashed_dense())
+                                                            .color(c.gamma_multiply(0.5)),
+                                                        );
+
+                                                        plot_ui.line(
+                                                            Line::new(
+                                                                &item.display_name,
+                                                                PlotPoints::new(points),
+                                                            )
+                                                            .color(c),
+                                                        );
+                                                    }
+                                                });
+                                        });
+                                    });
+                            });
+                        });
+                });
+                // Cycle to cycle delta
+                strip.cell(|ui| {
+                    StripBuilder::new(ui)
+                        // Heading
+                        .size(Size::exact(heading_text_size))
+                        // Chart
+                        .size(Size::remainder())
+                        .vertical(|mut strip| {
+                            strip.cell(|ui| {
+                                ui.heading("Cycle-cycle delta (us)");
+                            });
+                            strip.cell(|ui| {
+                                StripBuilder::new(ui)
+                                    .size(Size::relative(0.6))
+                                    .size(Size::remainder())
+                                    .horizontal(|mut strip| {
+                                        strip.cell(|ui| {
+                                            Plot::new("cycle_delta")
+                                                .x_axis_label("Packet number")
+                                                .y_axis_label("Cycle to cycle delta time (us)")
+                                                .legend(Legend::default())
+                                                .show(ui, |plot_ui| {
+                                                    let bounds = self.compute_bounds(plot_ui);
+
+                                                    for (idx, item) in files.iter().enumerate() {
+                                                        let points = self.aggregate(
+                                                            bounds,
+                                                            &item.cycle_delta_times,
+                                                        );
+
+                                                        plot_ui.line(
+                                                            Line::new(
+                                                                &item.display_name,
+                                                                PlotPoints::new(points),
+                                                            )
+                                                            .color(idx_to_colour(idx)),
+                                                        );
+                                                    }
+                                                });
+                                        });
+
+                                        strip.cell(|ui| {
+                                            Plot::new("cycle_delta_histo")
+                                                // Y is just a count of the bucket, so is meaningless
+                                                .show_y(false)
+                                                .y_axis_formatter(|_, _| String::new())
+                                                .x_axis_label("Cycle to cycle delta (us)")
+                                                .legend(Legend::default())
+                                                .show(ui, |plot_ui| {
+                                                    for (idx, item) in files.iter().enumerate() {
+                                                        let c = idx_to_colour(idx);
+
+                                                        let points: Vec<[f64; 2]> = item
+                                                            .cycle_delta_histo
+                                                            .iter_all()
+                                                            .enumerate()
+                                                            .map(|(idx, bucket)| {
+                                                                [
+                                                                    idx as f64,
+                                                                    bucket.count_at_value() as f64,
+                                                                ]
+                                                            })
+                                                            .collect::<Vec<_>>();
+
+                                                        // Mean
+                                                        plot_ui.vline(
+                                                            VLine::new(
+                                                                String::new(),
+                                                                item.cycle_delta_stats.mean,
+                                                            )
+                                                            .style(LineStyle::dashed_dense())
+                                                            .color(
+                                                                c
+                                                                    // 50% alpha
+                                                                    .gamma_multiply(0.75),
+                                                            ),
+                                                        );
+
+                                                        // Std dev
+                                                        plot_ui.vline(
+                                                            VLine::new(
+                                                                String::new(),
+                                                                item.cycle_delta_stats.mean
+                                                                    - item
+                                                                        .cycle_delta_stats
+                                                                        .std_dev
+                                                                        / 2.0,
+                                                            )
+                                                            .style(LineStyle::dashed_dense())
+                                                            .color(c.gamma_multiply(0.5)),
+                                                        );
+                                                        plot_ui.vline(
+                                                            VLine::new(
+                                                                String::new(),
+                                                                item.cycle_delta_stats.mean
+                                                                    + item.round_trip_stats.std_dev
+                                                                        / 2.0,
+                                                            )
+                                                            .style(LineStyle::dashed_dense())
+                                                            .color(c.gamma_multiply(0.5)),
+                                                        );
+
+                                                        plot_ui.line(
+                                                            Line::new(
+                                                                &item.display_name,
+                                                                PlotPoints::new(points),
+                                                            )
+                                                            .color(c),
+                                                        );
+                                                    }
+                                                });
+                                        });
+                                    });
+                            });
+                        });
+                });
+            });
+    }
+}
+
+impl eframe::App for MyApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.receive_significance();
+        self.receive_analyses();
+
+        egui::SidePanel::left("left_panel")
+            // .resizable(true)
+            // .default_width(200.0)
+            .min_width(200.0)
+            // .width_range(200.0..=500.0)
+            .show(ctx, |ui| {
+                // ui.vertical_centered(|ui| {
+                ui.heading("Captures");
+                // });
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.file_list(ui);
+                });
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // if ui.button("Save Plot").clicked() {
+            //     ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+            // }
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.view, View::Latency, "Latency");
+                ui.selectable_value(&mut self.view, View::SubDevices, "SubDevices");
+                ui.selectable_value(&mut self.view, View::Sdo, "CoE SDO");
+                ui.selectable_value(&mut self.view, View::ProcessImage, "Process image");
+                ui.selectable_value(&mut self.view, View::ProcessEvents, "Process data events");
+                ui.selectable_value(&mut self.view, View::Dc, "Distributed Clocks");
+                ui.selectable_value(&mut self.view, View::ErrorCounters, "Error counters");
+            });
+
+            ui.separator();
+
+            match self.view {
+                View::Latency => self.latency_view(ui),
+                View::SubDevices => self.subdevices_view(ui),
+                View::Sdo => self.sdo_view(ui),
+                View::ProcessImage => self.process_image_view(ui),
+                View::ProcessEvents => self.process_events_view(ui),
+                View::Dc => self.dc_view(ui),
+                View::ErrorCounters => self.error_counters_view(ui),
+            }
         });
     }
 }
@@ -626,7 +1570,7 @@ async fn main() -> Result<(), eframe::Error> {
                             } => {
                                 let paths = paths
                                     .into_iter()
-                                    .filter(|p| p.extension() == Some(&OsStr::new("pcapng")))
+                                    .filter(|p| p.extension() == Some(OsStr::new("pcapng")))
                                     .collect::<Vec<_>>();
 
                                 println!("Files deleted {:?}", paths);
@@ -655,13 +1599,17 @@ async fn main() -> Result<(), eframe::Error> {
                                 let paths = paths
                                     .into_iter()
                                     .filter(|p| {
-                                        p.is_file() && p.extension() == Some(&OsStr::new("pcapng"))
+                                        p.is_file() && p.extension() == Some(OsStr::new("pcapng"))
                                     })
                                     .collect::<Vec<_>>();
 
                                 println!("Files created {:?}", paths);
 
-                                files.write().update_items(paths);
+                                // Parse without holding the lock so the GUI keeps drawing
+                                let new = files.read().unloaded(paths);
+                                let items = DumpFiles::load(&new);
+
+                                files.write().insert_items(items);
                             }
                         }
                     }
@@ -670,7 +1618,21 @@ async fn main() -> Result<(), eframe::Error> {
                 }
             });
 
-            Ok(Box::new(MyApp { files }))
+            let (significance_tx, significance_rx) = mpsc::channel();
+            let (analysis_tx, analysis_rx) = mpsc::channel();
+
+            Ok(Box::new(MyApp {
+                files,
+                view: View::default(),
+                selected_signals: BTreeSet::new(),
+                event_filter: String::new(),
+                running_significance: BTreeSet::new(),
+                significance_tx,
+                significance_rx,
+                running_analyses: BTreeSet::new(),
+                analysis_tx,
+                analysis_rx,
+            }))
         }),
     )
 }
@@ -679,6 +1641,6 @@ async fn main() -> Result<(), eframe::Error> {
 fn idx_to_colour(idx: usize) -> Color32 {
     let i = idx as f32;
     let golden_ratio = (5.0_f32.sqrt() - 1.0) / 2.0; // 0.61803398875
-    let h = i as f32 * golden_ratio;
+    let h = i * golden_ratio;
     Hsva::new(h, 0.85, 0.5, 1.0).into() // TODO(emilk): OkLab or some other perspective color space
 }
@@ -1,7 +1,15 @@
-use dump_analyser::PcapFile;
+use dump_analyser::{
+    coe::{SdoReader, SdoTransaction},
+    dc::DcAnalysis,
+    error_counters::ErrorCounters,
+    identity::Identities,
+    process_image::{change_events, ChangeEvent, ProcessImage, Signal, TimeSeries},
+    significance::Comparison,
+    stats::{self, DumpFileStats},
+    topology::Topology,
+    PcapFile,
+};
 use hdrhistogram::Histogram;
-use parking_lot::RwLock;
-use statrs::statistics::{Data, OrderStatistics, Statistics};
 use std::{
     collections::{BTreeMap, HashSet},
     fs,
@@ -26,58 +34,159 @@ pub struct DumpFile {
     pub cycle_delta_stats: DumpFileStats,
     pub cpu: String,
     pub os: String,
+
+    /// Analyses built so far. Each needs a full pass over the capture, so is only built the first
+    /// time the capture is shown in a view that needs it.
+    pub subdevices: Option<SubDevices>,
+    pub sdo_transactions: Option<Vec<SdoTransaction>>,
+    pub process_data: Option<ProcessData>,
+    pub dc: Option<DcAnalysis>,
+    pub error_counters: Option<ErrorCounters>,
+}
+
+impl DumpFile {
+    /// Parse a capture, keeping only the round trip and cycle time statistics.
+    pub fn new(path: &Path) -> Self {
+        let mut capture = PcapFile::new(path);
+
+        let pairs = capture.match_tx_rx();
+
+        let round_trip_times = stats::round_trip_times(&pairs);
+        let cycle_delta_times = stats::cycle_delta_times(&pairs);
+
+        Self {
+            path: path.to_path_buf(),
+            display_name: path.file_stem().unwrap().to_string_lossy().to_string(),
+            selected: false,
+            num_points: pairs.len(),
+            round_trip_stats: DumpFileStats::new(&round_trip_times),
+            cycle_delta_stats: DumpFileStats::new(&cycle_delta_times),
+            round_trip_histo: stats::histogram(&round_trip_times),
+            cycle_delta_histo: stats::histogram(&cycle_delta_times),
+            round_trip_times,
+            cycle_delta_times,
+            cpu: capture.cpu,
+            os: capture.os,
+            subdevices: None,
+            sdo_transactions: None,
+            process_data: None,
+            dc: None,
+            error_counters: None,
+        }
+    }
+
+    /// Whether an analysis has been built yet.
+    pub fn has_analysis(&self, kind: AnalysisKind) -> bool {
+        match kind {
+            AnalysisKind::SubDevices => self.subdevices.is_some(),
+            AnalysisKind::Sdo => self.sdo_transactions.is_some(),
+            AnalysisKind::ProcessData => self.process_data.is_some(),
+            AnalysisKind::Dc => self.dc.is_some(),
+            AnalysisKind::ErrorCounters => self.error_counters.is_some(),
+        }
+    }
+
+    fn insert_analysis(&mut self, analysis: Analysis) {
+        match analysis {
+            Analysis::SubDevices(subdevices) => self.subdevices = Some(subdevices),
+            Analysis::Sdo(transactions) => self.sdo_transactions = Some(transactions),
+            Analysis::ProcessData(process_data) => self.process_data = Some(process_data),
+            Analysis::Dc(dc) => self.dc = Some(dc),
+            Analysis::ErrorCounters(error_counters) => self.error_counters = Some(error_counters),
+        }
+    }
 }
 
+/// SubDevices found in a capture.
 #[derive(Debug, Clone)]
-pub struct DumpFileStats {
-    pub std_dev: f64,
-    pub min: f64,
-    pub max: f64,
-    pub mean: f64,
-    pub variance: f64,
-    pub p25: f64,
-    pub p50: f64,
-    pub p90: f64,
-    pub p99: f64,
+pub struct SubDevices {
+    pub topology: Topology,
+    pub identities: Identities,
 }
 
-impl DumpFileStats {
-    pub fn new(data: &[[f64; 2]]) -> Self {
-        let values = data.iter().map(|[_x, y]| y);
-
-        let mut d: Data<Vec<f64>> = Data::new(values.clone().copied().collect());
-
-        let std_dev = values.clone().std_dev();
-        let variance = values.clone().variance();
-        let p25 = d.percentile(25);
-        let p50 = d.percentile(50);
-        let p90 = d.percentile(90);
-        let p99 = d.percentile(99);
-
-        let mut min = f64::MAX;
-        let mut max = 0.0f64;
-        let mut sum = 0.0;
-        let mut count = 0.0;
-
-        for value in values {
-            min = min.min(*value);
-            max = max.max(*value);
-            sum += value;
-            count += 1.0;
-        }
+/// Decoded process image of a capture.
+#[derive(Debug, Clone)]
+pub struct ProcessData {
+    pub series: BTreeMap<Signal, TimeSeries>,
+    pub events: Vec<ChangeEvent>,
+}
 
-        let mean = sum / count;
+/// An analysis of a capture built on demand.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnalysisKind {
+    SubDevices,
+    Sdo,
+    ProcessData,
+    Dc,
+    ErrorCounters,
+}
 
-        Self {
-            std_dev,
-            min,
-            max,
-            mean,
-            variance,
-            p25,
-            p50,
-            p90,
-            p99,
+/// Result of an [`AnalysisKind`].
+#[derive(Debug, Clone)]
+pub enum Analysis {
+    SubDevices(SubDevices),
+    Sdo(Vec<SdoTransaction>),
+    ProcessData(ProcessData),
+    Dc(DcAnalysis),
+    ErrorCounters(ErrorCounters),
+}
+
+impl Analysis {
+    /// Build an analysis from every exchange of a capture.
+    pub fn new(kind: AnalysisKind, path: &Path) -> Self {
+        let exchanges = PcapFile::new(path).exchanges();
+
+        match kind {
+            AnalysisKind::SubDevices => {
+                let mut topology = Topology::default();
+                let mut identities = Identities::default();
+
+                for exchange in exchanges {
+                    topology.push(&exchange);
+                    identities.push(&exchange);
+                }
+
+                Self::SubDevices(SubDevices {
+                    topology,
+                    identities,
+                })
+            }
+            AnalysisKind::Sdo => {
+                let mut sdo = SdoReader::default();
+                let mut transactions = Vec::new();
+
+                for exchange in exchanges {
+                    transactions.extend(sdo.push(&exchange));
+                }
+
+                transactions.extend(sdo.finish());
+
+                Self::Sdo(transactions)
+            }
+            AnalysisKind::ProcessData => {
+                let series = ProcessImage::new(exchanges).series();
+                let events = change_events(&series);
+
+                Self::ProcessData(ProcessData { series, events })
+            }
+            AnalysisKind::Dc => {
+                let mut dc = DcAnalysis::default();
+
+                for exchange in exchanges {
+                    dc.push(&exchange);
+                }
+
+                Self::Dc(dc)
+            }
+            AnalysisKind::ErrorCounters => {
+                let mut error_counters = ErrorCounters::default();
+
+                for exchange in exchanges {
+                    error_counters.push(&exchange);
+                }
+
+                Self::ErrorCounters(error_counters)
+            }
         }
     }
 }
@@ -85,6 +194,10 @@ impl DumpFileStats {
 #[derive(Default, Clone)]
 pub struct DumpFiles {
     pub names: BTreeMap<PathBuf, DumpFile>,
+
+    /// Round trip time significance tests, keyed by reference capture then compared capture.
+    /// Tests are dropped when either capture is removed or reloaded.
+    pub significance: BTreeMap<PathBuf, BTreeMap<PathBuf, Option<Comparison>>>,
 }
 
 impl DumpFiles {
@@ -93,6 +206,7 @@ impl DumpFiles {
 
         let mut self_ = Self {
             names: BTreeMap::new(),
+            significance: BTreeMap::new(),
         };
 
         let paths = fs::read_dir(path)
@@ -110,86 +224,93 @@ impl DumpFiles {
     }
 
     pub fn update_items(&mut self, paths: Vec<PathBuf>) {
-        let scratch = RwLock::new(Vec::with_capacity(paths.len()));
+        let new = self.unloaded(paths);
 
-        let paths = paths
+        self.insert_items(Self::load(&new));
+    }
+
+    /// Captures among `paths` which haven't been loaded yet.
+    pub fn unloaded(&self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        paths
             .into_iter()
             .filter(|path| {
-                path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("pcapng")
+                path.is_file()
+                    && path.extension().and_then(|s| s.to_str()) == Some("pcapng")
+                    && !self.names.contains_key(path)
             })
-            .collect::<HashSet<_>>();
+            .collect()
+    }
 
-        let n2 = self.names.keys().cloned().collect::<HashSet<_>>();
+    /// Parse captures in parallel. This doesn't need the [`DumpFiles`], so can run without holding
+    /// its lock.
+    pub fn load(paths: &[PathBuf]) -> Vec<DumpFile> {
+        thread::scope(|s| {
+            let handles = paths
+                .iter()
+                .map(|path| s.spawn(|| DumpFile::new(path)))
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Load capture"))
+                .collect()
+        })
+    }
 
-        let new = paths.difference(&n2);
+    pub fn insert_items(&mut self, items: Vec<DumpFile>) {
+        let paths = items
+            .iter()
+            .map(|item| item.path.clone())
+            .collect::<HashSet<_>>();
 
-        thread::scope(|s| {
-            for path in new.into_iter() {
-                s.spawn(|| {
-                    let mut capture = PcapFile::new(path);
-
-                    let pairs = capture.match_tx_rx();
-
-                    let round_trip_times = pairs
-                        .iter()
-                        .enumerate()
-                        .map(|(i, item)| [i as f64, item.delta_time.as_nanos() as f64 / 1000.0])
-                        .collect::<Vec<_>>();
-
-                    let cycle_delta_times = pairs
-                        .windows(2)
-                        .into_iter()
-                        .enumerate()
-                        .map(|(i, stats)| {
-                            let [prev, curr] = stats else { unreachable!() };
-
-                            let t = curr.tx_time.as_nanos() - prev.tx_time.as_nanos();
-
-                            [i as f64, t as f64 / 1000.0]
-                        })
-                        .collect::<Vec<_>>();
-
-                    let round_trip_stats = DumpFileStats::new(&round_trip_times);
-                    let cycle_delta_stats = DumpFileStats::new(&cycle_delta_times);
-
-                    let mut round_trip_histo = Histogram::new(3).expect("Histo");
-
-                    for [_x, y] in round_trip_times.iter() {
-                        round_trip_histo.record(*y as u64).ok();
-                    }
-
-                    let mut cycle_delta_histo = Histogram::new(3).expect("Histo");
-
-                    for [_x, y] in cycle_delta_times.iter() {
-                        cycle_delta_histo.record(*y as u64).ok();
-                    }
-
-                    scratch.write().push(DumpFile {
-                        round_trip_stats,
-                        cycle_delta_stats,
-                        round_trip_histo,
-                        cycle_delta_histo,
-                        selected: false,
-                        path: path.clone(),
-                        display_name: path.file_stem().unwrap().to_string_lossy().to_string(),
-                        round_trip_times,
-                        cycle_delta_times,
-                        num_points: pairs.len(),
-                        cpu: capture.cpu,
-                        os: capture.os,
-                    });
-                });
-            }
-        });
+        self.forget_significance(&paths);
 
-        for new in scratch.into_inner().into_iter() {
-            self.names.insert(new.path.clone(), new);
+        for item in items {
+            self.names.insert(item.path.clone(), item);
         }
     }
 
     pub fn remove_items(&mut self, remove: Vec<PathBuf>) {
-        for path in remove.into_iter() {
-            self.names.remove(&path);
+        let remove = remove.into_iter().collect::<HashSet<_>>();
+
+        self.forget_significance(&remove);
+
+        for path in remove.iter() {
+            self.names.remove(path);
+        }
+    }
+
+    /// Store the result of a significance test, unless either capture was removed while it ran.
+    pub fn insert_significance(
+        &mut self,
+        reference: PathBuf,
+        item: PathBuf,
+        comparison: Option<Comparison>,
+    ) {
+        if !self.names.contains_key(&reference) || !self.names.contains_key(&item) {
+            return;
+        }
+
+        self.significance
+            .entry(reference)
+            .or_default()
+            .insert(item, comparison);
+    }
+
+    /// Store an analysis built in the background, unless its capture was removed while it ran.
+    pub fn insert_analysis(&mut self, path: &Path, analysis: Analysis) {
+        if let Some(item) = self.names.get_mut(path) {
+            item.insert_analysis(analysis);
+        }
+    }
+
+    /// Drop significance tests involving any of the given captures.
+    fn forget_significance(&mut self, paths: &HashSet<PathBuf>) {
+        self.significance
+            .retain(|reference, _| !paths.contains(reference));
+
+        for items in self.significance.values_mut() {
+            items.retain(|item, _| !paths.contains(item));
         }
     }
 
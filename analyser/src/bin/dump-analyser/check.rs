@@ -0,0 +1,326 @@
+//! Check a capture's round trip and cycle delta times against a stored baseline, e.g. to gate
+//! merges on latency benchmarks in CI.
+//!
+//! The baseline is a JSON file holding the statistics of a known good capture along with the
+//! largest allowed increase of each checked metric:
+//!
+//! ```json
+//! {
+//!   "scenario": "...",
+//!   "round_trip_us": { "count": 1753, "mean": 239.881, ... },
+//!   "cycle_delta_us": { ... },
+//!   "thresholds": {
+//!     "round_trip_us": { "mean": { "relative": 0.1 }, "p99": { "absolute": 5.0 } },
+//!     "cycle_delta_us": { "p99": { "relative": 0.1 } }
+//!   }
+//! }
+//! ```
+
+use crate::{Cli, Format, Global, Input};
+use clap::{error::ErrorKind, CommandFactory};
+use dump_analyser::stats::{self, DumpFileStats, Metric, DEFAULT_PERCENTILES};
+use std::{collections::BTreeMap, fs, path::Path, path::PathBuf};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Baseline JSON file to check captures against.
+    #[arg(long)]
+    pub baseline: PathBuf,
+
+    /// Write the capture's statistics to the baseline file instead of checking them. Only one
+    /// capture can be given.
+    ///
+    /// Thresholds already in the file are kept, otherwise a default set is written.
+    #[arg(long)]
+    pub write_baseline: bool,
+}
+
+/// Largest allowed increase of a metric. A metric regresses if it exceeds any given limit.
+#[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
+struct Threshold {
+    /// Largest increase in microseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    absolute: Option<f64>,
+
+    /// Largest increase as a fraction of the baseline value, e.g. `0.1` for 10%.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    relative: Option<f64>,
+}
+
+impl Threshold {
+    /// Largest allowed value given the baseline value.
+    fn limit(&self, baseline: f64) -> f64 {
+        let absolute = self.absolute.map(|absolute| baseline + absolute);
+        let relative = self
+            .relative
+            .map(|relative| baseline + baseline.abs() * relative);
+
+        absolute
+            .into_iter()
+            .chain(relative)
+            .reduce(f64::min)
+            .unwrap_or(f64::INFINITY)
+    }
+}
+
+/// Thresholds of a single series, keyed by metric name, e.g. `p99`.
+type Thresholds = BTreeMap<String, Threshold>;
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+struct SeriesThresholds {
+    #[serde(default)]
+    round_trip_us: Thresholds,
+
+    #[serde(default)]
+    cycle_delta_us: Thresholds,
+}
+
+impl SeriesThresholds {
+    fn defaults() -> Self {
+        let relative = |relative| Threshold {
+            absolute: None,
+            relative: Some(relative),
+        };
+
+        Self {
+            round_trip_us: [("mean", relative(0.1)), ("p99", relative(0.1))]
+                .map(|(metric, threshold)| (metric.to_string(), threshold))
+                .into(),
+            cycle_delta_us: [("p99", relative(0.1))]
+                .map(|(metric, threshold)| (metric.to_string(), threshold))
+                .into(),
+        }
+    }
+
+    /// Percentiles needed to check every threshold.
+    fn percentiles(&self) -> Vec<f64> {
+        let mut percentiles = DEFAULT_PERCENTILES.to_vec();
+
+        for metric in self.round_trip_us.keys().chain(self.cycle_delta_us.keys()) {
+            if let Ok(Metric::Percentile(percentile)) = metric.parse() {
+                if !percentiles.contains(&percentile) {
+                    percentiles.push(percentile);
+                }
+            }
+        }
+
+        percentiles
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Baseline {
+    scenario: String,
+    round_trip_us: DumpFileStats,
+    cycle_delta_us: DumpFileStats,
+
+    #[serde(default)]
+    thresholds: SeriesThresholds,
+}
+
+/// Result of checking a single metric.
+#[derive(Debug, serde::Serialize)]
+struct Check {
+    series: &'static str,
+    metric: String,
+    baseline: f64,
+    value: f64,
+    limit: f64,
+    pass: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Report {
+    file: PathBuf,
+    baseline: PathBuf,
+    pass: bool,
+    checks: Vec<Check>,
+}
+
+/// Exit with a usage error. This uses a different exit code to a failed check, so a broken CI
+/// setup isn't mistaken for a regression.
+fn usage_error(kind: ErrorKind, message: String) -> ! {
+    Cli::command().error(kind, message).exit()
+}
+
+fn check(
+    series: &'static str,
+    thresholds: &Thresholds,
+    baseline: &DumpFileStats,
+    current: &DumpFileStats,
+) -> Vec<Check> {
+    thresholds
+        .iter()
+        .map(|(name, threshold)| {
+            let metric = name.parse::<Metric>().unwrap_or_else(|e| {
+                usage_error(
+                    ErrorKind::InvalidValue,
+                    format!("Invalid metric in baseline thresholds: {}", e),
+                )
+            });
+
+            let baseline = baseline.metric(metric).unwrap_or_else(|| {
+                usage_error(
+                    ErrorKind::InvalidValue,
+                    format!(
+                        "Baseline has no {} for {}, rewrite it with --write-baseline",
+                        metric, series
+                    ),
+                )
+            });
+            let value = current.metric(metric).expect("Percentile not computed");
+            let limit = threshold.limit(baseline);
+
+            Check {
+                series,
+                metric: metric.to_string(),
+                baseline,
+                value,
+                limit,
+                // NaN (no PDUs matched) always fails
+                pass: value <= limit,
+            }
+        })
+        .collect()
+}
+
+/// Returns whether every metric is within its threshold.
+pub fn run(args: &Args, global: &Global, file: &Path) -> bool {
+    global.require_format(&[Format::Text, Format::Json]);
+
+    if args.write_baseline && args.input.files.len() > 1 {
+        usage_error(
+            ErrorKind::ArgumentConflict,
+            "--write-baseline takes a single capture".to_string(),
+        );
+    }
+
+    let existing = fs::read_to_string(&args.baseline).ok().map(|json| {
+        serde_json::from_str::<Baseline>(&json).unwrap_or_else(|e| {
+            usage_error(
+                ErrorKind::InvalidValue,
+                format!("Invalid baseline {:?}: {}", args.baseline, e),
+            )
+        })
+    });
+
+    if existing.is_none() && !args.write_baseline {
+        usage_error(
+            ErrorKind::InvalidValue,
+            format!(
+                "Baseline {:?} not found, create it with --write-baseline",
+                args.baseline
+            ),
+        );
+    }
+
+    let thresholds = existing
+        .as_ref()
+        .map(|baseline| baseline.thresholds.clone())
+        .unwrap_or_else(SeriesThresholds::defaults);
+
+    let percentiles = thresholds.percentiles();
+
+    let pairs = global.pdu_stats(file);
+
+    let current = Baseline {
+        scenario: global.open(file).scenario,
+        round_trip_us: DumpFileStats::with_percentiles(
+            &stats::round_trip_times(&pairs),
+            &percentiles,
+        ),
+        cycle_delta_us: DumpFileStats::with_percentiles(
+            &stats::cycle_delta_times(&pairs),
+            &percentiles,
+        ),
+        thresholds,
+    };
+
+    if args.write_baseline {
+        // Statistics of no times are NaN, which is written as `null` and can't be read back
+        if current.round_trip_us.count == 0 || current.cycle_delta_us.count == 0 {
+            usage_error(
+                ErrorKind::InvalidValue,
+                format!(
+                    "No matched PDUs in {:?}, refusing to write an empty baseline",
+                    file
+                ),
+            );
+        }
+
+        fs::write(
+            &args.baseline,
+            serde_json::to_string_pretty(&current).expect("Serialize"),
+        )
+        .expect("Unable to write baseline");
+
+        log::info!("Wrote baseline {:?}", args.baseline);
+
+        return true;
+    }
+
+    let baseline = existing.expect("Baseline");
+
+    let checks = check(
+        "round_trip_us",
+        &baseline.thresholds.round_trip_us,
+        &baseline.round_trip_us,
+        &current.round_trip_us,
+    )
+    .into_iter()
+    .chain(check(
+        "cycle_delta_us",
+        &baseline.thresholds.cycle_delta_us,
+        &baseline.cycle_delta_us,
+        &current.cycle_delta_us,
+    ))
+    .collect::<Vec<_>>();
+
+    let report = Report {
+        file: file.to_path_buf(),
+        baseline: args.baseline.clone(),
+        pass: checks.iter().all(|check| check.pass),
+        checks,
+    };
+
+    if global.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Serialize")
+        );
+
+        return report.pass;
+    }
+
+    println!(
+        "{:<16} {:>8} {:>12} {:>12} {:>9} {:>12} {:>6}",
+        "Series", "Metric", "Baseline", "Value", "Change", "Limit", "Result"
+    );
+
+    for check in report.checks.iter() {
+        println!(
+            "{:<16} {:>8} {:>12.3} {:>12.3} {:>+8.1}% {:>12.3} {:>6}",
+            check.series,
+            check.metric,
+            check.baseline,
+            check.value,
+            (check.value - check.baseline) / check.baseline.abs() * 100.0,
+            check.limit,
+            if check.pass { "PASS" } else { "FAIL" }
+        );
+    }
+
+    println!();
+    println!(
+        "{}: {} against baseline {} ({})",
+        if report.pass { "PASS" } else { "FAIL" },
+        file.display(),
+        args.baseline.display(),
+        baseline.scenario
+    );
+
+    report.pass
+}
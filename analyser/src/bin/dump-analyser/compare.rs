@@ -0,0 +1,491 @@
+//! Compare round trip or cycle delta time statistics across many captures, e.g. to compare
+//! kernels, NICs or tuning settings.
+
+use crate::{Format, Global};
+use clap::ValueEnum;
+use dump_analyser::{
+    significance::{Bootstrap, Comparison},
+    stats::{self, DumpFileStats, Metric, DEFAULT_PERCENTILES},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Capture files, directories of captures, or file name patterns using `*` and `?`, e.g.
+    /// `dumps/*-rt.pcapng`.
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+
+    /// Which times to compare.
+    #[arg(long, value_enum, default_value_t = Series::RoundTrip)]
+    pub series: Series,
+
+    /// Percentiles to compute, from 0 to 100.
+    #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_PERCENTILES)]
+    pub percentiles: Vec<f64>,
+
+    /// Sort captures by this metric instead of by name, e.g. `mean`, `std_dev` or `p99`.
+    #[arg(long)]
+    pub sort: Option<Metric>,
+
+    /// Sort from highest to lowest.
+    #[arg(long)]
+    pub descending: bool,
+
+    /// Test whether each capture differs significantly from the reference capture.
+    ///
+    /// Adds Mann-Whitney U and Kolmogorov-Smirnov p-values, and a bootstrap confidence interval of
+    /// the difference of `--ci-percentile`.
+    #[arg(long)]
+    pub significance: bool,
+
+    /// Capture to test every other capture against. Defaults to the first capture in the table.
+    #[arg(long, requires = "significance")]
+    pub reference: Option<PathBuf>,
+
+    /// Percentile to compute a confidence interval of the difference for.
+    #[arg(long, default_value_t = 99.0, requires = "significance")]
+    pub ci_percentile: f64,
+
+    /// Confidence level of the bootstrap interval, from 0 to 1.
+    #[arg(long, default_value_t = 0.95, requires = "significance")]
+    pub confidence: f64,
+
+    /// Number of bootstrap resamples.
+    #[arg(long, default_value_t = 1000, requires = "significance")]
+    pub resamples: usize,
+}
+
+/// A series of times to compare captures by.
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Series {
+    /// Time between a PDU being sent and its response being received.
+    RoundTrip,
+
+    /// Time between a PDU being sent and the next.
+    CycleDelta,
+}
+
+/// A single capture in the comparison.
+#[derive(Debug, serde::Serialize)]
+struct Row {
+    /// Capture file name without extension, as shown in the GUI.
+    name: String,
+    path: PathBuf,
+    cpu: String,
+    os: String,
+    if_name: String,
+
+    /// Statistics of the selected series in microseconds.
+    stats: DumpFileStats,
+
+    /// Significance of the difference to the reference capture, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    significance: Option<Comparison>,
+
+    /// Every time of the selected series, only kept for `--significance`.
+    #[serde(skip)]
+    times: Vec<f64>,
+}
+
+/// Expand directories and file name patterns to a sorted list of capture files.
+fn expand(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            files.extend(
+                fs::read_dir(path)
+                    .expect("read_dir")
+                    .map(|entry| entry.expect("Entry").path())
+                    .filter(|path| {
+                        path.is_file()
+                            && path.extension().and_then(|s| s.to_str()) == Some("pcapng")
+                    }),
+            );
+
+            continue;
+        }
+
+        let pattern = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+
+        if !pattern.contains(['*', '?']) {
+            files.push(path.clone());
+
+            continue;
+        }
+
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        files.extend(
+            fs::read_dir(dir)
+                .expect("read_dir")
+                .map(|entry| entry.expect("Entry").path())
+                .filter(|path| {
+                    path.is_file()
+                        && path.file_name().is_some_and(|name| {
+                            wildcard_match(pattern.as_bytes(), name.to_string_lossy().as_bytes())
+                        })
+                }),
+        );
+    }
+
+    files.sort();
+    files.dedup();
+
+    files
+}
+
+/// Match a file name against a pattern where `*` matches any run of characters and `?` matches
+/// any single character.
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            wildcard_match(rest, name) || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some((b'?', rest)), Some((_, name))) => wildcard_match(rest, name),
+        (Some((p, rest)), Some((n, name))) if p == n => wildcard_match(rest, name),
+        _ => false,
+    }
+}
+
+fn load(path: &Path, args: &Args, global: &Global, percentiles: &[f64]) -> Row {
+    log::info!("Analysing {:?}", path);
+
+    let mut capture = global.open(path);
+    let pairs = global.capture_pdu_stats(&mut capture);
+
+    let times = match args.series {
+        Series::RoundTrip => stats::round_trip_times(&pairs),
+        Series::CycleDelta => stats::cycle_delta_times(&pairs),
+    };
+
+    Row {
+        name: path.file_stem().unwrap().to_string_lossy().to_string(),
+        path: path.to_path_buf(),
+        cpu: capture.cpu,
+        os: capture.os,
+        if_name: capture.if_name,
+        stats: DumpFileStats::with_percentiles(&times, percentiles),
+        significance: None,
+        times: if args.significance {
+            times.into_iter().map(|[_x, y]| y).collect()
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// Call `f` on every item from a pool of one thread per CPU, returning the results in order.
+fn parallel_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(items.len());
+
+    let next = AtomicUsize::new(0);
+
+    let mut results = thread::scope(|s| {
+        let handles = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut results = Vec::new();
+
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+
+                        let Some(item) = items.get(idx) else {
+                            break;
+                        };
+
+                        results.push((idx, f(item)));
+                    }
+
+                    results
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Worker thread panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_by_key(|(idx, _)| *idx);
+
+    results.into_iter().map(|(_idx, result)| result).collect()
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn run(args: &Args, global: &Global) {
+    let files = expand(&args.paths);
+
+    if files.is_empty() {
+        log::warn!("No capture files found");
+
+        return;
+    }
+
+    let mut percentiles = args.percentiles.clone();
+
+    if let Some(Metric::Percentile(sort)) = args.sort {
+        if !percentiles.contains(&sort) {
+            percentiles.push(sort);
+        }
+    }
+
+    let mut rows = parallel_map(&files, |path| load(path, args, global, &percentiles));
+
+    if let Some(metric) = args.sort {
+        // NaN (empty captures) sort last
+        rows.sort_by(|a, b| {
+            let a = a.stats.metric(metric).unwrap_or(f64::NAN);
+            let b = b.stats.metric(metric).unwrap_or(f64::NAN);
+
+            match (a.is_nan(), b.is_nan()) {
+                (true, true) => std::cmp::Ordering::Equal,
+                (true, false) => std::cmp::Ordering::Greater,
+                (false, true) => std::cmp::Ordering::Less,
+                (false, false) if args.descending => b.total_cmp(&a),
+                (false, false) => a.total_cmp(&b),
+            }
+        });
+    } else if args.descending {
+        rows.reverse();
+    }
+
+    if args.significance {
+        let reference = match &args.reference {
+            Some(reference) => rows
+                .iter()
+                .position(|row| row.path == *reference)
+                .unwrap_or_else(|| {
+                    log::warn!("Reference {:?} isn't being compared, loading it", reference);
+
+                    let row = load(reference, args, global, &percentiles);
+
+                    rows.insert(0, row);
+
+                    0
+                }),
+            None => 0,
+        };
+
+        let bootstrap = Bootstrap {
+            resamples: args.resamples,
+            confidence: args.confidence,
+            ..Bootstrap::default()
+        };
+
+        let reference = &rows[reference];
+
+        let significance = parallel_map(&rows, |row| {
+            if row.path == reference.path {
+                return None;
+            }
+
+            Comparison::new(&reference.times, &row.times, args.ci_percentile, &bootstrap)
+        });
+
+        for (row, significance) in rows.iter_mut().zip(significance) {
+            row.significance = significance;
+        }
+    }
+
+    if global.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&rows).expect("Serialize")
+        );
+
+        return;
+    }
+
+    let mut header = vec![
+        "File".to_string(),
+        "Count".to_string(),
+        "Std. Dev.".to_string(),
+        "Variance".to_string(),
+    ];
+
+    header.extend(percentiles.iter().map(|p| format!("P{}", p)));
+    header.extend(["Min", "Mean", "Max"].map(String::from));
+
+    if args.significance {
+        header.extend([
+            "MW p".to_string(),
+            "KS p".to_string(),
+            format!("ΔP{} ({}% CI)", args.ci_percentile, args.confidence * 100.0),
+        ]);
+    }
+
+    header.extend(["CPU", "OS"].map(String::from));
+
+    // CSV keeps full precision, everything else is rounded for display
+    let number = |value: f64| {
+        if global.format == Format::Csv {
+            value.to_string()
+        } else {
+            format!("{:.3}", value)
+        }
+    };
+
+    let table = rows
+        .iter()
+        .map(|row| {
+            let mut cells = vec![
+                row.name.clone(),
+                row.stats.count.to_string(),
+                number(row.stats.std_dev),
+                number(row.stats.variance),
+            ];
+
+            cells.extend(row.stats.percentiles.iter().map(|p| number(p.value)));
+            cells.extend([
+                number(row.stats.min),
+                number(row.stats.mean),
+                number(row.stats.max),
+            ]);
+
+            if args.significance {
+                match &row.significance {
+                    Some(significance) => {
+                        let difference = significance.percentile_difference;
+
+                        cells.extend([
+                            format!("{:.3e}", significance.mann_whitney_u.p_value),
+                            format!("{:.3e}", significance.kolmogorov_smirnov.p_value),
+                            format!(
+                                "{} [{}, {}]",
+                                number(difference.estimate),
+                                number(difference.lower),
+                                number(difference.upper)
+                            ),
+                        ]);
+                    }
+                    None => cells.extend(["-", "-", "-"].map(String::from)),
+                }
+            }
+
+            cells.extend([row.cpu.clone(), row.os.clone()]);
+
+            cells
+        })
+        .collect::<Vec<_>>();
+
+    match global.format {
+        Format::Text => {
+            let widths = (0..header.len())
+                .map(|col| {
+                    table
+                        .iter()
+                        .map(|cells| cells[col].chars().count())
+                        .chain([header[col].chars().count()])
+                        .max()
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+
+            for cells in [&header].into_iter().chain(table.iter()) {
+                let line = cells
+                    .iter()
+                    .zip(widths.iter())
+                    .enumerate()
+                    .map(|(col, (cell, width))| {
+                        // Numbers are right aligned, names and metadata left aligned
+                        if col == 0 || col >= header.len() - 2 {
+                            format!("{:<width$}", cell, width = width)
+                        } else {
+                            format!("{:>width$}", cell, width = width)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  ");
+
+                println!("{}", line.trim_end());
+            }
+        }
+        Format::Csv => {
+            let mut wtr = csv::Writer::from_writer(std::io::stdout());
+
+            wtr.write_record(&header).expect("Write");
+
+            for cells in table.iter() {
+                wtr.write_record(cells).expect("Write");
+            }
+
+            wtr.flush().expect("Flush");
+        }
+        Format::Markdown => {
+            let row = |cells: &[String]| {
+                let cells = cells
+                    .iter()
+                    .map(|cell| cell.replace('|', "\\|"))
+                    .collect::<Vec<_>>();
+
+                format!("| {} |", cells.join(" | "))
+            };
+
+            println!("{}", row(&header));
+            println!(
+                "|{}",
+                (0..header.len())
+                    .map(|col| if col == 0 || col >= header.len() - 2 {
+                        " --- |"
+                    } else {
+                        " ---: |"
+                    })
+                    .collect::<String>()
+            );
+
+            for cells in table.iter() {
+                println!("{}", row(cells));
+            }
+        }
+        Format::Html => {
+            println!("<table>");
+            println!("  <thead>");
+            println!(
+                "    <tr>{}</tr>",
+                header
+                    .iter()
+                    .map(|cell| format!("<th>{}</th>", escape_html(cell)))
+                    .collect::<String>()
+            );
+            println!("  </thead>");
+            println!("  <tbody>");
+
+            for cells in table.iter() {
+                println!(
+                    "    <tr>{}</tr>",
+                    cells
+                        .iter()
+                        .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+                        .collect::<String>()
+                );
+            }
+
+            println!("  </tbody>");
+            println!("</table>");
+        }
+        Format::Json => unreachable!(),
+    }
+}
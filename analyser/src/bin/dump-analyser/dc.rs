@@ -0,0 +1,100 @@
+//! Print the Distributed Clocks reference time drift and sync configuration found in a given
+//! Wireshark capture file.
+
+use crate::{Format, Global, Input};
+use dump_analyser::dc::DcAnalysis;
+use std::path::Path;
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+}
+
+/// Format an optional field, or `-` if it wasn't captured.
+fn field<T: std::fmt::Display>(value: Option<T>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// With `--format csv`, every reference time read is printed instead of a summary.
+pub fn run(_args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Csv]);
+
+    let mut dc = DcAnalysis::new(global.exchanges(file));
+
+    dc.times.retain(|time| global.in_window(time.host_time));
+
+    let offsets = dc.offsets();
+
+    if global.format == Format::Csv {
+        println!("packet_number,host_time_ns,dc_time_ns,offset_ns");
+
+        for (time, [_, offset]) in dc.times.iter().zip(offsets) {
+            println!(
+                "{},{},{},{}",
+                time.packet_number,
+                time.host_time.as_nanos(),
+                time.dc_time,
+                offset
+            );
+        }
+
+        return;
+    }
+
+    match dc.reference {
+        Some(reference) => println!("Reference clock {:#06x}", reference),
+        None => println!("No reference clock FRMW found in capture"),
+    }
+
+    println!("{} reference time reads", dc.times.len());
+
+    if let (Some(first), Some(last)) = (offsets.first(), offsets.last()) {
+        println!(
+            "Offset change over {:.3} s: {} ns",
+            last[0] - first[0],
+            last[1]
+        );
+    }
+
+    if let Some(drift) = dc.drift_ppm() {
+        println!("Drift relative to host clock: {:+.3} ppm", drift);
+    }
+
+    println!();
+
+    println!(
+        "{:>8} {:>20} {:>10} {:>10} {:>20} {:>12} {:>12}",
+        "Address",
+        "Offset (ns)",
+        "Delay (ns)",
+        "Activation",
+        "Start time",
+        "SYNC0 (ns)",
+        "SYNC1 (ns)"
+    );
+
+    for (address, config) in dc.configs.iter() {
+        // Skip SubDevices where no DC registers were accessed
+        if *config == Default::default() {
+            continue;
+        }
+
+        println!(
+            "{:>#8x} {:>20} {:>10} {:>10} {:>20} {:>12} {:>12}",
+            address,
+            field(config.system_time_offset),
+            field(config.system_time_delay),
+            field(
+                config
+                    .sync_activation
+                    .map(|activation| format!("{:#04x}", activation))
+            ),
+            field(config.sync0_start_time),
+            field(config.sync0_cycle_time),
+            field(config.sync1_cycle_time)
+        );
+    }
+}
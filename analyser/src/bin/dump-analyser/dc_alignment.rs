@@ -0,0 +1,118 @@
+//! Report where the host sent each cycle's process data relative to the DC SYNC0 edge.
+//!
+//! The SYNC0 grid is taken from the start time (`0x0990`) and cycle time (`0x09A0`) written to each
+//! SubDevice. Captures without DC configuration can give the grid on the command line instead.
+
+use crate::{Format, Global, Input};
+use dump_analyser::dc::DcAnalysis;
+use std::path::Path;
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// SYNC0 cycle time in nanoseconds to use instead of the captured configuration.
+    #[arg(long)]
+    pub cycle_time: Option<u32>,
+
+    /// SYNC0 start time in DC system time nanoseconds to use with `--cycle-time`.
+    #[arg(long, default_value_t = 0, requires = "cycle_time")]
+    pub start_time: u64,
+}
+
+/// With `--format csv`, every cycle is printed instead of a summary.
+pub fn run(args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Csv]);
+
+    let dc = DcAnalysis::new(global.exchanges(file));
+
+    // SYNC0 grid of each SubDevice, or the one given on the command line.
+    let grids = match args.cycle_time {
+        Some(cycle_time) => vec![("command line".to_string(), args.start_time, cycle_time)],
+        None => dc
+            .configs
+            .iter()
+            .filter_map(|(address, config)| {
+                Some((
+                    format!("{:#06x}", address),
+                    config.sync0_start_time?,
+                    config
+                        .sync0_cycle_time
+                        .filter(|cycle_time| *cycle_time > 0)?,
+                ))
+            })
+            .collect(),
+    };
+
+    if grids.is_empty() {
+        log::warn!("No SYNC0 configuration found in capture, use --cycle-time to give one");
+
+        return;
+    }
+
+    if global.format == Format::Csv {
+        println!("sync0,packet_number,host_time_ns,dc_time_ns,phase_ns");
+    }
+
+    for (name, start_time, cycle_time) in grids {
+        let cycles = dc
+            .alignment(start_time, cycle_time)
+            .into_iter()
+            .filter(|cycle| global.in_window(cycle.host_time))
+            .collect::<Vec<_>>();
+
+        if global.format == Format::Csv {
+            for cycle in cycles {
+                println!(
+                    "{},{},{},{},{}",
+                    name,
+                    cycle.packet_number,
+                    cycle.host_time.as_nanos(),
+                    cycle.dc_time,
+                    cycle.phase
+                );
+            }
+
+            continue;
+        }
+
+        println!(
+            "SYNC0 of {}: start time {} ns, cycle time {} ns",
+            name, start_time, cycle_time
+        );
+
+        if cycles.is_empty() {
+            println!("  No process data sent after the first reference time read");
+
+            continue;
+        }
+
+        let mut phases = cycles.iter().map(|cycle| cycle.phase).collect::<Vec<_>>();
+
+        phases.sort_unstable();
+
+        let n = phases.len() as f64;
+        let mean = phases.iter().map(|phase| *phase as f64).sum::<f64>() / n;
+        let std_dev = (phases
+            .iter()
+            .map(|phase| (*phase as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n)
+            .sqrt();
+
+        let percentile = |p: f64| phases[((n - 1.0) * p).round() as usize];
+
+        println!("  {} cycles", phases.len());
+        println!(
+            "  Phase from SYNC0 edge (ns): min {}, p1 {}, median {}, p99 {}, max {}",
+            phases[0],
+            percentile(0.01),
+            percentile(0.5),
+            percentile(0.99),
+            phases[phases.len() - 1]
+        );
+        println!("  Mean {:.0} ns, std. dev. {:.0} ns", mean, std_dev);
+        println!();
+    }
+}
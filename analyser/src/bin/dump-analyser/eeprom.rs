@@ -0,0 +1,56 @@
+//! Read EEPROM data from a Wireshark capture.
+//!
+//! This won't be a full dump - it will only be the segments that were actually read during the
+//! capture, but maybe that's enough to aid debugging.
+
+use crate::{Format, Global, Input};
+use dump_analyser::sii::sii_images;
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::Path,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+}
+
+pub fn run(_args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text]);
+
+    // EEPROM maps for each slave, by address
+    let eeprom_images = sii_images(global.exchanges(file));
+
+    // Now write out each device's EEPROM to a file
+
+    let dir = file.parent().unwrap().to_path_buf();
+    let base_file_name = file.file_stem().unwrap().to_string_lossy();
+
+    fs::create_dir_all(&dir).expect("Could not create dumps dir");
+
+    for (device, eeprom) in eeprom_images {
+        let file_name = |suffix: &str| {
+            let mut filename = dir.clone();
+            filename.push(format!("{}-eeprom-{}{}", base_file_name, device, suffix));
+
+            log::info!("Write {}", filename.display());
+
+            filename
+        };
+
+        // Only the captured ranges, so "not read" can be told apart from zeroes read from the
+        // device.
+        fs::write(file_name(".hex"), eeprom.to_intel_hex()).expect("Failed to write Intel HEX");
+
+        // Raw image up to the last captured word. Words that weren't read are zero.
+        let mut f = File::create(file_name(".bin")).expect("Could not open file for writing");
+
+        f.write_all(eeprom.trimmed())
+            .expect("Failed to write EEPROM dump data");
+
+        fs::write(file_name("-coverage.txt"), eeprom.coverage().to_string())
+            .expect("Failed to write coverage report");
+    }
+}
@@ -0,0 +1,102 @@
+//! Summarise the ESC error counters read in a given Wireshark capture file.
+
+use crate::{Format, Global, Input};
+use dump_analyser::error_counters::{link_segment, ErrorCounters};
+use std::path::Path;
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+}
+
+/// Keep only counter reads inside the `--from`/`--to` window, with increments counted from the
+/// start of it.
+fn window(counters: &mut ErrorCounters, global: &Global) {
+    for series in counters.series.values_mut() {
+        let start = series
+            .iter()
+            .position(|sample| global.in_window(sample.time))
+            .unwrap_or(series.len());
+
+        let before = start.checked_sub(1).map_or(0, |idx| series[idx].increments);
+
+        series.retain(|sample| global.in_window(sample.time));
+
+        for sample in series.iter_mut() {
+            sample.increments -= before;
+        }
+    }
+
+    counters.series.retain(|_key, series| !series.is_empty());
+}
+
+/// With `--format csv`, every counter read is printed instead of a summary.
+pub fn run(_args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Csv]);
+
+    let mut counters = ErrorCounters::new(global.exchanges(file));
+
+    window(&mut counters, global);
+
+    if global.format == Format::Csv {
+        println!("address,port,counter,packet_number,time_ns,value,increments");
+
+        for (key, series) in counters.series.iter() {
+            for sample in series {
+                println!(
+                    "{},{},{},{},{},{},{}",
+                    key.address,
+                    key.port.map(|port| port.to_string()).unwrap_or_default(),
+                    key.kind,
+                    sample.packet_number,
+                    sample.time.as_nanos(),
+                    sample.value,
+                    sample.increments
+                );
+            }
+        }
+
+        return;
+    }
+
+    if counters.series.is_empty() {
+        println!("No error counter reads found in capture");
+
+        return;
+    }
+
+    println!(
+        "{:>8} {:>6} {:>22} {:>8} {:>8} {:>12}",
+        "Address", "Port", "Counter", "Reads", "Last", "Increments"
+    );
+
+    for (key, series) in counters.series.iter() {
+        let Some(last) = series.last() else {
+            continue;
+        };
+
+        println!(
+            "{:>#8x} {:>6} {:>22} {:>8} {:>8} {:>12}",
+            key.address,
+            key.port
+                .map(|port| port.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            key.kind,
+            series.len(),
+            last.value,
+            last.increments
+        );
+    }
+
+    println!();
+
+    match counters.most_degraded() {
+        Some((address, port, total)) => println!(
+            "Most degraded link: {}, {} link error increments",
+            link_segment(address, port),
+            total
+        ),
+        None => println!("No link errors counted during capture"),
+    }
+}
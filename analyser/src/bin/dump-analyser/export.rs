@@ -0,0 +1,207 @@
+//! Write PDU statistics, per-cycle metrics and decoded process data to Arrow IPC or Parquet files
+//! next to each capture, for loading into pandas, polars or similar.
+
+use crate::{Format, Global, Input};
+use arrow_array::RecordBatch;
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::SchemaRef;
+use clap::ValueEnum;
+use dump_analyser::{
+    columnar::{self, BATCH_SIZE},
+    process_image::ProcessImage,
+};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Tables to write.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [Table::Pdus, Table::Cycles, Table::ProcessData]
+    )]
+    pub table: Vec<Table>,
+
+    /// File format to write.
+    #[arg(long, value_enum, default_value_t = FileFormat::Parquet)]
+    pub file_format: FileFormat,
+
+    /// Directory to write files to instead of next to each capture.
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+}
+
+/// A table to export, written to `<capture>-<table>.<extension>`.
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Table {
+    /// One row per matched PDU, with the same columns as the `csv` subcommand.
+    Pdus,
+
+    /// One row per frame with its round trip and cycle delta times.
+    Cycles,
+
+    /// One row per sample of every mapped byte and PDO entry of the process image.
+    ProcessData,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FileFormat {
+    /// Snappy compressed Parquet.
+    Parquet,
+
+    /// Arrow IPC file, also known as Feather V2.
+    Arrow,
+}
+
+enum Writer {
+    Parquet(ArrowWriter<File>),
+    Arrow(FileWriter<File>),
+}
+
+impl Writer {
+    fn create(path: &Path, schema: SchemaRef, format: FileFormat) -> Self {
+        let file = File::create(path).expect("Unable to create output file");
+
+        match format {
+            FileFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+
+                Self::Parquet(ArrowWriter::try_new(file, schema, Some(props)).expect("Writer"))
+            }
+            FileFormat::Arrow => Self::Arrow(FileWriter::try_new(file, &schema).expect("Writer")),
+        }
+    }
+
+    fn write(&mut self, batch: &RecordBatch) {
+        match self {
+            Self::Parquet(writer) => writer.write(batch).expect("Write batch"),
+            Self::Arrow(writer) => writer.write(batch).expect("Write batch"),
+        }
+    }
+
+    fn finish(self) {
+        match self {
+            Self::Parquet(writer) => {
+                writer.close().expect("Finish file");
+            }
+            Self::Arrow(mut writer) => writer.finish().expect("Finish file"),
+        }
+    }
+}
+
+pub fn run(args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text]);
+
+    let scenario = global.open(file).scenario;
+    let base_file_name = file.file_stem().unwrap().to_string_lossy();
+    let dir = args
+        .output_dir
+        .clone()
+        .or_else(|| file.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+
+    let extension = match args.file_format {
+        FileFormat::Parquet => "parquet",
+        FileFormat::Arrow => "arrow",
+    };
+
+    let pairs = if args.table.contains(&Table::Pdus) || args.table.contains(&Table::Cycles) {
+        global.pdu_stats(file)
+    } else {
+        Vec::new()
+    };
+
+    for table in args.table.iter() {
+        let name = table
+            .to_possible_value()
+            .expect("Value")
+            .get_name()
+            .to_string();
+        let out_path = dir.join(format!("{}-{}.{}", base_file_name, name, extension));
+
+        let rows = match table {
+            Table::Pdus => {
+                let mut writer =
+                    Writer::create(&out_path, columnar::pdu_stats_schema(), args.file_format);
+
+                let dictionaries = columnar::PduStatsDictionaries::new(&pairs);
+
+                for chunk in pairs.chunks(BATCH_SIZE) {
+                    writer.write(&columnar::pdu_stats(chunk, &dictionaries));
+                }
+
+                writer.finish();
+
+                pairs.len()
+            }
+            Table::Cycles => {
+                let mut writer =
+                    Writer::create(&out_path, columnar::cycles_schema(), args.file_format);
+
+                let scenario = columnar::Dictionary::new(pairs.iter().map(|stat| &stat.scenario));
+
+                for (i, chunk) in pairs.chunks(BATCH_SIZE).enumerate() {
+                    let previous = (i * BATCH_SIZE).checked_sub(1).and_then(|i| pairs.get(i));
+
+                    writer.write(&columnar::cycles(chunk, &scenario, previous));
+                }
+
+                writer.finish();
+
+                pairs.len()
+            }
+            Table::ProcessData => {
+                let mut series = ProcessImage::new(global.exchanges(file)).series();
+
+                for series in series.values_mut() {
+                    series
+                        .samples
+                        .retain(|sample| global.in_window(sample.time));
+                }
+
+                let dictionaries = columnar::ProcessDataDictionaries::new(&scenario, series.keys());
+
+                let mut writer =
+                    Writer::create(&out_path, columnar::process_data_schema(), args.file_format);
+
+                let mut rows = 0;
+                let mut chunk = Vec::with_capacity(BATCH_SIZE);
+
+                for (signal, series) in series.iter() {
+                    for sample in series.samples.iter() {
+                        chunk.push((signal, sample));
+
+                        if chunk.len() == BATCH_SIZE {
+                            writer.write(&columnar::process_data(&scenario, &chunk, &dictionaries));
+
+                            rows += chunk.len();
+                            chunk.clear();
+                        }
+                    }
+                }
+
+                if !chunk.is_empty() {
+                    writer.write(&columnar::process_data(&scenario, &chunk, &dictionaries));
+
+                    rows += chunk.len();
+                }
+
+                writer.finish();
+
+                rows
+            }
+        };
+
+        log::info!("Wrote {} rows to {:?}", rows, out_path);
+    }
+}
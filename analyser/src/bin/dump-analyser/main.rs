@@ -0,0 +1,358 @@
+//! Wireshark EtherCAT dump analyser.
+//!
+//! Every analysis is a subcommand taking one or more capture files. Options shared by all
+//! subcommands, like the output format and time window, can be given before or after the
+//! subcommand name.
+
+mod check;
+mod compare;
+mod dc;
+mod dc_alignment;
+mod eeprom;
+mod error_counters;
+mod export;
+mod pdo;
+mod pdu_csv;
+mod process_data;
+mod register_state;
+mod registers;
+mod sdo;
+mod sii;
+mod sqlite;
+mod stats;
+mod summary;
+mod topology;
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use dump_analyser::{exchange::PduExchange, DirectionDetection, PcapFile, PduStat};
+use env_logger::Env;
+use smoltcp::wire::EthernetAddress;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Wireshark EtherCAT dump analyser.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[command(flatten)]
+    global: Global,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Options shared by every subcommand.
+#[derive(clap::Args, Debug)]
+pub struct Global {
+    /// Output format. Not every subcommand supports every format.
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// Ignore PDUs and events before this many seconds after the start of the capture.
+    ///
+    /// Configuration like the topology and PDO mapping is still taken from the whole capture.
+    #[arg(long, global = true)]
+    pub from: Option<f64>,
+
+    /// Ignore PDUs and events after this many seconds after the start of the capture.
+    #[arg(long, global = true)]
+    pub to: Option<f64>,
+
+    /// Source MAC address of the MainDevice, e.g. `10:10:10:10:10:10`.
+    ///
+    /// By default, frames without the locally administered bit set in their source MAC address are
+    /// taken as sent by the MainDevice.
+    #[arg(long, global = true, value_parser = parse_mac)]
+    pub main_device_mac: Option<EthernetAddress>,
+}
+
+impl Global {
+    /// Open a capture using the global direction detection option.
+    pub fn open(&self, path: &Path) -> PcapFile {
+        let direction = self
+            .main_device_mac
+            .map(DirectionDetection::MainDeviceMac)
+            .unwrap_or_default();
+
+        PcapFile::new(path).with_direction(direction)
+    }
+
+    /// Whether a PDU sent at the given time falls inside the `--from`/`--to` window.
+    pub fn in_window(&self, time: Duration) -> bool {
+        let time = time.as_secs_f64();
+
+        self.from.is_none_or(|from| time >= from) && self.to.is_none_or(|to| time <= to)
+    }
+
+    /// Every exchange in a capture.
+    ///
+    /// Analyses that build up state from the exchanges they see need the whole capture, so the
+    /// `--from`/`--to` window is applied to what they report with [`in_window`](Self::in_window).
+    pub fn exchanges(&self, path: &Path) -> impl Iterator<Item = PduExchange> {
+        self.open(path).exchanges()
+    }
+
+    /// First PDU of each frame matched with its response, inside the `--from`/`--to` window.
+    pub fn pdu_stats(&self, path: &Path) -> Vec<PduStat> {
+        self.capture_pdu_stats(&mut self.open(path))
+    }
+
+    /// Like [`pdu_stats`](Self::pdu_stats), for a capture that's already open.
+    pub fn capture_pdu_stats(&self, capture: &mut PcapFile) -> Vec<PduStat> {
+        capture
+            .match_tx_rx()
+            .into_iter()
+            .filter(|stat| self.in_window(stat.tx_time))
+            .collect()
+    }
+
+    /// Exit with an error if the subcommand doesn't support the selected output format.
+    pub fn require_format(&self, supported: &[Format]) {
+        if supported.contains(&self.format) {
+            return;
+        }
+
+        let supported = supported
+            .iter()
+            .map(|format| {
+                format
+                    .to_possible_value()
+                    .expect("Value")
+                    .get_name()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::InvalidValue,
+                format!(
+                    "--format {} is not supported by this subcommand, use one of: {}",
+                    self.format.to_possible_value().expect("Value").get_name(),
+                    supported
+                ),
+            )
+            .exit()
+    }
+}
+
+/// Output format.
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Human readable report.
+    Text,
+    Csv,
+    Json,
+    Markdown,
+    Html,
+}
+
+/// Capture files to analyse.
+#[derive(clap::Args, Debug)]
+pub struct Input {
+    /// Paths to pcapng files.
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print capture metadata, PDU counts and timing statistics.
+    Summary(summary::Args),
+
+    /// Print round trip and cycle delta time statistics.
+    Stats(stats::Args),
+
+    /// Check timing statistics against a stored baseline, exiting with an error on regression.
+    Check(check::Args),
+
+    /// Compare timing statistics across many captures.
+    Compare(compare::Args),
+
+    /// Write the round trip time and metadata of every PDU to CSV.
+    Csv(pdu_csv::Args),
+
+    /// Write PDU statistics, cycle metrics and process data to Parquet or Arrow IPC files.
+    Export(export::Args),
+
+    /// Import frames, PDUs and matched exchanges into a SQLite database.
+    Sqlite(sqlite::Args),
+
+    /// Trace register reads and writes.
+    Registers(registers::Args),
+
+    /// Dump the last known value of every register of every SubDevice.
+    State(register_state::Args),
+
+    /// Write the captured parts of each SubDevice's EEPROM next to each capture.
+    Eeprom(eeprom::Args),
+
+    /// Decode the captured SII (EEPROM) contents.
+    Sii(sii::Args),
+
+    /// List the SubDevices found in a capture, along with their identities.
+    Topology(topology::Args),
+
+    /// List CoE SDO uploads and downloads.
+    Sdo(sdo::Args),
+
+    /// Print the PDO mapping and FMMU configuration of each SubDevice.
+    Pdo(pdo::Args),
+
+    /// Log every change of the process image.
+    ProcessData(process_data::Args),
+
+    /// Print the Distributed Clocks reference time drift and sync configuration.
+    Dc(dc::Args),
+
+    /// Report where each cycle's process data was sent relative to the SYNC0 edge.
+    DcAlignment(dc_alignment::Args),
+
+    /// Summarise the ESC error counters read during a capture.
+    ErrorCounters(error_counters::Args),
+
+    /// Print a shell completion script.
+    Completions { shell: clap_complete::Shell },
+}
+
+/// Run a subcommand once per input file, with a heading between files if there's more than one.
+///
+/// JSON documents of several files are printed as a single array, in input order. CSV rows don't
+/// say which capture they came from and each file would repeat the header, so `--format csv` only
+/// takes a single file.
+fn for_each_file(input: &Input, global: &Global, mut run: impl FnMut(&Path)) {
+    let several = input.files.len() > 1;
+
+    if several && global.format == Format::Csv {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--format csv takes a single capture file with this subcommand",
+            )
+            .exit()
+    }
+
+    if several && global.format == Format::Json {
+        println!("[");
+    }
+
+    for (idx, file) in input.files.iter().enumerate() {
+        log::info!("Analysing {:?}", file);
+
+        if several && global.format == Format::Text {
+            if idx > 0 {
+                println!();
+            }
+
+            println!("== {} ==", file.display());
+        }
+
+        if several && global.format == Format::Json && idx > 0 {
+            println!(",");
+        }
+
+        run(file);
+    }
+
+    if several && global.format == Format::Json {
+        println!("]");
+    }
+}
+
+fn parse_mac(s: &str) -> Result<EthernetAddress, String> {
+    let bytes = s
+        .split([':', '-'])
+        .map(|byte| u8::from_str_radix(byte, 16).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if bytes.len() != 6 {
+        return Err(format!("expected 6 bytes, got {}", bytes.len()));
+    }
+
+    Ok(EthernetAddress::from_bytes(&bytes))
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+
+    let global = &cli.global;
+
+    match &cli.command {
+        Command::Summary(args) => {
+            for_each_file(&args.input, global, |file| summary::run(args, global, file))
+        }
+        Command::Stats(args) => {
+            if global.format == Format::Csv {
+                // Rows start with the scenario, so every capture can share a single header
+                stats::print_csv_header(args);
+
+                for file in args.input.files.iter() {
+                    log::info!("Analysing {:?}", file);
+
+                    stats::run(args, global, file);
+                }
+            } else {
+                for_each_file(&args.input, global, |file| stats::run(args, global, file))
+            }
+        }
+        Command::Check(args) => {
+            let mut pass = true;
+
+            for_each_file(&args.input, global, |file| {
+                pass &= check::run(args, global, file)
+            });
+
+            if !pass {
+                std::process::exit(1);
+            }
+        }
+        Command::Compare(args) => compare::run(args, global),
+        Command::Csv(args) => pdu_csv::run(args, global),
+        Command::Export(args) => {
+            for_each_file(&args.input, global, |file| export::run(args, global, file))
+        }
+        Command::Sqlite(args) => sqlite::run(args, global),
+        Command::Registers(args) => for_each_file(&args.input, global, |file| {
+            registers::run(args, global, file)
+        }),
+        Command::State(args) => for_each_file(&args.input, global, |file| {
+            register_state::run(args, global, file)
+        }),
+        Command::Eeprom(args) => {
+            for_each_file(&args.input, global, |file| eeprom::run(args, global, file))
+        }
+        Command::Sii(args) => {
+            for_each_file(&args.input, global, |file| sii::run(args, global, file))
+        }
+        Command::Topology(args) => for_each_file(&args.input, global, |file| {
+            topology::run(args, global, file)
+        }),
+        Command::Sdo(args) => {
+            for_each_file(&args.input, global, |file| sdo::run(args, global, file))
+        }
+        Command::Pdo(args) => {
+            for_each_file(&args.input, global, |file| pdo::run(args, global, file))
+        }
+        Command::ProcessData(args) => for_each_file(&args.input, global, |file| {
+            process_data::run(args, global, file)
+        }),
+        Command::Dc(args) => for_each_file(&args.input, global, |file| dc::run(args, global, file)),
+        Command::DcAlignment(args) => for_each_file(&args.input, global, |file| {
+            dc_alignment::run(args, global, file)
+        }),
+        Command::ErrorCounters(args) => for_each_file(&args.input, global, |file| {
+            error_counters::run(args, global, file)
+        }),
+        Command::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+
+            clap_complete::generate(*shell, &mut command, name, &mut std::io::stdout());
+        }
+    }
+}
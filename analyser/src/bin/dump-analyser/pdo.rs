@@ -0,0 +1,92 @@
+//! Print the PDO mapping and FMMU configuration of each SubDevice in a Wireshark capture file.
+
+use crate::{Format, Global, Input};
+use dump_analyser::pdo::PdoMapping;
+use std::path::Path;
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+}
+
+/// With `--format json`, only the mapped PDO entries are printed.
+pub fn run(_args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Json]);
+
+    let mapping = PdoMapping::new(global.exchanges(file));
+
+    if global.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&mapping.entries()).expect("Serialize")
+        );
+
+        return;
+    }
+
+    for (address, subdevice) in mapping.subdevices.iter() {
+        println!("SubDevice {:#06x}", address);
+
+        for (index, sm) in subdevice.sync_managers.iter() {
+            println!(
+                "  SM{:<2} start {:#06x}, length {:>4}, control {:#04x}, {}, {}",
+                index,
+                sm.start,
+                sm.length,
+                sm.control,
+                sm.direction(),
+                if sm.enabled { "enabled" } else { "disabled" }
+            );
+        }
+
+        for (index, fmmu) in subdevice.fmmus.iter() {
+            println!(
+                "  FMMU{:<2} logical {:#010x}.{}..{}, length {:>4}, physical {:#06x}.{}, {:?}, {}",
+                index,
+                fmmu.logical_start,
+                fmmu.logical_start_bit,
+                fmmu.logical_end_bit,
+                fmmu.length,
+                fmmu.physical_start,
+                fmmu.physical_start_bit,
+                fmmu.fmmu_type,
+                if fmmu.enabled { "enabled" } else { "disabled" }
+            );
+        }
+
+        let entries = subdevice.entries(*address);
+
+        if entries.is_empty() {
+            println!("  No PDO entries found");
+        } else {
+            println!(
+                "  {:>4} {:>8} {:>6} {:<10} {:>9} {:>5} {:>8} {:>14}  Name",
+                "SM", "PDO", "Source", "    Entry", "Direction", "Bits", "SM bit", "Logical bit"
+            );
+        }
+
+        for entry in entries {
+            let logical = entry
+                .logical_bit
+                .map(|bit| format!("{:#010x}.{}", bit / 8, bit % 8))
+                .unwrap_or_else(|| "-".to_string());
+
+            println!(
+                "  {:>4} {:>#8x} {:>6} {:>#6x}:{:<3} {:>9} {:>5} {:>8} {:>14}  {}",
+                entry.sync_manager,
+                entry.pdo,
+                format!("{:?}", entry.source),
+                entry.entry.index,
+                entry.entry.sub_index,
+                entry.direction,
+                entry.entry.bit_len,
+                entry.sm_bit_offset,
+                logical,
+                entry.entry.name.as_deref().unwrap_or("")
+            );
+        }
+
+        println!();
+    }
+}
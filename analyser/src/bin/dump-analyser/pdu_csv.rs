@@ -0,0 +1,246 @@
+//! Write PDU round trip times and metadata to CSV.
+
+use crate::{Format, Global, Input};
+use clap::builder::PossibleValuesParser;
+use dump_analyser::PduStat;
+use serde::ser::{SerializeMap, SerializeSeq};
+use smoltcp::wire::EthernetAddress;
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Columns to write, in order. Defaults to every column.
+    #[arg(long, value_delimiter = ',', value_parser = PossibleValuesParser::new(PduStat::COLUMNS))]
+    pub columns: Vec<String>,
+
+    /// File to write rows of every capture to, or `-` for stdout. Defaults to a CSV file next to
+    /// each capture.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+/// A single cell, serialized the same way as the [`PduStat`] field it's taken from.
+enum Value<'a> {
+    Str(&'a str),
+    Int(u64),
+    Mac(EthernetAddress),
+    Null,
+}
+
+impl serde::Serialize for Value<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Str(s) => serializer.serialize_str(s),
+            Self::Int(value) => serializer.serialize_u64(*value),
+            Self::Mac(mac) => serializer.collect_str(mac),
+            Self::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+fn nanos(duration: Duration) -> Value<'static> {
+    Value::Int(u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX))
+}
+
+fn int(value: Option<impl Into<u64>>) -> Value<'static> {
+    value.map_or(Value::Null, |value| Value::Int(value.into()))
+}
+
+/// Get the value of a column from a row.
+type Column = for<'a> fn(&'a PduStat) -> Value<'a>;
+
+/// Look up the getter of one of [`PduStat::COLUMNS`].
+fn column(name: &str) -> Column {
+    match name {
+        "scenario" => |stat| Value::Str(&stat.scenario),
+        "packet_number" => |stat| Value::Int(stat.packet_number as u64),
+        "index" => |stat| Value::Int(stat.index.into()),
+        "command" => |stat| Value::Str(&stat.command),
+        "register" => |stat| Value::Str(&stat.register),
+        "value" => |stat| Value::Str(&stat.value),
+        "tx_time_ns" => |stat| nanos(stat.tx_time),
+        "rx_time_ns" => |stat| nanos(stat.rx_time),
+        "delta_time_ns" => |stat| nanos(stat.delta_time),
+        "rx_packet_number" => |stat| int(stat.rx_packet_number.map(|n| n as u64)),
+        "cycle" => |stat| Value::Int(stat.cycle as u64),
+        "addressing" => |stat| {
+            stat.addressing
+                .map_or(Value::Null, |addressing| Value::Str(addressing.as_str()))
+        },
+        "station_address" => |stat| int(stat.station_address),
+        "logical_address" => |stat| int(stat.logical_address),
+        "register_address" => |stat| int(stat.register_address),
+        "data_len" => |stat| Value::Int(stat.data_len as u64),
+        "working_counter" => |stat| int(stat.working_counter),
+        "expected_working_counter" => |stat| int(stat.expected_working_counter),
+        "pdus_in_frame" => |stat| Value::Int(stat.pdus_in_frame as u64),
+        "frame_size" => |stat| Value::Int(stat.frame_size as u64),
+        "tx_source" => |stat| Value::Mac(stat.tx_source),
+        "rx_source" => |stat| stat.rx_source.map_or(Value::Null, Value::Mac),
+        other => unreachable!("Unknown column {}", other),
+    }
+}
+
+/// Selected columns of a single row.
+struct Row<'a> {
+    stat: &'a PduStat,
+    columns: &'a [(&'a str, Column)],
+}
+
+/// Serialized as a CSV record, without column names.
+struct Record<'a>(Row<'a>);
+
+impl serde::Serialize for Record<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.0.columns.len()))?;
+
+        for (_name, column) in self.0.columns {
+            seq.serialize_element(&column(self.0.stat))?;
+        }
+
+        seq.end()
+    }
+}
+
+// Serialized as an object with keys in column order
+impl serde::Serialize for Row<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+
+        for (name, column) in self.columns {
+            map.serialize_entry(name, &column(self.stat))?;
+        }
+
+        map.end()
+    }
+}
+
+fn write_csv(
+    writer: &mut csv::Writer<impl Write>,
+    stats: &[PduStat],
+    columns: &[(&str, Column)],
+) -> csv::Result<()> {
+    for stat in stats {
+        writer.serialize(Record(Row { stat, columns }))?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Write rows of every capture as a single JSON array, one capture at a time.
+fn write_json(
+    out: impl Write,
+    args: &Args,
+    global: &Global,
+    columns: &[(&str, Column)],
+) -> serde_json::Result<()> {
+    let mut serializer = serde_json::Serializer::pretty(io::BufWriter::new(out));
+
+    let mut seq = serde::Serializer::serialize_seq(&mut serializer, None)?;
+
+    for file in args.input.files.iter() {
+        log::info!("Analysing {:?}", file);
+
+        for stat in global.pdu_stats(file).iter() {
+            seq.serialize_element(&Row { stat, columns })?;
+        }
+    }
+
+    SerializeSeq::end(seq)?;
+
+    let mut out = serializer.into_inner();
+
+    writeln!(out).map_err(serde_json::Error::io)?;
+
+    out.flush().map_err(serde_json::Error::io)
+}
+
+pub fn run(args: &Args, global: &Global) {
+    global.require_format(&[Format::Text, Format::Csv, Format::Json]);
+
+    let names = if args.columns.is_empty() {
+        PduStat::COLUMNS.to_vec()
+    } else {
+        args.columns.iter().map(String::as_str).collect()
+    };
+
+    let columns = names
+        .iter()
+        .map(|name| (*name, column(name)))
+        .collect::<Vec<_>>();
+
+    let output = args.output.as_deref().map(|path| {
+        let output: Box<dyn Write> = if path == Path::new("-") {
+            Box::new(io::stdout().lock())
+        } else {
+            Box::new(File::create(path).expect("Unable to create output file"))
+        };
+
+        output
+    });
+
+    if global.format == Format::Json {
+        let output = output.unwrap_or_else(|| Box::new(io::stdout().lock()));
+
+        write_json(output, args, global, &columns).expect("Write rows");
+    } else {
+        // A single output gets rows from every capture, with one header
+        let mut shared = output.map(|output| {
+            let mut writer = csv::Writer::from_writer(output);
+
+            writer.write_record(&names).expect("Write header");
+
+            writer
+        });
+
+        for file in args.input.files.iter() {
+            log::info!("Analysing {:?}", file);
+
+            let stats = global.pdu_stats(file);
+
+            if let Some(writer) = shared.as_mut() {
+                write_csv(writer, &stats, &columns).expect("Write rows");
+            } else {
+                let mut out_path = file.to_path_buf();
+
+                out_path.set_extension("csv");
+
+                let mut writer =
+                    csv::Writer::from_path(&out_path).expect("Unable to create writer");
+
+                writer.write_record(&names).expect("Write header");
+
+                write_csv(&mut writer, &stats, &columns).expect("Write rows");
+
+                log::info!("Done, wrote {:?}", out_path);
+            }
+        }
+    }
+
+    if let Some(path) = args
+        .output
+        .as_deref()
+        .filter(|path| *path != Path::new("-"))
+    {
+        log::info!("Done, wrote {:?}", path);
+    }
+}
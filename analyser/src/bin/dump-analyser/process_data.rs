@@ -0,0 +1,169 @@
+//! Log every change of the process image in a Wireshark capture file.
+//!
+//! By default every mapped byte and PDO entry is watched. Use `--range` to watch specific bit
+//! ranges of the logical process image instead.
+
+use crate::{Format, Global, Input};
+use clap_num::maybe_hex;
+use dump_analyser::process_image::{change_events, ProcessImage};
+use serde_with::{serde_as, DurationNanoSeconds};
+use std::{path::Path, time::Duration};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Logical bit ranges to watch, as `BYTE[.BIT][:BITS]`, e.g. `0x10`, `0x10.3` or `0x10.0:16`.
+    ///
+    /// `BITS` defaults to 8 for whole bytes and 1 when `BIT` is given.
+    #[clap(long, num_args = 1.., value_delimiter = ',', value_parser = parse_range)]
+    pub range: Vec<(u64, u8)>,
+
+    /// Only watch SubDevices with the given configured addresses.
+    #[clap(long, num_args = 1.., value_delimiter = ',', value_parser=maybe_hex::<u16>)]
+    pub address: Vec<u16>,
+}
+
+/// A single change event, also a single CSV row.
+#[serde_as]
+#[derive(Debug, serde::Serialize)]
+struct EventRow {
+    #[serde_as(as = "DurationNanoSeconds")]
+    #[serde(rename = "time_ns")]
+    time: Duration,
+
+    /// Wireshark packet number.
+    packet_number: usize,
+
+    cycle: usize,
+
+    address: u16,
+
+    direction: String,
+
+    signal: String,
+
+    previous: u64,
+
+    value: u64,
+}
+
+fn parse_range(s: &str) -> Result<(u64, u8), String> {
+    let (start, bits) = match s.split_once(':') {
+        Some((start, bits)) => (start, Some(bits)),
+        None => (s, None),
+    };
+
+    let (byte, bit) = match start.split_once('.') {
+        Some((byte, bit)) => (byte, Some(bit)),
+        None => (start, None),
+    };
+
+    let byte = maybe_hex::<u32>(byte)?;
+
+    let bit = bit
+        .map(|bit| bit.parse::<u8>().map_err(|e| e.to_string()))
+        .transpose()?
+        .unwrap_or(0);
+
+    if bit > 7 {
+        return Err(format!("bit {} out of range 0-7", bit));
+    }
+
+    let bits = match bits {
+        Some(bits) => maybe_hex::<u8>(bits)?,
+        None if s.contains('.') => 1,
+        None => 8,
+    };
+
+    Ok((u64::from(byte) * 8 + u64::from(bit), bits))
+}
+
+pub fn run(args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Csv, Format::Json]);
+
+    let image = ProcessImage::new(global.exchanges(file));
+
+    let signals = if args.range.is_empty() {
+        image.signals()
+    } else {
+        args.range
+            .iter()
+            .filter_map(|(logical_bit, bit_len)| {
+                let signal = image.range_signal(*logical_bit, *bit_len);
+
+                if signal.is_none() {
+                    log::warn!(
+                        "Range {:#010x}.{}:{} is not mapped by a single FMMU",
+                        logical_bit / 8,
+                        logical_bit % 8,
+                        bit_len
+                    );
+                }
+
+                signal
+            })
+            .collect()
+    };
+
+    let signals = signals
+        .into_iter()
+        .filter(|signal| args.address.is_empty() || args.address.contains(&signal.address))
+        .collect();
+
+    let rows = change_events(&image.series_of(signals))
+        .into_iter()
+        .filter(|event| global.in_window(event.sample.time))
+        .map(|event| EventRow {
+            time: event.sample.time,
+            packet_number: event.sample.packet_number,
+            cycle: event.sample.cycle,
+            address: event.signal.address,
+            direction: event.signal.direction.to_string(),
+            signal: event.signal.to_string(),
+            previous: event.previous,
+            value: event.sample.value,
+        })
+        .collect::<Vec<_>>();
+
+    log::info!("Found {} changes", rows.len());
+
+    if global.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&rows).expect("Serialize")
+        );
+
+        return;
+    }
+
+    if global.format == Format::Csv {
+        let mut wtr = csv::Writer::from_writer(std::io::stdout());
+
+        for row in rows {
+            wtr.serialize(row).expect("Serialize");
+        }
+
+        wtr.flush().expect("Flush");
+
+        return;
+    }
+
+    println!(
+        "{:>8} {:>12} {:>8} {:<40} {:>12} {:>12}",
+        "Packet", "Time (s)", "Cycle", "Signal", "Previous", "Value"
+    );
+
+    for row in rows {
+        println!(
+            "{:>8} {:>12.6} {:>8} {:<40} {:>#12x} {:>#12x}",
+            row.packet_number,
+            row.time.as_secs_f64(),
+            row.cycle,
+            row.signal,
+            row.previous,
+            row.value
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Dump the last known value of every register of every SubDevice at a point in a given Wireshark
+//! capture file.
+//!
+//! The default output holds only register values so that two dumps can be compared with `diff`.
+//! Use `--format csv` to also see when each register was last accessed.
+
+use crate::{Format, Global, Input};
+use clap_num::maybe_hex;
+use dump_analyser::register_state::{RegisterState, Station};
+use serde_with::{serde_as, DurationNanoSeconds};
+use std::{path::Path, time::Duration};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Dump the state after this Wireshark packet number.
+    #[arg(long, conflicts_with = "time")]
+    pub packet: Option<usize>,
+
+    /// Dump the state at this many seconds after the start of the capture.
+    #[arg(long)]
+    pub time: Option<f64>,
+
+    /// Only dump this configured station address.
+    #[arg(long, value_parser=maybe_hex::<u16>)]
+    pub address: Option<u16>,
+
+    /// Include process data RAM (`0x1000` and above), e.g. mailbox contents.
+    #[arg(long)]
+    pub memory: bool,
+}
+
+/// A single register, also a single CSV row.
+#[serde_as]
+#[derive(Debug, serde::Serialize)]
+struct Row {
+    station: String,
+
+    register: u16,
+
+    name: String,
+
+    /// Raw bytes in hex.
+    data: String,
+
+    value: String,
+
+    /// `read` or `write`.
+    source: &'static str,
+
+    packet_number: usize,
+
+    #[serde_as(as = "DurationNanoSeconds")]
+    #[serde(rename = "time_ns")]
+    time: Duration,
+}
+
+pub fn run(args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Csv, Format::Json]);
+
+    let until = args.time.map(Duration::from_secs_f64);
+
+    // Exchanges come out in response order, so a request sent before the cut-off can follow one
+    // sent after it
+    let state = RegisterState::new(global.exchanges(file).filter(|exchange| {
+        args.packet
+            .is_none_or(|packet| exchange.tx_packet_number <= packet)
+            && until.is_none_or(|until| exchange.tx_time <= until)
+    }));
+
+    let rows = state
+        .snapshot()
+        .into_iter()
+        .filter(|register| {
+            args.address
+                .is_none_or(|address| register.station == Station::Address(address))
+        })
+        .filter(|register| args.memory || register.address < 0x1000)
+        .map(|register| Row {
+            station: register.station.to_string(),
+            register: register.address,
+            name: register.name,
+            data: register
+                .data
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+            value: register.value,
+            source: if register.written { "write" } else { "read" },
+            packet_number: register.packet_number,
+            time: register.time,
+        })
+        .collect::<Vec<_>>();
+
+    if global.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&rows).expect("Serialize")
+        );
+
+        return;
+    }
+
+    if global.format == Format::Csv {
+        let mut wtr = csv::Writer::from_writer(std::io::stdout());
+
+        for row in rows {
+            wtr.serialize(row).expect("Serialize");
+        }
+
+        wtr.flush().expect("Flush");
+
+        return;
+    }
+
+    for row in rows {
+        println!(
+            "{:>8} {:#06x} {:<36} {}",
+            row.station, row.register, row.name, row.value
+        );
+    }
+}
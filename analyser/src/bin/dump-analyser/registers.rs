@@ -0,0 +1,157 @@
+//! Trace register reads and writes in a given Wireshark capture file.
+//!
+//! Each register PDU is split into the registers it covers, so an access to a range of registers
+//! matches every register it overlaps, not just the one it starts at.
+
+use crate::{Format, Global, Input};
+use clap_num::maybe_hex;
+use dump_analyser::{
+    command::{self, Access},
+    registers::split_range,
+};
+use serde_with::{serde_as, DurationNanoSeconds};
+use std::{path::Path, time::Duration};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Registers to trace. Every register access is traced if none are given.
+    #[clap(long, num_args = 1.., value_delimiter = ',', value_parser=maybe_hex::<u16>)]
+    pub registers: Vec<u16>,
+
+    /// Stop after this many accesses.
+    #[arg(long)]
+    pub limit: Option<usize>,
+}
+
+/// A single register read or written, also a single CSV row.
+#[serde_as]
+#[derive(Debug, serde::Serialize)]
+struct Row {
+    /// Wireshark packet number of the request.
+    packet_number: usize,
+
+    #[serde_as(as = "DurationNanoSeconds")]
+    #[serde(rename = "time_ns")]
+    time: Duration,
+
+    command: &'static str,
+
+    /// `read` or `write`.
+    access: &'static str,
+
+    /// Station address, or auto increment position for APRD/APWR.
+    address: u16,
+
+    register: u16,
+
+    name: String,
+
+    working_counter: u16,
+
+    /// Raw bytes in hex.
+    data: String,
+
+    /// Value decoded by register width and field layout.
+    value: String,
+}
+
+pub fn run(args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Csv, Format::Json]);
+
+    let limit = args.limit.unwrap_or(usize::MAX);
+
+    let mut accesses = Vec::new();
+
+    'exchanges: for exchange in global.exchanges(file) {
+        if !global.in_window(exchange.tx_time) {
+            continue;
+        }
+
+        let command = exchange.request.command;
+
+        let (Some(register), Some(address)) = (
+            command::register(&command),
+            command::subdevice_address(&command),
+        ) else {
+            continue;
+        };
+
+        // Writes carry their data in the request, reads in the response. Read/write commands show
+        // the value read before it was overwritten.
+        let (access, data) = match command::access(&command) {
+            Some(Access::Write) => ("write", &exchange.request.data),
+            Some(Access::ReadWrite) => ("read/write", &exchange.response.data),
+            _ => ("read", &exchange.response.data),
+        };
+
+        for chunk in split_range(register, data) {
+            if !args.registers.is_empty() && !args.registers.iter().any(|r| chunk.contains(*r)) {
+                continue;
+            }
+
+            if accesses.len() >= limit {
+                break 'exchanges;
+            }
+
+            accesses.push(Row {
+                packet_number: exchange.tx_packet_number,
+                time: exchange.tx_time,
+                command: command::name(&command),
+                access,
+                address,
+                register: chunk.address,
+                name: chunk
+                    .register
+                    .as_ref()
+                    .map(|register| register.name.to_string())
+                    .unwrap_or_default(),
+                working_counter: exchange.response.working_counter,
+                data: chunk
+                    .data
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect(),
+                value: chunk.value(),
+            });
+        }
+    }
+
+    if global.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&accesses).expect("Serialize")
+        );
+
+        return;
+    }
+
+    if global.format == Format::Csv {
+        let mut wtr = csv::Writer::from_writer(std::io::stdout());
+
+        for access in accesses {
+            wtr.serialize(access).expect("Serialize");
+        }
+
+        wtr.flush().expect("Flush");
+
+        return;
+    }
+
+    for access in accesses {
+        println!(
+            "{:>8} {:>12.6} {:>5} {:>5} {:#06x} {:#06x} {:<32} wkc {:>3} {}",
+            access.packet_number,
+            access.time.as_secs_f64(),
+            access.command,
+            access.access,
+            access.address,
+            access.register,
+            access.name,
+            access.working_counter,
+            access.value
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! List the CoE SDO uploads and downloads found in a given Wireshark capture file.
+
+use crate::{Format, Global, Input};
+use clap_num::maybe_hex;
+use dump_analyser::coe::sdo_transactions;
+use std::path::Path;
+
+/// Maximum number of data bytes to print per transaction.
+const MAX_DATA_LEN: usize = 16;
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Only show transactions with the given SubDevice configured addresses.
+    #[clap(long, num_args = 1.., value_delimiter = ',', value_parser=maybe_hex::<u16>)]
+    pub address: Vec<u16>,
+
+    /// Only show transactions for the given object indices.
+    #[clap(long, num_args = 1.., value_delimiter = ',', value_parser=maybe_hex::<u16>)]
+    pub index: Vec<u16>,
+}
+
+pub fn run(args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text]);
+
+    let transactions = sdo_transactions(global.exchanges(file))
+        .into_iter()
+        .filter(|transaction| global.in_window(transaction.start_time))
+        .filter(|transaction| {
+            args.address.is_empty() || args.address.contains(&transaction.address)
+        })
+        .filter(|transaction| args.index.is_empty() || args.index.contains(&transaction.index));
+
+    println!(
+        "{:>8} {:>12} {:>8} {:>10} {:>10} {:>4} {:>8} {:>6} {:<51} Result",
+        "Packet", "Time (s)", "Address", "Direction", "Object", "CA", "Segments", "Size", "Data"
+    );
+
+    for transaction in transactions {
+        let mut data = transaction
+            .data
+            .iter()
+            .take(MAX_DATA_LEN)
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if transaction.data.len() > MAX_DATA_LEN {
+            data.push_str(" ...");
+        }
+
+        println!(
+            "{:>8} {:>12.6} {:>#8x} {:>10} {:>#7x}:{:<2} {:>4} {:>8} {:>6} {:<51} {}",
+            transaction.start_packet,
+            transaction.start_time.as_secs_f64(),
+            transaction.address,
+            transaction.direction,
+            transaction.index,
+            transaction.sub_index,
+            if transaction.complete_access {
+                "yes"
+            } else {
+                "no"
+            },
+            transaction.segments,
+            transaction.data.len(),
+            data,
+            transaction.result
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! Decode the SII (EEPROM) contents recovered from a Wireshark capture.
+//!
+//! Only the words read during the capture can be decoded. Anything else is marked as not
+//! captured.
+
+use crate::{Format, Global, Input};
+use dump_analyser::{sii::sii_images, sii_decode::SiiContents};
+use std::{collections::BTreeMap, path::Path};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+}
+
+pub fn run(_args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Json]);
+
+    let decoded = sii_images(global.exchanges(file))
+        .into_iter()
+        .map(|(device, image)| (device.to_string(), SiiContents::decode(&image)))
+        .collect::<BTreeMap<_, _>>();
+
+    if global.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&decoded).expect("Serialize")
+        );
+
+        return;
+    }
+
+    for (address, contents) in decoded {
+        println!("SubDevice {}", address);
+        println!("{}", contents);
+    }
+}
@@ -0,0 +1,76 @@
+//! Import frames, PDUs and matched exchanges of captures into a SQLite database. See
+//! [`dump_analyser::sqlite`] for the schema.
+
+use crate::{Format, Global, Input};
+use dump_analyser::{
+    exchange::Exchanges,
+    sqlite::{self, Import},
+    working_counter::WorkingCounters,
+};
+use std::{cell::RefCell, path::PathBuf};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Database to import into, created if it doesn't exist.
+    #[arg(long)]
+    pub database: PathBuf,
+}
+
+pub fn run(args: &Args, global: &Global) {
+    global.require_format(&[Format::Text]);
+
+    let mut conn = sqlite::open(&args.database).expect("Unable to open database");
+
+    for file in args.input.files.iter() {
+        log::info!("Importing {:?}", file);
+
+        let capture = global.open(file);
+        let scenario = capture.scenario.clone();
+
+        let import = RefCell::new(Import::new(&mut conn, &capture, file).expect("Start import"));
+
+        let mut start_time = None;
+
+        // Frames are imported as they're decoded, so each capture is only read once
+        let mut exchanges = Exchanges::new(capture.inspect(|frame| {
+            let start = *start_time.get_or_insert(frame.time);
+            let time = frame.time.saturating_sub(start);
+
+            if global.in_window(time) {
+                import
+                    .borrow_mut()
+                    .frame(frame, time)
+                    .expect("Insert frame");
+            }
+        }));
+
+        let mut working_counters = WorkingCounters::default();
+
+        for exchange in exchanges.by_ref() {
+            let expected = working_counters.expected(&exchange.request);
+
+            working_counters.push(&exchange);
+
+            if global.in_window(exchange.tx_time) {
+                import
+                    .borrow_mut()
+                    .exchange(&exchange, expected)
+                    .expect("Insert exchange");
+            }
+        }
+
+        let lost = exchanges.lost();
+
+        drop(exchanges);
+
+        import
+            .into_inner()
+            .finish(start_time, lost)
+            .expect("Finish import");
+
+        log::info!("Imported {} into {:?}", scenario, args.database);
+    }
+}
@@ -0,0 +1,140 @@
+//! Round trip and cycle delta time statistics of a capture.
+
+use crate::{Format, Global, Input};
+use dump_analyser::stats::{self, DumpFileStats, DEFAULT_PERCENTILES};
+use std::{fs::File, io::BufWriter, path::Path};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+
+    /// Percentiles to compute, from 0 to 100.
+    #[arg(long, value_delimiter = ',', default_values_t = DEFAULT_PERCENTILES)]
+    pub percentiles: Vec<f64>,
+
+    /// Also write HdrHistogram percentile distributions (`.hgrm`) next to each capture.
+    #[arg(long)]
+    pub histograms: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Stats {
+    scenario: String,
+    round_trip_us: DumpFileStats,
+    cycle_delta_us: DumpFileStats,
+}
+
+pub fn run(args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Csv, Format::Json]);
+
+    let scenario = global.open(file).scenario;
+    let pairs = global.pdu_stats(file);
+
+    let round_trip_times = stats::round_trip_times(&pairs);
+    let cycle_delta_times = stats::cycle_delta_times(&pairs);
+
+    if args.histograms {
+        let base_file_name = file.file_stem().unwrap().to_string_lossy();
+
+        for (name, times) in [
+            ("round-trip", &round_trip_times),
+            ("cycle-delta", &cycle_delta_times),
+        ] {
+            let out_path = file.with_file_name(format!("{}-{}.hgrm", base_file_name, name));
+
+            let out = File::create(&out_path).expect("Unable to create histogram file");
+
+            stats::write_percentile_distribution(&stats::histogram(times), BufWriter::new(out))
+                .expect("Write histogram");
+
+            log::info!("Wrote {:?}", out_path);
+        }
+    }
+
+    let stats = Stats {
+        scenario,
+        round_trip_us: DumpFileStats::with_percentiles(&round_trip_times, &args.percentiles),
+        cycle_delta_us: DumpFileStats::with_percentiles(&cycle_delta_times, &args.percentiles),
+    };
+
+    match global.format {
+        Format::Text => print_table(&[
+            ("Round trip", &stats.round_trip_us),
+            ("Cycle delta", &stats.cycle_delta_us),
+        ]),
+        Format::Csv => {
+            for (name, series) in [
+                ("round_trip_us", &stats.round_trip_us),
+                ("cycle_delta_us", &stats.cycle_delta_us),
+            ] {
+                print!(
+                    "{},{},{},{},{},{},{},{}",
+                    stats.scenario,
+                    name,
+                    series.count,
+                    series.min,
+                    series.mean,
+                    series.std_dev,
+                    series.variance,
+                    series.max
+                );
+
+                for percentile in series.percentiles.iter() {
+                    print!(",{}", percentile.value);
+                }
+
+                println!();
+            }
+        }
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).expect("Serialize")
+        ),
+        Format::Markdown | Format::Html => unreachable!(),
+    }
+}
+
+/// Print the CSV header, once before the rows of every capture.
+pub fn print_csv_header(args: &Args) {
+    print!("scenario,series,count,min,mean,std_dev,variance,max");
+
+    for percentile in args.percentiles.iter() {
+        print!(",p{}", percentile);
+    }
+
+    println!();
+}
+
+/// Print a table of time statistics in microseconds, one row per series.
+///
+/// Every series must have been computed with the same percentiles.
+pub fn print_table(rows: &[(&str, &DumpFileStats)]) {
+    let Some((_name, first)) = rows.first() else {
+        return;
+    };
+
+    print!(
+        "{:<16} {:>8} {:>10} {:>10} {:>10}",
+        "Time (us)", "Count", "Min", "Mean", "Std. Dev."
+    );
+
+    for percentile in first.percentiles.iter() {
+        print!(" {:>10}", format!("P{}", percentile.percentile));
+    }
+
+    println!(" {:>10}", "Max");
+
+    for (name, stats) in rows {
+        print!(
+            "{:<16} {:>8} {:>10.3} {:>10.3} {:>10.3}",
+            name, stats.count, stats.min, stats.mean, stats.std_dev
+        );
+
+        for percentile in stats.percentiles.iter() {
+            print!(" {:>10.3}", percentile.value);
+        }
+
+        println!(" {:>10.3}", stats.max);
+    }
+}
@@ -0,0 +1,215 @@
+//! Print a quick summary of a capture: metadata, traffic counts and timing statistics.
+
+use crate::{Format, Global, Input};
+use dump_analyser::{
+    command,
+    exchange::Exchanges,
+    stats::{self, DumpFileStats},
+    working_counter::WorkingCounters,
+};
+use std::{collections::BTreeMap, path::Path, time::Duration};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+}
+
+/// PDU counts for a single command type.
+#[derive(Debug, Default, serde::Serialize)]
+struct CommandCounts {
+    /// PDUs sent by the MainDevice.
+    sent: usize,
+
+    /// Responses with a working counter of zero, i.e. no SubDevice processed the PDU.
+    zero_wkc: usize,
+
+    /// Responses with a working counter other than expected from the topology and FMMU
+    /// configuration seen so far. PDUs with an unknown expected working counter aren't counted.
+    unexpected_wkc: usize,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Summary {
+    scenario: String,
+    cpu: String,
+    os: String,
+    if_name: String,
+
+    /// Time between the first and last EtherCAT frame, in seconds.
+    duration: f64,
+
+    /// EtherCAT frames sent by the MainDevice.
+    frames_sent: usize,
+
+    /// EtherCAT frames returned by the network.
+    frames_received: usize,
+
+    commands: BTreeMap<&'static str, CommandCounts>,
+
+    /// PDUs that never received a response, before either their index was reused or the capture
+    /// ended.
+    lost_pdus: usize,
+
+    round_trip_us: Option<DumpFileStats>,
+
+    cycle_delta_us: Option<DumpFileStats>,
+}
+
+pub fn run(_args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text, Format::Json]);
+
+    let reader = global.open(file);
+
+    let mut summary = Summary {
+        scenario: reader.scenario.clone(),
+        cpu: reader.cpu.clone(),
+        os: reader.os.clone(),
+        if_name: reader.if_name.clone(),
+        duration: 0.0,
+        frames_sent: 0,
+        frames_received: 0,
+        commands: BTreeMap::new(),
+        lost_pdus: 0,
+        round_trip_us: None,
+        cycle_delta_us: None,
+    };
+
+    let mut start = None;
+    let mut frames_sent = 0;
+    let mut frames_received = 0;
+    let mut first_frame = None;
+    let mut last_frame = Duration::ZERO;
+
+    let mut exchanges = Exchanges::new(reader.inspect(|frame| {
+        let time = frame.time - *start.get_or_insert(frame.time);
+
+        if !global.in_window(time) {
+            return;
+        }
+
+        first_frame.get_or_insert(time);
+        last_frame = time;
+
+        if frame.from_master {
+            frames_sent += 1;
+        } else {
+            frames_received += 1;
+        }
+    }));
+
+    let mut working_counters = WorkingCounters::default();
+
+    for exchange in &mut exchanges {
+        let expected = working_counters.expected(&exchange.request);
+
+        working_counters.push(&exchange);
+
+        if !global.in_window(exchange.tx_time) {
+            continue;
+        }
+
+        let counts = summary
+            .commands
+            .entry(command::name(&exchange.request.command))
+            .or_default();
+
+        counts.sent += 1;
+
+        if exchange.response.working_counter == 0 {
+            counts.zero_wkc += 1;
+        }
+
+        if expected.is_some_and(|expected| exchange.response.working_counter != expected) {
+            counts.unexpected_wkc += 1;
+        }
+    }
+
+    summary.lost_pdus = exchanges.lost();
+
+    drop(exchanges);
+
+    summary.frames_sent = frames_sent;
+    summary.frames_received = frames_received;
+    summary.duration = first_frame.map_or(0.0, |first| (last_frame - first).as_secs_f64());
+
+    let pairs = global.pdu_stats(file);
+
+    let round_trip_times = stats::round_trip_times(&pairs);
+    let cycle_delta_times = stats::cycle_delta_times(&pairs);
+
+    summary.round_trip_us =
+        (!round_trip_times.is_empty()).then(|| DumpFileStats::new(&round_trip_times));
+    summary.cycle_delta_us =
+        (!cycle_delta_times.is_empty()).then(|| DumpFileStats::new(&cycle_delta_times));
+
+    if global.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&summary).expect("Serialize")
+        );
+
+        return;
+    }
+
+    println!("Scenario:  {}", summary.scenario);
+    println!("CPU:       {}", summary.cpu);
+    println!("OS:        {}", summary.os);
+    println!("Interface: {}", summary.if_name);
+    println!("Duration:  {:.3} s", summary.duration);
+    println!(
+        "Frames:    {} sent, {} received",
+        summary.frames_sent, summary.frames_received
+    );
+    println!("Lost PDUs: {}", summary.lost_pdus);
+    println!();
+
+    println!(
+        "{:>8} {:>10} {:>10} {:>14}",
+        "Command", "Sent", "Zero WKC", "Unexpected WKC"
+    );
+
+    for (name, counts) in summary.commands.iter() {
+        println!(
+            "{:>8} {:>10} {:>10} {:>14}",
+            name, counts.sent, counts.zero_wkc, counts.unexpected_wkc
+        );
+    }
+
+    println!(
+        "{:>8} {:>10} {:>10} {:>14}",
+        "Total",
+        summary
+            .commands
+            .values()
+            .map(|counts| counts.sent)
+            .sum::<usize>(),
+        summary
+            .commands
+            .values()
+            .map(|counts| counts.zero_wkc)
+            .sum::<usize>(),
+        summary
+            .commands
+            .values()
+            .map(|counts| counts.unexpected_wkc)
+            .sum::<usize>()
+    );
+    println!();
+
+    let timings = [
+        ("Round trip", &summary.round_trip_us),
+        ("Cycle delta", &summary.cycle_delta_us),
+    ]
+    .into_iter()
+    .filter_map(|(name, stats)| Some((name, stats.as_ref()?)))
+    .collect::<Vec<_>>();
+
+    if timings.is_empty() {
+        println!("No matched PDUs found in capture");
+
+        return;
+    }
+
+    crate::stats::print_table(&timings);
+}
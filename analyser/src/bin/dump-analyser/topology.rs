@@ -0,0 +1,77 @@
+//! List the SubDevices found in a given Wireshark capture file, along with their identities.
+
+use crate::{Format, Global, Input};
+use dump_analyser::{
+    identity::{Identities, Identity},
+    topology::Topology,
+};
+use std::path::Path;
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    pub input: Input,
+}
+
+pub fn run(_args: &Args, global: &Global, file: &Path) {
+    global.require_format(&[Format::Text]);
+
+    let mut topology = Topology::default();
+    let mut identities = Identities::default();
+
+    for exchange in global.exchanges(file) {
+        topology.push(&exchange);
+        identities.push(&exchange);
+    }
+
+    match topology.discovered_count {
+        Some(count) => println!("{} SubDevices discovered", count),
+        None => println!("No discovery broadcast found in capture"),
+    }
+
+    println!(
+        "{:>8} {:>10} {:>14} {:>14} {:>14} {:>14} {:>10} {:>10} {:>10} {:>10}",
+        "Position",
+        "Address",
+        "Port 0",
+        "Port 1",
+        "Port 2",
+        "Port 3",
+        "Vendor",
+        "Product",
+        "Revision",
+        "Serial"
+    );
+
+    for subdevice in topology.subdevices.values() {
+        let address = subdevice
+            .configured_address
+            .map(|address| format!("{:#06x}", address))
+            .unwrap_or_else(|| "-".to_string());
+
+        let ports = match subdevice.dl_status {
+            Some(status) => status.ports().map(|port| port.to_string()),
+            None => ["-"; 4].map(String::from),
+        };
+
+        let identity = subdevice
+            .configured_address
+            .and_then(|address| identities.by_address.get(&address))
+            .copied()
+            .unwrap_or_default();
+
+        println!(
+            "{:>8} {:>10} {:>14} {:>14} {:>14} {:>14} {:>10} {:>10} {:>10} {:>10}",
+            subdevice.position,
+            address,
+            ports[0],
+            ports[1],
+            ports[2],
+            ports[3],
+            Identity::display_field(identity.vendor_id),
+            Identity::display_field(identity.product_code),
+            Identity::display_field(identity.revision),
+            Identity::display_field(identity.serial)
+        );
+    }
+}
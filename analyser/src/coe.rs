@@ -0,0 +1,881 @@
+//! CANopen over EtherCAT (CoE) message parsing and SDO transaction reconstruction.
+
+use crate::{
+    exchange::PduExchange,
+    mailbox::{MailboxDirection, MailboxMessage, MailboxReader, MailboxType},
+};
+use std::{collections::HashMap, time::Duration};
+
+/// Length of the CoE header following the mailbox header.
+pub const COE_HEADER_LEN: usize = 2;
+
+/// Length of the SDO header used by initiate requests and responses.
+pub const SDO_HEADER_LEN: usize = 4;
+
+/// Segment data length at or below which the unused byte count in the segment header is valid.
+const MIN_SEGMENT_LEN: usize = 7;
+
+/// CoE service, defined in ETG1000.6 Table 29.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[repr(u8)]
+pub enum CoeService {
+    Emergency = 0x01,
+    SdoRequest = 0x02,
+    SdoResponse = 0x03,
+    TxPdo = 0x04,
+    RxPdo = 0x05,
+    TxPdoRemoteRequest = 0x06,
+    RxPdoRemoteRequest = 0x07,
+    SdoInformation = 0x08,
+    #[num_enum(catch_all)]
+    Unknown(u8),
+}
+
+/// CoE header, defined in ETG1000.6 Table 29.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CoeHeader {
+    /// PDO number for PDO services, otherwise unused.
+    pub number: u16,
+
+    pub service: CoeService,
+}
+
+impl CoeHeader {
+    /// Parse the CoE header at the start of a mailbox payload.
+    pub fn parse(i: &[u8]) -> Option<Self> {
+        let raw = u16::from_le_bytes(i.get(0..COE_HEADER_LEN)?.try_into().unwrap());
+
+        Some(Self {
+            number: raw & 0x01ff,
+            service: CoeService::from((raw >> 12) as u8),
+        })
+    }
+}
+
+/// A single SDO request or response, defined in ETG1000.6 section 5.6.2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sdo {
+    DownloadRequest {
+        index: u16,
+        sub_index: u8,
+        complete_access: bool,
+        expedited: bool,
+        /// Total object size, if given.
+        size: Option<u32>,
+        data: Vec<u8>,
+    },
+    DownloadSegmentRequest {
+        toggle: bool,
+        last: bool,
+        data: Vec<u8>,
+    },
+    DownloadResponse {
+        index: u16,
+        sub_index: u8,
+    },
+    DownloadSegmentResponse {
+        toggle: bool,
+    },
+    UploadRequest {
+        index: u16,
+        sub_index: u8,
+        complete_access: bool,
+    },
+    UploadSegmentRequest {
+        toggle: bool,
+    },
+    UploadResponse {
+        index: u16,
+        sub_index: u8,
+        complete_access: bool,
+        expedited: bool,
+        /// Total object size, if given.
+        size: Option<u32>,
+        data: Vec<u8>,
+    },
+    UploadSegmentResponse {
+        toggle: bool,
+        last: bool,
+        data: Vec<u8>,
+    },
+    /// Abort sent by either side.
+    Abort {
+        index: u16,
+        sub_index: u8,
+        code: u32,
+    },
+}
+
+impl Sdo {
+    /// Parse an SDO request or response from a mailbox message.
+    ///
+    /// Returns `None` for non-CoE messages, other CoE services and malformed SDOs.
+    pub fn parse(message: &MailboxMessage) -> Option<Self> {
+        if message.header.mailbox_type != MailboxType::Coe {
+            return None;
+        }
+
+        let header = CoeHeader::parse(&message.payload)?;
+        let i = message.payload.get(COE_HEADER_LEN..)?;
+
+        match header.service {
+            CoeService::SdoRequest => Self::parse_request(i),
+            CoeService::SdoResponse => Self::parse_response(i),
+            _ => None,
+        }
+    }
+
+    fn parse_request(i: &[u8]) -> Option<Self> {
+        let flags = *i.first()?;
+
+        match flags >> 5 {
+            // Download segment
+            0x00 => {
+                let (toggle, last, data) = parse_segment(i)?;
+
+                Some(Self::DownloadSegmentRequest { toggle, last, data })
+            }
+            // Initiate download
+            0x01 => {
+                let (index, sub_index, complete_access) = parse_address(i)?;
+                let (expedited, size, data) = parse_initiate_data(flags, &i[SDO_HEADER_LEN..])?;
+
+                Some(Self::DownloadRequest {
+                    index,
+                    sub_index,
+                    complete_access,
+                    expedited,
+                    size,
+                    data,
+                })
+            }
+            // Initiate upload
+            0x02 => {
+                let (index, sub_index, complete_access) = parse_address(i)?;
+
+                Some(Self::UploadRequest {
+                    index,
+                    sub_index,
+                    complete_access,
+                })
+            }
+            // Upload segment
+            0x03 => Some(Self::UploadSegmentRequest {
+                toggle: flags & 0x10 != 0,
+            }),
+            0x04 => parse_abort(i),
+            _ => None,
+        }
+    }
+
+    fn parse_response(i: &[u8]) -> Option<Self> {
+        let flags = *i.first()?;
+
+        match flags >> 5 {
+            // Upload segment
+            0x00 => {
+                let (toggle, last, data) = parse_segment(i)?;
+
+                Some(Self::UploadSegmentResponse { toggle, last, data })
+            }
+            // Download segment
+            0x01 => Some(Self::DownloadSegmentResponse {
+                toggle: flags & 0x10 != 0,
+            }),
+            // Initiate upload
+            0x02 => {
+                let (index, sub_index, complete_access) = parse_address(i)?;
+                let (expedited, size, data) = parse_initiate_data(flags, &i[SDO_HEADER_LEN..])?;
+
+                Some(Self::UploadResponse {
+                    index,
+                    sub_index,
+                    complete_access,
+                    expedited,
+                    size,
+                    data,
+                })
+            }
+            // Initiate download
+            0x03 => {
+                let (index, sub_index, _) = parse_address(i)?;
+
+                Some(Self::DownloadResponse { index, sub_index })
+            }
+            0x04 => parse_abort(i),
+            _ => None,
+        }
+    }
+}
+
+/// Parse index, sub-index and complete access flag from an initiate or abort header.
+fn parse_address(i: &[u8]) -> Option<(u16, u8, bool)> {
+    let sdo = i.get(0..SDO_HEADER_LEN)?;
+
+    Some((
+        u16::from_le_bytes([sdo[1], sdo[2]]),
+        sdo[3],
+        sdo[0] & 0x10 != 0,
+    ))
+}
+
+/// Parse the data following an initiate upload response or initiate download request header.
+fn parse_initiate_data(flags: u8, i: &[u8]) -> Option<(bool, Option<u32>, Vec<u8>)> {
+    let size_indicator = flags & 0x01 != 0;
+    let expedited = flags & 0x02 != 0;
+
+    if expedited {
+        let unused = if size_indicator {
+            usize::from((flags >> 2) & 0b11)
+        } else {
+            0
+        };
+
+        let data = i.get(0..4 - unused)?;
+
+        Some((true, Some(data.len() as u32), data.to_vec()))
+    } else {
+        let size = u32::from_le_bytes(i.get(0..4)?.try_into().unwrap());
+
+        // Data that doesn't fit in this message is sent in segments.
+        let data = &i[4..];
+        let data = &data[..data.len().min(size as usize)];
+
+        Some((false, size_indicator.then_some(size), data.to_vec()))
+    }
+}
+
+/// Parse a download segment request or upload segment response.
+fn parse_segment(i: &[u8]) -> Option<(bool, bool, Vec<u8>)> {
+    let flags = *i.first()?;
+    let data = &i[1..];
+
+    // The unused byte count is only valid for minimum length segments.
+    let data = if data.len() == MIN_SEGMENT_LEN {
+        &data[..MIN_SEGMENT_LEN - usize::from((flags >> 1) & 0b111)]
+    } else {
+        data
+    };
+
+    Some((flags & 0x10 != 0, flags & 0x01 != 0, data.to_vec()))
+}
+
+fn parse_abort(i: &[u8]) -> Option<Sdo> {
+    let (index, sub_index, _) = parse_address(i)?;
+    let code = u32::from_le_bytes(i.get(4..8)?.try_into().unwrap());
+
+    Some(Sdo::Abort {
+        index,
+        sub_index,
+        code,
+    })
+}
+
+/// Description of an SDO abort code, defined in ETG1000.6 Table 41.
+pub fn abort_description(code: u32) -> &'static str {
+    match code {
+        0x0503_0000 => "Toggle bit not changed",
+        0x0504_0000 => "SDO protocol timeout",
+        0x0504_0001 => "Client/Server command specifier not valid or unknown",
+        0x0504_0005 => "Out of memory",
+        0x0601_0000 => "Unsupported access to an object",
+        0x0601_0001 => "Attempt to read a write only object",
+        0x0601_0002 => "Attempt to write a read only object",
+        0x0601_0003 => "Subindex cannot be written, SI0 must be 0 for write access",
+        0x0601_0004 => "Complete access not supported for objects of variable length",
+        0x0601_0005 => "Object length exceeds mailbox size",
+        0x0601_0006 => "Object mapped to RxPDO, SDO download blocked",
+        0x0602_0000 => "Object does not exist in the object dictionary",
+        0x0604_0041 => "Object cannot be mapped into the PDO",
+        0x0604_0042 => "Number and length of the objects to be mapped would exceed the PDO length",
+        0x0604_0043 => "General parameter incompatibility",
+        0x0604_0047 => "General internal incompatibility in the device",
+        0x0606_0000 => "Access failed due to a hardware error",
+        0x0607_0010 => "Data type does not match, length of service parameter does not match",
+        0x0607_0012 => "Data type does not match, length of service parameter too high",
+        0x0607_0013 => "Data type does not match, length of service parameter too low",
+        0x0609_0011 => "Subindex does not exist",
+        0x0609_0030 => "Value range of parameter exceeded",
+        0x0609_0031 => "Value of parameter written too high",
+        0x0609_0032 => "Value of parameter written too low",
+        0x0609_0036 => "Maximum value is less than minimum value",
+        0x0800_0000 => "General error",
+        0x0800_0020 => "Data cannot be transferred or stored to the application",
+        0x0800_0021 => "Data cannot be transferred or stored because of local control",
+        0x0800_0022 => "Data cannot be transferred or stored because of the present device state",
+        0x0800_0023 => {
+            "Object dictionary dynamic generation failed or no object dictionary present"
+        }
+        _ => "Unknown abort code",
+    }
+}
+
+/// Direction of an SDO transfer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SdoDirection {
+    /// Object read from the SubDevice.
+    Upload,
+
+    /// Object written to the SubDevice.
+    Download,
+}
+
+impl std::fmt::Display for SdoDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Upload => f.pad("upload"),
+            Self::Download => f.pad("download"),
+        }
+    }
+}
+
+/// Outcome of an SDO transaction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SdoResult {
+    Ok,
+
+    /// Aborted by either side with the given abort code.
+    Aborted(u32),
+
+    /// Superseded by another request, or still running at the end of the capture.
+    Incomplete,
+}
+
+impl std::fmt::Display for SdoResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ok => f.pad("ok"),
+            Self::Aborted(code) => write!(f, "abort {:#010x} ({})", code, abort_description(*code)),
+            Self::Incomplete => f.pad("incomplete"),
+        }
+    }
+}
+
+/// A complete SDO upload or download, from initiate request to final response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdoTransaction {
+    /// Configured station address of the SubDevice.
+    pub address: u16,
+
+    pub direction: SdoDirection,
+
+    pub index: u16,
+
+    pub sub_index: u8,
+
+    /// The whole object was transferred starting from `sub_index`.
+    pub complete_access: bool,
+
+    /// Data was sent in the initiate request or response header.
+    pub expedited: bool,
+
+    /// Number of segments following the initiate request or response.
+    pub segments: usize,
+
+    /// Object size given in the initiate request or response, if any.
+    pub size: Option<u32>,
+
+    /// Object data, reassembled from all segments.
+    pub data: Vec<u8>,
+
+    pub result: SdoResult,
+
+    /// Time of the initiate request, relative to the start of the capture.
+    pub start_time: Duration,
+
+    /// Time of the last message in the transaction, relative to the start of the capture.
+    pub end_time: Duration,
+
+    /// Wireshark packet number of the initiate request.
+    pub start_packet: usize,
+
+    /// Wireshark packet number of the last message in the transaction.
+    pub end_packet: usize,
+}
+
+impl SdoTransaction {
+    fn new(
+        message: &MailboxMessage,
+        direction: SdoDirection,
+        index: u16,
+        sub_index: u8,
+        complete_access: bool,
+    ) -> Self {
+        Self {
+            address: message.address,
+            direction,
+            index,
+            sub_index,
+            complete_access,
+            expedited: false,
+            segments: 0,
+            size: None,
+            data: Vec::new(),
+            result: SdoResult::Incomplete,
+            start_time: message.time,
+            end_time: message.time,
+            start_packet: message.packet_number,
+            end_packet: message.packet_number,
+        }
+    }
+
+    /// All data given by the object size has been transferred.
+    fn data_complete(&self) -> bool {
+        self.size
+            .is_some_and(|size| self.data.len() >= size as usize)
+    }
+}
+
+/// Reconstructs SDO transactions from mailbox traffic.
+#[derive(Debug, Clone, Default)]
+pub struct SdoReader {
+    mailbox: MailboxReader,
+
+    /// In-progress transaction by configured station address.
+    open: HashMap<u16, SdoTransaction>,
+}
+
+impl SdoReader {
+    /// Process a single exchange, returning any transactions it completed or superseded.
+    pub fn push(&mut self, exchange: &PduExchange) -> Vec<SdoTransaction> {
+        let Some(message) = self.mailbox.push(exchange) else {
+            return Vec::new();
+        };
+
+        let Some(sdo) = Sdo::parse(&message) else {
+            return Vec::new();
+        };
+
+        self.push_sdo(&message, sdo)
+    }
+
+    /// Transactions still running at the end of the capture.
+    pub fn finish(self) -> Vec<SdoTransaction> {
+        let mut open = self.open.into_values().collect::<Vec<_>>();
+
+        open.sort_by_key(|transaction| transaction.start_packet);
+
+        open
+    }
+
+    fn push_sdo(&mut self, message: &MailboxMessage, sdo: Sdo) -> Vec<SdoTransaction> {
+        let address = message.address;
+        let mut done = Vec::new();
+
+        // A new initiate request supersedes anything left open for this SubDevice.
+        let initiate = match &sdo {
+            Sdo::DownloadRequest {
+                index,
+                sub_index,
+                complete_access,
+                ..
+            } => Some((SdoDirection::Download, *index, *sub_index, *complete_access)),
+            Sdo::UploadRequest {
+                index,
+                sub_index,
+                complete_access,
+            } => Some((SdoDirection::Upload, *index, *sub_index, *complete_access)),
+            _ => None,
+        };
+
+        if let Some((direction, index, sub_index, complete_access)) = initiate {
+            if message.direction != MailboxDirection::ToSubDevice {
+                return done;
+            }
+
+            done.extend(self.open.remove(&address));
+
+            self.open.insert(
+                address,
+                SdoTransaction::new(message, direction, index, sub_index, complete_access),
+            );
+        }
+
+        let Some(transaction) = self.open.get_mut(&address) else {
+            return done;
+        };
+
+        transaction.end_time = message.time;
+        transaction.end_packet = message.packet_number;
+
+        let complete = match sdo {
+            Sdo::DownloadRequest {
+                expedited,
+                size,
+                data,
+                ..
+            } => {
+                transaction.expedited = expedited;
+                transaction.size = size;
+                transaction.data = data;
+
+                false
+            }
+            Sdo::UploadRequest { .. } => false,
+            Sdo::UploadResponse {
+                expedited,
+                size,
+                data,
+                ..
+            } => {
+                transaction.expedited = expedited;
+                transaction.size = size;
+                transaction.data = data;
+
+                expedited || transaction.data_complete()
+            }
+            Sdo::DownloadResponse { .. } => transaction.expedited || transaction.data_complete(),
+            Sdo::DownloadSegmentRequest { last, data, .. } => {
+                transaction.segments += 1;
+                transaction.data.extend(data);
+
+                // Wait for the SubDevice to acknowledge the last segment.
+                if last {
+                    transaction
+                        .size
+                        .get_or_insert(transaction.data.len() as u32);
+                }
+
+                false
+            }
+            Sdo::DownloadSegmentResponse { .. } => transaction.data_complete(),
+            Sdo::UploadSegmentRequest { .. } => false,
+            Sdo::UploadSegmentResponse { last, data, .. } => {
+                transaction.segments += 1;
+                transaction.data.extend(data);
+
+                last
+            }
+            Sdo::Abort { code, .. } => {
+                transaction.result = SdoResult::Aborted(code);
+
+                true
+            }
+        };
+
+        if complete {
+            let mut transaction = self.open.remove(&address).unwrap();
+
+            if transaction.result == SdoResult::Incomplete {
+                transaction.result = SdoResult::Ok;
+            }
+
+            done.push(transaction);
+        }
+
+        done
+    }
+}
+
+/// Reconstruct every SDO transaction in a capture, in order of completion.
+pub fn sdo_transactions(exchanges: impl Iterator<Item = PduExchange>) -> Vec<SdoTransaction> {
+    let mut reader = SdoReader::default();
+    let mut transactions = Vec::new();
+
+    for exchange in exchanges {
+        transactions.extend(reader.push(&exchange));
+    }
+
+    transactions.extend(reader.finish());
+
+    transactions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mailbox::tests::{mailbox_read, mailbox_write, sm_config, ADDRESS};
+
+    /// SDO request sent by the MainDevice.
+    fn request(sdo: &[u8]) -> (MailboxDirection, Vec<u8>) {
+        (MailboxDirection::ToSubDevice, [&[0x00, 0x20], sdo].concat())
+    }
+
+    /// SDO response returned by the SubDevice.
+    fn response(sdo: &[u8]) -> (MailboxDirection, Vec<u8>) {
+        (
+            MailboxDirection::FromSubDevice,
+            [&[0x00, 0x30], sdo].concat(),
+        )
+    }
+
+    /// Mailbox exchanges carrying the given messages after the SyncManager configuration, two
+    /// packets apart.
+    fn exchanges(messages: &[(MailboxDirection, Vec<u8>)]) -> Vec<PduExchange> {
+        std::iter::once(sm_config(1))
+            .chain(
+                messages
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (direction, payload))| {
+                        let packet_number = 3 + i * 2;
+
+                        match direction {
+                            MailboxDirection::ToSubDevice => mailbox_write(payload, packet_number),
+                            MailboxDirection::FromSubDevice => mailbox_read(payload, packet_number),
+                        }
+                    }),
+            )
+            .collect()
+    }
+
+    fn transactions(messages: &[(MailboxDirection, Vec<u8>)]) -> Vec<SdoTransaction> {
+        sdo_transactions(exchanges(messages).into_iter())
+    }
+
+    fn sdos(messages: &[(MailboxDirection, Vec<u8>)]) -> Vec<Sdo> {
+        let mut reader = MailboxReader::default();
+
+        exchanges(messages)
+            .iter()
+            .filter_map(|exchange| reader.push(exchange))
+            .filter_map(|message| Sdo::parse(&message))
+            .collect()
+    }
+
+    /// Upload of 10 bytes from 0x1008:00 in two segments.
+    fn segmented_upload() -> Vec<(MailboxDirection, Vec<u8>)> {
+        vec![
+            request(&[0x40, 0x08, 0x10, 0x00, 0, 0, 0, 0]),
+            response(&[0x41, 0x08, 0x10, 0x00, 10, 0, 0, 0]),
+            request(&[0x60, 0, 0, 0, 0, 0, 0, 0]),
+            response(&[0x00, b'E', b'K', b'1', b'9', b'1', b'4', b' ']),
+            request(&[0x70, 0, 0, 0, 0, 0, 0, 0]),
+            // Last segment with 4 unused bytes
+            response(&[0x19, b'I', b'/', b'O', 0, 0, 0, 0]),
+        ]
+    }
+
+    #[test]
+    fn coe_header() {
+        assert_eq!(
+            CoeHeader::parse(&[0x05, 0x30]),
+            Some(CoeHeader {
+                number: 5,
+                service: CoeService::SdoResponse,
+            })
+        );
+
+        assert_eq!(CoeHeader::parse(&[0x00]), None);
+    }
+
+    #[test]
+    fn expedited_download() {
+        // 2 bytes to 0x1c12:00, 2 unused bytes
+        let transactions = transactions(&[
+            request(&[0x2b, 0x12, 0x1c, 0x00, 0x01, 0x00, 0xaa, 0xaa]),
+            response(&[0x60, 0x12, 0x1c, 0x00, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(
+            transactions,
+            [SdoTransaction {
+                address: ADDRESS,
+                direction: SdoDirection::Download,
+                index: 0x1c12,
+                sub_index: 0,
+                complete_access: false,
+                expedited: true,
+                segments: 0,
+                size: Some(2),
+                data: vec![0x01, 0x00],
+                result: SdoResult::Ok,
+                start_time: Duration::from_micros(300),
+                end_time: Duration::from_micros(510),
+                start_packet: 3,
+                end_packet: 6,
+            }]
+        );
+    }
+
+    #[test]
+    fn expedited_upload() {
+        let transactions = transactions(&[
+            request(&[0x40, 0x00, 0x10, 0x00, 0, 0, 0, 0]),
+            response(&[0x43, 0x00, 0x10, 0x00, 0x92, 0x01, 0x02, 0x00]),
+        ]);
+
+        assert_eq!(transactions.len(), 1);
+
+        let transaction = &transactions[0];
+
+        assert_eq!(transaction.direction, SdoDirection::Upload);
+        assert_eq!(transaction.index, 0x1000);
+        assert!(transaction.expedited);
+        assert_eq!(transaction.size, Some(4));
+        assert_eq!(transaction.data, [0x92, 0x01, 0x02, 0x00]);
+        assert_eq!(transaction.result, SdoResult::Ok);
+    }
+
+    #[test]
+    fn normal_download() {
+        // Complete access to 0x1c13, data sent in the initiate request
+        let transactions = transactions(&[
+            request(&[0x31, 0x13, 0x1c, 0x00, 4, 0, 0, 0, 0x01, 0x00, 0x00, 0x1a]),
+            response(&[0x60, 0x13, 0x1c, 0x00, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(transactions.len(), 1);
+
+        let transaction = &transactions[0];
+
+        assert_eq!(transaction.direction, SdoDirection::Download);
+        assert!(transaction.complete_access);
+        assert!(!transaction.expedited);
+        assert_eq!(transaction.segments, 0);
+        assert_eq!(transaction.size, Some(4));
+        assert_eq!(transaction.data, [0x01, 0x00, 0x00, 0x1a]);
+        assert_eq!(transaction.result, SdoResult::Ok);
+        assert_eq!(transaction.end_packet, 6);
+    }
+
+    #[test]
+    fn normal_upload() {
+        let transactions = transactions(&[
+            request(&[0x40, 0x08, 0x10, 0x00, 0, 0, 0, 0]),
+            response(&[
+                0x41, 0x08, 0x10, 0x00, 6, 0, 0, 0, b'E', b'K', b'1', b'1', b'0', b'0',
+            ]),
+        ]);
+
+        assert_eq!(transactions.len(), 1);
+
+        let transaction = &transactions[0];
+
+        assert!(!transaction.expedited);
+        assert_eq!(transaction.segments, 0);
+        assert_eq!(transaction.size, Some(6));
+        assert_eq!(transaction.data, b"EK1100");
+        assert_eq!(transaction.result, SdoResult::Ok);
+    }
+
+    #[test]
+    fn segmented_upload_reassembly() {
+        let transactions = transactions(&segmented_upload());
+
+        assert_eq!(transactions.len(), 1);
+
+        let transaction = &transactions[0];
+
+        assert_eq!(transaction.index, 0x1008);
+        assert!(!transaction.expedited);
+        assert_eq!(transaction.segments, 2);
+        assert_eq!(transaction.size, Some(10));
+        assert_eq!(transaction.data, b"EK1914 I/O");
+        assert_eq!(transaction.result, SdoResult::Ok);
+        assert_eq!(transaction.start_packet, 3);
+        assert_eq!(transaction.end_packet, 14);
+    }
+
+    #[test]
+    fn segmented_download_reassembly() {
+        let transactions = transactions(&[
+            request(&[0x21, 0x00, 0x20, 0x01, 10, 0, 0, 0]),
+            response(&[0x60, 0x00, 0x20, 0x01, 0, 0, 0, 0]),
+            request(&[0x00, 1, 2, 3, 4, 5, 6, 7]),
+            response(&[0x20, 0, 0, 0, 0, 0, 0, 0]),
+            // Last segment with 4 unused bytes
+            request(&[0x19, 8, 9, 10, 0, 0, 0, 0]),
+            response(&[0x30, 0, 0, 0, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(transactions.len(), 1);
+
+        let transaction = &transactions[0];
+
+        assert_eq!(transaction.direction, SdoDirection::Download);
+        assert_eq!((transaction.index, transaction.sub_index), (0x2000, 1));
+        assert_eq!(transaction.segments, 2);
+        assert_eq!(transaction.size, Some(10));
+        assert_eq!(transaction.data, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(transaction.result, SdoResult::Ok);
+        assert_eq!(transaction.end_packet, 14);
+    }
+
+    #[test]
+    fn segment_toggle() {
+        let toggles = sdos(&segmented_upload())
+            .into_iter()
+            .filter_map(|sdo| match sdo {
+                Sdo::UploadSegmentRequest { toggle } => Some(("request", toggle)),
+                Sdo::UploadSegmentResponse { toggle, .. } => Some(("response", toggle)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            toggles,
+            [
+                ("request", false),
+                ("response", false),
+                ("request", true),
+                ("response", true),
+            ]
+        );
+
+        // The unused byte count only applies to minimum length segments
+        assert_eq!(
+            parse_segment(&[0x1f, 1, 2, 3, 4, 5, 6, 7]),
+            Some((true, true, vec![]))
+        );
+        assert_eq!(
+            parse_segment(&[0x0e, 1, 2, 3, 4, 5, 6, 7, 8]),
+            Some((false, false, vec![1, 2, 3, 4, 5, 6, 7, 8]))
+        );
+    }
+
+    #[test]
+    fn abort() {
+        let transactions = transactions(&[
+            request(&[0x40, 0x00, 0x60, 0x01, 0, 0, 0, 0]),
+            response(&[0x80, 0x00, 0x60, 0x01, 0x00, 0x00, 0x02, 0x06]),
+        ]);
+
+        assert_eq!(transactions.len(), 1);
+
+        let transaction = &transactions[0];
+
+        assert_eq!(transaction.result, SdoResult::Aborted(0x0602_0000));
+        assert_eq!(
+            transaction.result.to_string(),
+            "abort 0x06020000 (Object does not exist in the object dictionary)"
+        );
+        assert_eq!(transaction.end_packet, 6);
+    }
+
+    #[test]
+    fn abort_during_segments() {
+        let mut messages = segmented_upload();
+
+        // SubDevice aborts the second segment instead of sending it
+        messages[5] = response(&[0x80, 0x08, 0x10, 0x00, 0x00, 0x00, 0x03, 0x05]);
+
+        let transactions = transactions(&messages);
+
+        assert_eq!(transactions.len(), 1);
+
+        let transaction = &transactions[0];
+
+        assert_eq!(transaction.segments, 1);
+        assert_eq!(transaction.data, b"EK1914 ");
+        assert_eq!(transaction.result, SdoResult::Aborted(0x0503_0000));
+        assert_eq!(abort_description(0x0503_0000), "Toggle bit not changed");
+        assert_eq!(transaction.end_packet, 14);
+    }
+
+    #[test]
+    fn superseded() {
+        // Upload left waiting for its segments when the next request is sent
+        let transactions = transactions(&[
+            request(&[0x40, 0x08, 0x10, 0x00, 0, 0, 0, 0]),
+            response(&[0x41, 0x08, 0x10, 0x00, 10, 0, 0, 0]),
+            request(&[0x40, 0x00, 0x10, 0x00, 0, 0, 0, 0]),
+        ]);
+
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].index, 0x1008);
+        assert_eq!(transactions[0].result, SdoResult::Incomplete);
+        assert_eq!(transactions[1].index, 0x1000);
+        assert_eq!(transactions[1].result, SdoResult::Incomplete);
+    }
+}
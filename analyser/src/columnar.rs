@@ -0,0 +1,469 @@
+//! Arrow record batches of PDU statistics, per-cycle metrics and decoded process data, for
+//! writing to Arrow IPC or Parquet files.
+//!
+//! Times are unsigned 64 bit nanoseconds relative to the start of the capture, and repetitive
+//! strings like the scenario and command are dictionary encoded so they load as categoricals in
+//! pandas or polars. Arrow IPC files can only hold one dictionary per column, so dictionaries are
+//! collected from a whole capture up front and shared by every batch written from it.
+
+use crate::{
+    process_image::{Sample, Signal},
+    PduStat,
+};
+use arrow_array::{
+    builder::{StringBuilder, UInt16Builder, UInt32Builder, UInt64Builder, UInt8Builder},
+    types::Int32Type,
+    ArrayRef, DictionaryArray, Int32Array, RecordBatch, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+/// Recommended number of rows per batch, to bound memory use on large captures.
+pub const BATCH_SIZE: usize = 64 * 1024;
+
+fn dictionary() -> DataType {
+    DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8))
+}
+
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// Distinct values of a dictionary encoded column, shared by every batch of a capture.
+#[derive(Debug, Clone)]
+pub struct Dictionary {
+    values: ArrayRef,
+    keys: HashMap<String, i32>,
+}
+
+impl Dictionary {
+    /// Collect distinct values in order of first appearance.
+    pub fn new<S: AsRef<str>>(values: impl IntoIterator<Item = S>) -> Self {
+        let mut keys = HashMap::new();
+        let mut distinct = Vec::new();
+
+        for value in values {
+            let value = value.as_ref();
+
+            if !keys.contains_key(value) {
+                let key = i32::try_from(distinct.len()).expect("Too many dictionary values");
+
+                keys.insert(value.to_string(), key);
+                distinct.push(value.to_string());
+            }
+        }
+
+        Self {
+            values: Arc::new(StringArray::from(distinct)),
+            keys,
+        }
+    }
+
+    /// Encode a column, where every value must have been passed to [`Dictionary::new`].
+    fn column<S: AsRef<str>>(&self, values: impl IntoIterator<Item = Option<S>>) -> ArrayRef {
+        let keys = values
+            .into_iter()
+            .map(|value| value.map(|value| self.keys[value.as_ref()]))
+            .collect::<Int32Array>();
+
+        Arc::new(
+            DictionaryArray::<Int32Type>::try_new(keys, Arc::clone(&self.values))
+                .expect("Invalid dictionary keys"),
+        )
+    }
+}
+
+fn batch(schema: SchemaRef, columns: Vec<ArrayRef>) -> RecordBatch {
+    RecordBatch::try_new(schema, columns).expect("Columns don't match schema")
+}
+
+/// Schema of [`pdu_stats`] batches.
+pub fn pdu_stats_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("scenario", dictionary(), false),
+        Field::new("packet_number", DataType::UInt64, false),
+        Field::new("index", DataType::UInt8, false),
+        Field::new("command", dictionary(), false),
+        Field::new("register", dictionary(), false),
+        Field::new("value", DataType::Utf8, false),
+        Field::new("tx_time_ns", DataType::UInt64, false),
+        Field::new("rx_time_ns", DataType::UInt64, false),
+        Field::new("delta_time_ns", DataType::UInt64, false),
+        Field::new("rx_packet_number", DataType::UInt64, true),
+        Field::new("cycle", DataType::UInt64, false),
+        Field::new("addressing", dictionary(), false),
+        Field::new("station_address", DataType::UInt16, true),
+        Field::new("logical_address", DataType::UInt32, true),
+        Field::new("register_address", DataType::UInt16, true),
+        Field::new("data_len", DataType::UInt64, false),
+        Field::new("working_counter", DataType::UInt16, true),
+        Field::new("expected_working_counter", DataType::UInt16, true),
+        Field::new("pdus_in_frame", DataType::UInt64, false),
+        Field::new("frame_size", DataType::UInt64, false),
+        Field::new("tx_source", dictionary(), false),
+        Field::new("rx_source", dictionary(), true),
+    ]))
+}
+
+/// Dictionaries of the [`pdu_stats`] columns of a capture.
+#[derive(Debug, Clone)]
+pub struct PduStatsDictionaries {
+    scenario: Dictionary,
+    command: Dictionary,
+    register: Dictionary,
+    addressing: Dictionary,
+    tx_source: Dictionary,
+    rx_source: Dictionary,
+}
+
+impl PduStatsDictionaries {
+    /// Collect dictionaries from every PDU of a capture.
+    pub fn new(pairs: &[PduStat]) -> Self {
+        Self {
+            scenario: Dictionary::new(pairs.iter().map(|stat| &stat.scenario)),
+            command: Dictionary::new(pairs.iter().map(|stat| &stat.command)),
+            register: Dictionary::new(pairs.iter().map(|stat| &stat.register)),
+            addressing: Dictionary::new(pairs.iter().map(addressing)),
+            tx_source: Dictionary::new(pairs.iter().map(|stat| stat.tx_source.to_string())),
+            rx_source: Dictionary::new(
+                pairs
+                    .iter()
+                    .filter_map(|stat| stat.rx_source.map(|source| source.to_string())),
+            ),
+        }
+    }
+}
+
+fn addressing(stat: &PduStat) -> &'static str {
+    stat.addressing.map_or("", |addressing| addressing.as_str())
+}
+
+/// One row per matched PDU, with the same columns as the CSV output.
+pub fn pdu_stats(pairs: &[PduStat], dictionaries: &PduStatsDictionaries) -> RecordBatch {
+    let mut packet_number = UInt64Builder::with_capacity(pairs.len());
+    let mut index = UInt8Builder::with_capacity(pairs.len());
+    let mut value = StringBuilder::new();
+    let mut tx_time = UInt64Builder::with_capacity(pairs.len());
+    let mut rx_time = UInt64Builder::with_capacity(pairs.len());
+    let mut delta_time = UInt64Builder::with_capacity(pairs.len());
+    let mut rx_packet_number = UInt64Builder::with_capacity(pairs.len());
+    let mut cycle = UInt64Builder::with_capacity(pairs.len());
+    let mut station_address = UInt16Builder::with_capacity(pairs.len());
+    let mut logical_address = UInt32Builder::with_capacity(pairs.len());
+    let mut register_address = UInt16Builder::with_capacity(pairs.len());
+    let mut data_len = UInt64Builder::with_capacity(pairs.len());
+    let mut working_counter = UInt16Builder::with_capacity(pairs.len());
+    let mut expected_working_counter = UInt16Builder::with_capacity(pairs.len());
+    let mut pdus_in_frame = UInt64Builder::with_capacity(pairs.len());
+    let mut frame_size = UInt64Builder::with_capacity(pairs.len());
+
+    for stat in pairs {
+        packet_number.append_value(stat.packet_number as u64);
+        index.append_value(stat.index);
+        value.append_value(&stat.value);
+        tx_time.append_value(nanos(stat.tx_time));
+        rx_time.append_value(nanos(stat.rx_time));
+        delta_time.append_value(nanos(stat.delta_time));
+        rx_packet_number.append_option(stat.rx_packet_number.map(|number| number as u64));
+        cycle.append_value(stat.cycle as u64);
+        station_address.append_option(stat.station_address);
+        logical_address.append_option(stat.logical_address);
+        register_address.append_option(stat.register_address);
+        data_len.append_value(stat.data_len as u64);
+        working_counter.append_option(stat.working_counter);
+        expected_working_counter.append_option(stat.expected_working_counter);
+        pdus_in_frame.append_value(stat.pdus_in_frame as u64);
+        frame_size.append_value(stat.frame_size as u64);
+    }
+
+    batch(
+        pdu_stats_schema(),
+        vec![
+            dictionaries
+                .scenario
+                .column(pairs.iter().map(|stat| Some(&stat.scenario))),
+            Arc::new(packet_number.finish()),
+            Arc::new(index.finish()),
+            dictionaries
+                .command
+                .column(pairs.iter().map(|stat| Some(&stat.command))),
+            dictionaries
+                .register
+                .column(pairs.iter().map(|stat| Some(&stat.register))),
+            Arc::new(value.finish()),
+            Arc::new(tx_time.finish()),
+            Arc::new(rx_time.finish()),
+            Arc::new(delta_time.finish()),
+            Arc::new(rx_packet_number.finish()),
+            Arc::new(cycle.finish()),
+            dictionaries
+                .addressing
+                .column(pairs.iter().map(|stat| Some(addressing(stat)))),
+            Arc::new(station_address.finish()),
+            Arc::new(logical_address.finish()),
+            Arc::new(register_address.finish()),
+            Arc::new(data_len.finish()),
+            Arc::new(working_counter.finish()),
+            Arc::new(expected_working_counter.finish()),
+            Arc::new(pdus_in_frame.finish()),
+            Arc::new(frame_size.finish()),
+            dictionaries
+                .tx_source
+                .column(pairs.iter().map(|stat| Some(stat.tx_source.to_string()))),
+            dictionaries.rx_source.column(
+                pairs
+                    .iter()
+                    .map(|stat| stat.rx_source.map(|source| source.to_string())),
+            ),
+        ],
+    )
+}
+
+/// Schema of [`cycles`] batches.
+pub fn cycles_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("scenario", dictionary(), false),
+        Field::new("cycle", DataType::UInt64, false),
+        Field::new("packet_number", DataType::UInt64, false),
+        Field::new("tx_time_ns", DataType::UInt64, false),
+        Field::new("round_trip_ns", DataType::UInt64, false),
+        Field::new("cycle_delta_ns", DataType::UInt64, true),
+    ]))
+}
+
+/// One row per frame with the round trip and cycle delta times used by [`stats`](crate::stats).
+///
+/// `scenario` holds the scenario names of the whole capture. `previous` is the PDU before the first
+/// in `pairs`, if any, to compute the first cycle delta when writing a capture in several batches.
+pub fn cycles(pairs: &[PduStat], scenario: &Dictionary, previous: Option<&PduStat>) -> RecordBatch {
+    let mut cycle = UInt64Builder::with_capacity(pairs.len());
+    let mut packet_number = UInt64Builder::with_capacity(pairs.len());
+    let mut tx_time = UInt64Builder::with_capacity(pairs.len());
+    let mut round_trip = UInt64Builder::with_capacity(pairs.len());
+    let mut cycle_delta = UInt64Builder::with_capacity(pairs.len());
+
+    for (i, stat) in pairs.iter().enumerate() {
+        let previous = match i {
+            0 => previous,
+            i => pairs.get(i - 1),
+        };
+
+        cycle.append_value(stat.cycle as u64);
+        packet_number.append_value(stat.packet_number as u64);
+        tx_time.append_value(nanos(stat.tx_time));
+        round_trip.append_value(nanos(stat.delta_time));
+        cycle_delta.append_option(previous.map(|previous| nanos(stat.tx_time - previous.tx_time)));
+    }
+
+    batch(
+        cycles_schema(),
+        vec![
+            scenario.column(pairs.iter().map(|stat| Some(&stat.scenario))),
+            Arc::new(cycle.finish()),
+            Arc::new(packet_number.finish()),
+            Arc::new(tx_time.finish()),
+            Arc::new(round_trip.finish()),
+            Arc::new(cycle_delta.finish()),
+        ],
+    )
+}
+
+/// Schema of [`process_data`] batches.
+pub fn process_data_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("scenario", dictionary(), false),
+        Field::new("time_ns", DataType::UInt64, false),
+        Field::new("packet_number", DataType::UInt64, false),
+        Field::new("cycle", DataType::UInt64, false),
+        Field::new("address", DataType::UInt16, false),
+        Field::new("direction", dictionary(), false),
+        Field::new("signal", dictionary(), false),
+        Field::new("logical_bit", DataType::UInt64, false),
+        Field::new("bit_len", DataType::UInt8, false),
+        Field::new("value", DataType::UInt64, false),
+    ]))
+}
+
+/// Dictionaries of the [`process_data`] columns of a capture.
+#[derive(Debug, Clone)]
+pub struct ProcessDataDictionaries {
+    scenario: Dictionary,
+    direction: Dictionary,
+    signal: Dictionary,
+}
+
+impl ProcessDataDictionaries {
+    /// Collect dictionaries from every signal of a capture.
+    pub fn new<'a>(scenario: &str, signals: impl IntoIterator<Item = &'a Signal> + Clone) -> Self {
+        Self {
+            scenario: Dictionary::new([scenario]),
+            direction: Dictionary::new(
+                signals
+                    .clone()
+                    .into_iter()
+                    .map(|signal| signal.direction.to_string()),
+            ),
+            signal: Dictionary::new(signals.into_iter().map(Signal::to_string)),
+        }
+    }
+}
+
+/// One row per sample of each process image signal.
+pub fn process_data(
+    scenario_name: &str,
+    samples: &[(&Signal, &Sample)],
+    dictionaries: &ProcessDataDictionaries,
+) -> RecordBatch {
+    let mut time = UInt64Builder::with_capacity(samples.len());
+    let mut packet_number = UInt64Builder::with_capacity(samples.len());
+    let mut cycle = UInt64Builder::with_capacity(samples.len());
+    let mut address = UInt16Builder::with_capacity(samples.len());
+    let mut signal_names = Vec::with_capacity(samples.len());
+    let mut logical_bit = UInt64Builder::with_capacity(samples.len());
+    let mut bit_len = UInt8Builder::with_capacity(samples.len());
+    let mut value = UInt64Builder::with_capacity(samples.len());
+
+    let mut last_signal: Option<(&Signal, String)> = None;
+
+    for (signal, sample) in samples {
+        // Samples are usually grouped by signal, so avoid formatting the same name every row
+        let name = match &last_signal {
+            Some((last, name)) if last == signal => name.clone(),
+            _ => {
+                let name = signal.to_string();
+
+                last_signal = Some((signal, name.clone()));
+
+                name
+            }
+        };
+
+        time.append_value(nanos(sample.time));
+        packet_number.append_value(sample.packet_number as u64);
+        cycle.append_value(sample.cycle as u64);
+        address.append_value(signal.address);
+        signal_names.push(Some(name));
+        logical_bit.append_value(signal.logical_bit);
+        bit_len.append_value(signal.bit_len);
+        value.append_value(sample.value);
+    }
+
+    batch(
+        process_data_schema(),
+        vec![
+            dictionaries
+                .scenario
+                .column(samples.iter().map(|_| Some(scenario_name))),
+            Arc::new(time.finish()),
+            Arc::new(packet_number.finish()),
+            Arc::new(cycle.finish()),
+            Arc::new(address.finish()),
+            dictionaries.direction.column(
+                samples
+                    .iter()
+                    .map(|(signal, _)| Some(signal.direction.to_string())),
+            ),
+            dictionaries.signal.column(signal_names),
+            Arc::new(logical_bit.finish()),
+            Arc::new(bit_len.finish()),
+            Arc::new(value.finish()),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::Addressing;
+    use arrow_array::{cast::AsArray, Array};
+    use arrow_ipc::{reader::FileReader, writer::FileWriter};
+    use smoltcp::wire::EthernetAddress;
+    use std::fs::File;
+
+    fn stat(i: usize) -> PduStat {
+        let answered = !i.is_multiple_of(3);
+
+        PduStat {
+            scenario: String::from("test"),
+            packet_number: 2 * i + 1,
+            index: i as u8,
+            // Values only seen in a later batch must still be in the shared dictionary
+            command: String::from(if i < BATCH_SIZE { "LRW" } else { "FPRD" }),
+            register: String::new(),
+            value: String::new(),
+            tx_time: Duration::from_micros(i as u64 * 1000),
+            rx_time: Duration::from_micros(i as u64 * 1000 + 50),
+            delta_time: Duration::from_micros(50),
+            rx_packet_number: answered.then_some(2 * i + 2),
+            cycle: i,
+            addressing: Some(Addressing::Logical),
+            station_address: None,
+            logical_address: Some(0),
+            register_address: None,
+            data_len: 4,
+            working_counter: Some(3),
+            expected_working_counter: Some(3),
+            pdus_in_frame: 1,
+            frame_size: 60,
+            tx_source: EthernetAddress([0x10, 0x10, 0x10, 0x10, 0x10, 0x10]),
+            rx_source: answered.then_some(EthernetAddress([0x12, 0x10, 0x10, 0x10, 0x10, 0x10])),
+        }
+    }
+
+    #[test]
+    fn ipc_file_with_several_batches() {
+        let pairs = (0..BATCH_SIZE + 10).map(stat).collect::<Vec<_>>();
+        let path = std::env::temp_dir().join(format!("columnar-{}.arrow", std::process::id()));
+
+        let dictionaries = PduStatsDictionaries::new(&pairs);
+        let scenario = Dictionary::new(pairs.iter().map(|stat| &stat.scenario));
+
+        let mut writer =
+            FileWriter::try_new(File::create(&path).expect("Create"), &pdu_stats_schema())
+                .expect("Writer");
+
+        for chunk in pairs.chunks(BATCH_SIZE) {
+            writer
+                .write(&pdu_stats(chunk, &dictionaries))
+                .expect("Write batch");
+        }
+
+        writer.finish().expect("Finish");
+
+        let mut cycle_writer = FileWriter::try_new(Vec::new(), &cycles_schema()).expect("Writer");
+
+        for (i, chunk) in pairs.chunks(BATCH_SIZE).enumerate() {
+            let previous = (i * BATCH_SIZE).checked_sub(1).and_then(|i| pairs.get(i));
+
+            cycle_writer
+                .write(&cycles(chunk, &scenario, previous))
+                .expect("Write batch");
+        }
+
+        cycle_writer.finish().expect("Finish");
+
+        let reader = FileReader::try_new(File::open(&path).expect("Open"), None).expect("Reader");
+        let batches = reader.collect::<Result<Vec<_>, _>>().expect("Read batches");
+
+        std::fs::remove_file(&path).expect("Remove");
+
+        assert_eq!(batches.len(), 2);
+        assert_eq!(
+            batches.iter().map(RecordBatch::num_rows).sum::<usize>(),
+            pairs.len()
+        );
+
+        let last = &batches[1];
+        let command = last
+            .column_by_name("command")
+            .expect("Command")
+            .as_dictionary::<Int32Type>();
+        let values = command.values().as_string::<i32>();
+
+        assert_eq!(values.value(command.key(0).expect("Key")), "FPRD");
+
+        let rx_source = last.column_by_name("rx_source").expect("RX source");
+
+        assert_eq!(rx_source.null_count(), 3);
+    }
+}
@@ -0,0 +1,396 @@
+//! Classify EtherCAT commands by how they address SubDevices and what they do with the data.
+
+use crate::pdu::{
+    Command, Reads, Writes, APRD, APRW, APWR, ARMW, BRD, BRW, BWR, FPRD, FPRW, FPWR, FRMW, LRD,
+    LRW, LWR, NOP,
+};
+
+/// How a command selects the SubDevice(s) it's processed by, ETG1000.4 section 5.4.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Addressing {
+    /// Position in the network, incremented by every SubDevice the PDU passes through.
+    AutoIncrement,
+
+    /// Configured station address.
+    Configured,
+
+    /// Every SubDevice.
+    Broadcast,
+
+    /// FMMU mapped logical address space.
+    Logical,
+}
+
+impl Addressing {
+    /// Name as shown to the user, e.g. `auto increment`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AutoIncrement => "auto increment",
+            Self::Configured => "configured",
+            Self::Broadcast => "broadcast",
+            Self::Logical => "logical",
+        }
+    }
+}
+
+impl std::fmt::Display for Addressing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// What a command does with the data it carries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Access {
+    Read,
+    Write,
+
+    /// Read and write in the same PDU (APRW, FPRW, BRW, LRW).
+    ReadWrite,
+
+    /// Read from the addressed SubDevice and written to every other SubDevice (FRMW, ARMW).
+    ReadMultipleWrite,
+}
+
+impl std::fmt::Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::ReadWrite => "read/write",
+            Self::ReadMultipleWrite => "read multiple write",
+        };
+
+        f.pad(s)
+    }
+}
+
+/// Command code as sent on the wire.
+pub fn code(command: &Command) -> u8 {
+    match command {
+        Command::Nop => NOP,
+        Command::Read(read) => match read {
+            Reads::Aprd { .. } => APRD,
+            Reads::Fprd { .. } => FPRD,
+            Reads::Brd { .. } => BRD,
+            Reads::Lrd { .. } => LRD,
+            Reads::Frmw { .. } => FRMW,
+            Reads::Armw { .. } => ARMW,
+        },
+        Command::Write(write) => match write {
+            Writes::Bwr { .. } => BWR,
+            Writes::Apwr { .. } => APWR,
+            Writes::Fpwr { .. } => FPWR,
+            Writes::Lwr { .. } => LWR,
+            Writes::Lrw { .. } => LRW,
+            Writes::Aprw { .. } => APRW,
+            Writes::Fprw { .. } => FPRW,
+            Writes::Brw { .. } => BRW,
+        },
+    }
+}
+
+/// Command mnemonic, e.g. `FPRD`.
+pub fn name(command: &Command) -> &'static str {
+    match command {
+        Command::Nop => "NOP",
+        Command::Read(read) => match read {
+            Reads::Aprd { .. } => "APRD",
+            Reads::Fprd { .. } => "FPRD",
+            Reads::Brd { .. } => "BRD",
+            Reads::Lrd { .. } => "LRD",
+            Reads::Frmw { .. } => "FRMW",
+            Reads::Armw { .. } => "ARMW",
+        },
+        Command::Write(write) => match write {
+            Writes::Bwr { .. } => "BWR",
+            Writes::Apwr { .. } => "APWR",
+            Writes::Fpwr { .. } => "FPWR",
+            Writes::Lwr { .. } => "LWR",
+            Writes::Lrw { .. } => "LRW",
+            Writes::Aprw { .. } => "APRW",
+            Writes::Fprw { .. } => "FPRW",
+            Writes::Brw { .. } => "BRW",
+        },
+    }
+}
+
+/// Addressing mode of a command, or `None` for NOP.
+pub fn addressing(command: &Command) -> Option<Addressing> {
+    match command {
+        Command::Nop => None,
+        Command::Read(Reads::Aprd { .. } | Reads::Armw { .. })
+        | Command::Write(Writes::Apwr { .. } | Writes::Aprw { .. }) => {
+            Some(Addressing::AutoIncrement)
+        }
+        Command::Read(Reads::Fprd { .. } | Reads::Frmw { .. })
+        | Command::Write(Writes::Fpwr { .. } | Writes::Fprw { .. }) => Some(Addressing::Configured),
+        Command::Read(Reads::Brd { .. })
+        | Command::Write(Writes::Bwr { .. } | Writes::Brw { .. }) => Some(Addressing::Broadcast),
+        Command::Read(Reads::Lrd { .. })
+        | Command::Write(Writes::Lwr { .. } | Writes::Lrw { .. }) => Some(Addressing::Logical),
+    }
+}
+
+/// Access kind of a command, or `None` for NOP.
+pub fn access(command: &Command) -> Option<Access> {
+    match command {
+        Command::Nop => None,
+        Command::Read(Reads::Frmw { .. } | Reads::Armw { .. }) => Some(Access::ReadMultipleWrite),
+        Command::Read(_) => Some(Access::Read),
+        Command::Write(
+            Writes::Lrw { .. } | Writes::Aprw { .. } | Writes::Fprw { .. } | Writes::Brw { .. },
+        ) => Some(Access::ReadWrite),
+        Command::Write(_) => Some(Access::Write),
+    }
+}
+
+/// Raw SubDevice address field of a register command.
+///
+/// This is the configured station address for configured addressing, and the auto increment
+/// address for auto increment and broadcast addressing. Logical commands and NOP return `None`.
+pub fn subdevice_address(command: &Command) -> Option<u16> {
+    match command {
+        Command::Nop => None,
+        Command::Read(read) => match read {
+            Reads::Aprd { address, .. }
+            | Reads::Fprd { address, .. }
+            | Reads::Brd { address, .. }
+            | Reads::Frmw { address, .. }
+            | Reads::Armw { address, .. } => Some(*address),
+            Reads::Lrd { .. } => None,
+        },
+        Command::Write(write) => match write {
+            Writes::Bwr { address, .. }
+            | Writes::Apwr { address, .. }
+            | Writes::Fpwr { address, .. }
+            | Writes::Aprw { address, .. }
+            | Writes::Fprw { address, .. }
+            | Writes::Brw { address, .. } => Some(*address),
+            Writes::Lwr { .. } | Writes::Lrw { .. } => None,
+        },
+    }
+}
+
+/// Configured station address of a configured address command.
+pub fn configured_address(command: &Command) -> Option<u16> {
+    match addressing(command)? {
+        Addressing::Configured => subdevice_address(command),
+        _ => None,
+    }
+}
+
+/// Position in the network addressed by an auto increment command, starting at `0` for the
+/// SubDevice closest to the MainDevice.
+pub fn position(command: &Command) -> Option<u16> {
+    match addressing(command)? {
+        Addressing::AutoIncrement => subdevice_address(command).map(|a| 0u16.wrapping_sub(a)),
+        _ => None,
+    }
+}
+
+/// Register address accessed by a command, or `None` for logical addressing.
+pub fn register(command: &Command) -> Option<u16> {
+    match command {
+        Command::Nop => None,
+        Command::Read(read) => match read {
+            Reads::Aprd { register, .. }
+            | Reads::Fprd { register, .. }
+            | Reads::Brd { register, .. }
+            | Reads::Frmw { register, .. }
+            | Reads::Armw { register, .. } => Some(*register),
+            Reads::Lrd { .. } => None,
+        },
+        Command::Write(write) => match write {
+            Writes::Bwr { register, .. }
+            | Writes::Apwr { register, .. }
+            | Writes::Fpwr { register, .. }
+            | Writes::Aprw { register, .. }
+            | Writes::Fprw { register, .. }
+            | Writes::Brw { register, .. } => Some(*register),
+            Writes::Lwr { .. } | Writes::Lrw { .. } => None,
+        },
+    }
+}
+
+/// Logical address accessed by a logical command.
+pub fn logical_address(command: &Command) -> Option<u32> {
+    match command {
+        Command::Read(Reads::Lrd { address })
+        | Command::Write(Writes::Lwr { address } | Writes::Lrw { address }) => Some(*address),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdu::parse_command;
+
+    /// Address field of every test command: SubDevice address `0xfffe`, register `0x0130`, or
+    /// logical address `0x0130fffe`.
+    const ADDRESS: [u8; 4] = [0xfe, 0xff, 0x30, 0x01];
+
+    fn parse(code: u8) -> Command {
+        let (_rest, command) = parse_command(code, &ADDRESS).expect("Parse");
+
+        command.expect("Known command")
+    }
+
+    #[test]
+    fn every_code() {
+        let expected = [
+            (NOP, "NOP", None, None),
+            (
+                APRD,
+                "APRD",
+                Some(Addressing::AutoIncrement),
+                Some(Access::Read),
+            ),
+            (
+                APWR,
+                "APWR",
+                Some(Addressing::AutoIncrement),
+                Some(Access::Write),
+            ),
+            (
+                FPRD,
+                "FPRD",
+                Some(Addressing::Configured),
+                Some(Access::Read),
+            ),
+            (
+                FPWR,
+                "FPWR",
+                Some(Addressing::Configured),
+                Some(Access::Write),
+            ),
+            (
+                APRW,
+                "APRW",
+                Some(Addressing::AutoIncrement),
+                Some(Access::ReadWrite),
+            ),
+            (
+                FPRW,
+                "FPRW",
+                Some(Addressing::Configured),
+                Some(Access::ReadWrite),
+            ),
+            (BRD, "BRD", Some(Addressing::Broadcast), Some(Access::Read)),
+            (BWR, "BWR", Some(Addressing::Broadcast), Some(Access::Write)),
+            (
+                BRW,
+                "BRW",
+                Some(Addressing::Broadcast),
+                Some(Access::ReadWrite),
+            ),
+            (LRD, "LRD", Some(Addressing::Logical), Some(Access::Read)),
+            (LWR, "LWR", Some(Addressing::Logical), Some(Access::Write)),
+            (
+                LRW,
+                "LRW",
+                Some(Addressing::Logical),
+                Some(Access::ReadWrite),
+            ),
+            (
+                FRMW,
+                "FRMW",
+                Some(Addressing::Configured),
+                Some(Access::ReadMultipleWrite),
+            ),
+            (
+                ARMW,
+                "ARMW",
+                Some(Addressing::AutoIncrement),
+                Some(Access::ReadMultipleWrite),
+            ),
+        ];
+
+        for (raw, mnemonic, expected_addressing, expected_access) in expected {
+            let command = parse(raw);
+
+            assert_eq!(code(&command), raw, "{}", mnemonic);
+            assert_eq!(name(&command), mnemonic);
+            assert_eq!(addressing(&command), expected_addressing, "{}", mnemonic);
+            assert_eq!(access(&command), expected_access, "{}", mnemonic);
+        }
+    }
+
+    #[test]
+    fn unknown_codes() {
+        for raw in [0x0f, 0xff] {
+            let (rest, command) = parse_command(raw, &ADDRESS).expect("Skip");
+
+            assert_eq!(command, None, "{:#04x}", raw);
+            assert!(rest.is_empty(), "{:#04x}", raw);
+        }
+    }
+
+    #[test]
+    fn register_addresses() {
+        for raw in [
+            APRD, APWR, APRW, FPRD, FPWR, FPRW, BRD, BWR, BRW, FRMW, ARMW,
+        ] {
+            let command = parse(raw);
+
+            assert_eq!(register(&command), Some(0x0130), "{}", name(&command));
+            assert_eq!(subdevice_address(&command), Some(0xfffe));
+            assert_eq!(logical_address(&command), None);
+        }
+
+        for raw in [NOP, LRD, LWR, LRW] {
+            let command = parse(raw);
+
+            assert_eq!(register(&command), None, "{}", name(&command));
+            assert_eq!(subdevice_address(&command), None);
+            assert_eq!(configured_address(&command), None);
+            assert_eq!(position(&command), None);
+        }
+    }
+
+    #[test]
+    fn station_addresses() {
+        for raw in [APRD, APWR, APRW, ARMW] {
+            let command = parse(raw);
+
+            assert_eq!(position(&command), Some(2), "{}", name(&command));
+            assert_eq!(configured_address(&command), None);
+        }
+
+        for raw in [FPRD, FPWR, FPRW, FRMW] {
+            let command = parse(raw);
+
+            assert_eq!(
+                configured_address(&command),
+                Some(0xfffe),
+                "{}",
+                name(&command)
+            );
+            assert_eq!(position(&command), None);
+        }
+
+        for raw in [BRD, BWR, BRW] {
+            let command = parse(raw);
+
+            assert_eq!(configured_address(&command), None, "{}", name(&command));
+            assert_eq!(position(&command), None);
+        }
+    }
+
+    #[test]
+    fn logical_addresses() {
+        for raw in [LRD, LWR, LRW] {
+            let command = parse(raw);
+
+            assert_eq!(
+                logical_address(&command),
+                Some(0x0130_fffe),
+                "{}",
+                name(&command)
+            );
+        }
+
+        assert_eq!(logical_address(&parse(FPRD)), None);
+    }
+}
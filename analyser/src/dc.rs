@@ -0,0 +1,341 @@
+//! Distributed Clocks (DC) traffic analysis.
+//!
+//! The reference clock's system time is taken from FRMW and ARMW responses to register `0x0910`.
+//! ARMW addresses the reference clock by position, which is resolved to its configured station
+//! address using the topology seen so far. Static sync configuration of each SubDevice is taken
+//! from FPWR writes and FPRD reads of the DC registers.
+
+use crate::{
+    command,
+    exchange::{register_range, PduExchange},
+    pdu::{Command, Reads, Writes},
+    topology::Topology,
+};
+use ethercrab::RegisterAddress;
+use std::{collections::BTreeMap, time::Duration};
+
+/// SYNC1 cycle time register.
+const DC_SYNC1_CYCLE_TIME: u16 = 0x09a4;
+
+/// The reference clock's system time at a single point in the capture.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ReferenceTime {
+    /// Time the FRMW or ARMW was sent by the host, relative to the start of the capture.
+    pub host_time: Duration,
+
+    /// Wireshark packet number of the frame containing the FRMW or ARMW.
+    pub packet_number: usize,
+
+    /// DC system time in nanoseconds, extended to 64 bits for SubDevices with 32 bit clocks.
+    pub dc_time: u64,
+}
+
+/// Static DC configuration of a single SubDevice. Fields are `None` if they weren't seen in the
+/// capture.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct DcConfig {
+    /// System time offset (`0x0920`) in nanoseconds. The register is unsigned, but offsets are
+    /// commonly negative so it's shown as two's complement.
+    pub system_time_offset: Option<i64>,
+
+    /// System time transmission delay (`0x0928`) in nanoseconds.
+    pub system_time_delay: Option<u32>,
+
+    /// Sync activation (`0x0981`).
+    pub sync_activation: Option<u8>,
+
+    /// Start time of cyclic operation (`0x0990`) in DC system time.
+    pub sync0_start_time: Option<u64>,
+
+    /// SYNC0 cycle time (`0x09A0`) in nanoseconds.
+    pub sync0_cycle_time: Option<u32>,
+
+    /// SYNC1 cycle time (`0x09A4`) in nanoseconds.
+    pub sync1_cycle_time: Option<u32>,
+}
+
+impl DcConfig {
+    /// Update fields from data written to or read from `register`.
+    fn update(&mut self, register: u16, data: &[u8]) {
+        let field = |start: RegisterAddress, len| register_range(register, data, start.into(), len);
+
+        if let Some(raw) = field(RegisterAddress::DcSystemTimeOffset, 8) {
+            self.system_time_offset = Some(i64::from_le_bytes(raw.try_into().unwrap()));
+        }
+
+        if let Some(raw) = field(RegisterAddress::DcSystemTimeTransmissionDelay, 4) {
+            self.system_time_delay = Some(u32::from_le_bytes(raw.try_into().unwrap()));
+        }
+
+        if let Some(raw) = field(RegisterAddress::DcSyncActive, 1) {
+            self.sync_activation = Some(raw[0]);
+        }
+
+        if let Some(raw) = field(RegisterAddress::DcSyncStartTime, 8) {
+            self.sync0_start_time = Some(u64::from_le_bytes(raw.try_into().unwrap()));
+        }
+
+        if let Some(raw) = field(RegisterAddress::DcSync0CycleTime, 4) {
+            self.sync0_cycle_time = Some(u32::from_le_bytes(raw.try_into().unwrap()));
+        }
+
+        if let Some(raw) = register_range(register, data, DC_SYNC1_CYCLE_TIME, 4) {
+            self.sync1_cycle_time = Some(u32::from_le_bytes(raw.try_into().unwrap()));
+        }
+    }
+
+    /// SYNC0 pulse generation is enabled.
+    pub fn sync0_active(&self) -> bool {
+        // Cyclic operation and SYNC0 activation bits
+        self.sync_activation
+            .is_some_and(|activation| activation & 0b11 == 0b11)
+    }
+}
+
+/// DC reference time and configuration recovered from a capture.
+#[derive(Debug, Clone, Default)]
+pub struct DcAnalysis {
+    /// Configured station address of the reference clock, from the first FRMW or ARMW seen.
+    pub reference: Option<u16>,
+
+    /// Every reference time read, in capture order.
+    pub times: Vec<ReferenceTime>,
+
+    /// Static configuration by configured station address.
+    pub configs: BTreeMap<u16, DcConfig>,
+
+    /// Host TX time and Wireshark packet number of every frame containing process data.
+    pub cycles: Vec<(Duration, usize)>,
+
+    /// Used to resolve ARMW positions to configured station addresses.
+    topology: Topology,
+}
+
+/// Position of a single process data frame relative to the SYNC0 grid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CycleAlignment {
+    /// Time the frame was sent by the host, relative to the start of the capture.
+    pub host_time: Duration,
+
+    /// Wireshark packet number of the frame.
+    pub packet_number: usize,
+
+    /// Estimated DC system time when the frame was sent.
+    pub dc_time: u64,
+
+    /// Offset from the nearest SYNC0 edge in nanoseconds. Negative values mean the frame was sent
+    /// before the edge.
+    pub phase: i64,
+}
+
+impl DcAnalysis {
+    /// Analyse every exchange in a capture.
+    pub fn new(exchanges: impl Iterator<Item = PduExchange>) -> Self {
+        let mut self_ = Self::default();
+
+        for exchange in exchanges {
+            self_.push(&exchange);
+        }
+
+        self_
+    }
+
+    /// Update the analysis with a single exchange.
+    pub fn push(&mut self, exchange: &PduExchange) {
+        self.topology.push(exchange);
+
+        if let Command::Read(Reads::Lrd { .. })
+        | Command::Write(Writes::Lwr { .. } | Writes::Lrw { .. }) = exchange.request.command
+        {
+            // Frames can hold more than one logical PDU.
+            if self.cycles.last().map(|(_, packet)| *packet) != Some(exchange.tx_packet_number) {
+                self.cycles
+                    .push((exchange.tx_time, exchange.tx_packet_number));
+            }
+        }
+
+        if exchange.response.working_counter == 0 {
+            return;
+        }
+
+        match exchange.request.command {
+            Command::Read(Reads::Frmw { address, register })
+                if register == u16::from(RegisterAddress::DcSystemTime) =>
+            {
+                self.push_time(address, exchange);
+            }
+            Command::Read(Reads::Armw { register, .. })
+                if register == u16::from(RegisterAddress::DcSystemTime) =>
+            {
+                let position = command::position(&exchange.request.command).expect("ARMW");
+
+                let Some(address) = self
+                    .topology
+                    .subdevices
+                    .get(&position)
+                    .and_then(|subdevice| subdevice.configured_address)
+                else {
+                    log::warn!(
+                        "Packet {}: ARMW to position {} with unknown configured address",
+                        exchange.tx_packet_number,
+                        position
+                    );
+
+                    return;
+                };
+
+                self.push_time(address, exchange);
+            }
+            Command::Write(Writes::Fpwr { address, register }) => {
+                self.configs
+                    .entry(address)
+                    .or_default()
+                    .update(register, &exchange.request.data);
+            }
+            Command::Read(Reads::Fprd { address, register }) => {
+                self.configs
+                    .entry(address)
+                    .or_default()
+                    .update(register, &exchange.response.data);
+            }
+            _ => (),
+        }
+    }
+
+    fn push_time(&mut self, address: u16, exchange: &PduExchange) {
+        let reference = *self.reference.get_or_insert(address);
+
+        if reference != address {
+            log::warn!(
+                "Packet {}: reference time from {:#06x} but reference clock is {:#06x}",
+                exchange.tx_packet_number,
+                address,
+                reference
+            );
+
+            return;
+        }
+
+        let data = &exchange.response.data;
+
+        let dc_time = if let Some(raw) = data.get(0..8) {
+            u64::from_le_bytes(raw.try_into().unwrap())
+        } else if let Some(raw) = data.get(0..4) {
+            let low = u64::from(u32::from_le_bytes(raw.try_into().unwrap()));
+
+            // Extend 32 bit clocks using the previous reading, accounting for wraparound.
+            match self.times.last() {
+                Some(prev) => {
+                    let extended = (prev.dc_time & !0xffff_ffff) | low;
+
+                    if extended < prev.dc_time {
+                        extended + (1 << 32)
+                    } else {
+                        extended
+                    }
+                }
+                None => low,
+            }
+        } else {
+            return;
+        };
+
+        self.times.push(ReferenceTime {
+            host_time: exchange.tx_time,
+            packet_number: exchange.tx_packet_number,
+            dc_time,
+        });
+    }
+
+    /// Offset of the reference clock from the host clock in nanoseconds at each reference time
+    /// read, relative to the offset at the first read.
+    ///
+    /// Returns `[host time in seconds, offset in ns]` pairs. A constant slope shows drift between
+    /// the two clocks.
+    pub fn offsets(&self) -> Vec<[f64; 2]> {
+        let Some(first) = self.times.first() else {
+            return Vec::new();
+        };
+
+        let offset =
+            |time: &ReferenceTime| i128::from(time.dc_time) - time.host_time.as_nanos() as i128;
+
+        let first = offset(first);
+
+        self.times
+            .iter()
+            .map(|time| [time.host_time.as_secs_f64(), (offset(time) - first) as f64])
+            .collect()
+    }
+
+    /// Drift of the reference clock relative to the host clock in parts per million, from a least
+    /// squares fit of [`offsets`](Self::offsets). Positive values mean the reference clock runs
+    /// faster than the host.
+    pub fn drift_ppm(&self) -> Option<f64> {
+        let offsets = self.offsets();
+
+        if offsets.len() < 2 {
+            return None;
+        }
+
+        let n = offsets.len() as f64;
+        let mean_x = offsets.iter().map(|[x, _]| x).sum::<f64>() / n;
+        let mean_y = offsets.iter().map(|[_, y]| y).sum::<f64>() / n;
+
+        let (cov, var) = offsets.iter().fold((0.0, 0.0), |(cov, var), [x, y]| {
+            (
+                cov + (x - mean_x) * (y - mean_y),
+                var + (x - mean_x).powi(2),
+            )
+        });
+
+        if var == 0.0 {
+            return None;
+        }
+
+        // Slope is ns per second, so divide by 1000 to get ppm.
+        Some(cov / var / 1000.0)
+    }
+
+    /// Position of every process data frame relative to the SYNC0 grid given by `start_time` and
+    /// `cycle_time`, both in nanoseconds.
+    ///
+    /// The DC time of each frame is estimated from the most recent reference time read, so frames
+    /// sent before the first read are skipped. When the FRMW is sent in the same frame as the
+    /// process data, as ethercrab does, the estimate is exact.
+    pub fn alignment(&self, start_time: u64, cycle_time: u32) -> Vec<CycleAlignment> {
+        if cycle_time == 0 {
+            return Vec::new();
+        }
+
+        let cycle_time = i128::from(cycle_time);
+
+        self.cycles
+            .iter()
+            .filter_map(|(host_time, packet_number)| {
+                let idx = self
+                    .times
+                    .partition_point(|time| time.host_time <= *host_time);
+
+                let reference = self.times.get(idx.checked_sub(1)?)?;
+
+                let dc_time =
+                    reference.dc_time + (*host_time - reference.host_time).as_nanos() as u64;
+
+                let mut phase =
+                    (i128::from(dc_time) - i128::from(start_time)).rem_euclid(cycle_time);
+
+                if phase >= cycle_time / 2 {
+                    phase -= cycle_time;
+                }
+
+                Some(CycleAlignment {
+                    host_time: *host_time,
+                    packet_number: *packet_number,
+                    dc_time,
+                    phase: phase as i64,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,291 @@
+//! Track ESC error counters (`0x0300` to `0x0313`) read during a capture.
+//!
+//! Counters are read per configured station address with FPRD. The first read of each counter is
+//! taken as a baseline, and every later increase is counted as an increment. A counter that goes
+//! down was cleared, either by a captured write or by something outside the capture, so its new
+//! value is counted in full.
+
+use crate::{
+    exchange::{register_range, PduExchange},
+    pdu::{Command, Reads, Writes},
+    topology::MAX_PORTS,
+};
+use std::{collections::BTreeMap, ops::RangeInclusive, time::Duration};
+
+/// Invalid frame counter of port 0. Each port has an invalid frame counter followed by an RX error
+/// counter.
+const INVALID_FRAME_PORT0: u16 = 0x0300;
+
+/// Forwarded RX error counter of port 0, one byte per port.
+const FORWARDED_RX_ERROR_PORT0: u16 = 0x0308;
+
+/// ECAT processing unit error counter.
+const PROCESSING_UNIT_ERROR: u16 = 0x030c;
+
+/// PDI error counter.
+const PDI_ERROR: u16 = 0x030d;
+
+/// Lost link counter of port 0, one byte per port.
+const LOST_LINK_PORT0: u16 = 0x0310;
+
+/// A single ESC error counter, ETG1000.4 Table 41 to 46.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CounterKind {
+    /// Frames with an invalid CRC or length received on a port.
+    InvalidFrame,
+
+    /// Physical layer errors received on a port.
+    RxError,
+
+    /// Frames received on a port that were already marked as invalid by a previous SubDevice.
+    ForwardedRxError,
+
+    /// Times a port's link went down.
+    LostLink,
+
+    /// Frames with an error detected by the EtherCAT processing unit.
+    ProcessingUnit,
+
+    /// Errors on the process data interface.
+    Pdi,
+}
+
+impl CounterKind {
+    /// Whether this counter points at a problem with the cable or PHY on the port it was counted
+    /// on, rather than elsewhere in the network or inside the SubDevice.
+    pub fn is_link_error(&self) -> bool {
+        matches!(self, Self::InvalidFrame | Self::RxError | Self::LostLink)
+    }
+}
+
+impl std::fmt::Display for CounterKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::InvalidFrame => "invalid frame",
+            Self::RxError => "RX error",
+            Self::ForwardedRxError => "forwarded RX error",
+            Self::LostLink => "lost link",
+            Self::ProcessingUnit => "processing unit error",
+            Self::Pdi => "PDI error",
+        };
+
+        f.pad(s)
+    }
+}
+
+/// Identifies a single counter of a single SubDevice.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CounterKey {
+    /// Configured station address.
+    pub address: u16,
+
+    /// Port the counter belongs to, or `None` for counters of the whole SubDevice.
+    pub port: Option<u8>,
+
+    pub kind: CounterKind,
+}
+
+impl CounterKey {
+    /// Register address of this counter.
+    fn register(&self) -> u16 {
+        let port = u16::from(self.port.unwrap_or(0));
+
+        match self.kind {
+            CounterKind::InvalidFrame => INVALID_FRAME_PORT0 + port * 2,
+            CounterKind::RxError => INVALID_FRAME_PORT0 + port * 2 + 1,
+            CounterKind::ForwardedRxError => FORWARDED_RX_ERROR_PORT0 + port,
+            CounterKind::LostLink => LOST_LINK_PORT0 + port,
+            CounterKind::ProcessingUnit => PROCESSING_UNIT_ERROR,
+            CounterKind::Pdi => PDI_ERROR,
+        }
+    }
+
+    /// Registers that clear this counter when written, ETG1000.4 Table 41 to 46.
+    fn clear_group(&self) -> RangeInclusive<u16> {
+        match self.kind {
+            CounterKind::InvalidFrame | CounterKind::RxError | CounterKind::ForwardedRxError => {
+                INVALID_FRAME_PORT0..=FORWARDED_RX_ERROR_PORT0 + MAX_PORTS as u16 - 1
+            }
+            CounterKind::LostLink => LOST_LINK_PORT0..=LOST_LINK_PORT0 + MAX_PORTS as u16 - 1,
+            CounterKind::ProcessingUnit | CounterKind::Pdi => self.register()..=self.register(),
+        }
+    }
+
+    /// Every counter of a single SubDevice.
+    fn all(address: u16) -> impl Iterator<Item = Self> {
+        let per_port = (0..MAX_PORTS as u8).flat_map(move |port| {
+            [
+                CounterKind::InvalidFrame,
+                CounterKind::RxError,
+                CounterKind::ForwardedRxError,
+                CounterKind::LostLink,
+            ]
+            .map(|kind| Self {
+                address,
+                port: Some(port),
+                kind,
+            })
+        });
+
+        let device = [CounterKind::ProcessingUnit, CounterKind::Pdi].map(|kind| Self {
+            address,
+            port: None,
+            kind,
+        });
+
+        per_port.chain(device)
+    }
+}
+
+impl std::fmt::Display for CounterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.port {
+            Some(port) => write!(f, "{:#06x} port {} {}", self.address, port, self.kind),
+            None => write!(f, "{:#06x} {}", self.address, self.kind),
+        }
+    }
+}
+
+/// Describe the link segment attached to a SubDevice's port.
+///
+/// Port 0 faces the MainDevice, so errors counted there point at the cable from the previous
+/// SubDevice.
+pub fn link_segment(address: u16, port: u8) -> String {
+    match port {
+        0 => format!("{:#06x} port 0 (upstream link)", address),
+        port => format!("{:#06x} port {} (downstream link)", address, port),
+    }
+}
+
+/// A single read of a counter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CounterSample {
+    /// Time of the read, relative to the start of the capture.
+    pub time: Duration,
+
+    /// Wireshark packet number of the frame containing the read.
+    pub packet_number: usize,
+
+    /// Raw counter value. Counters saturate at 255.
+    pub value: u8,
+
+    /// Total increments since the first read of this counter.
+    pub increments: u64,
+}
+
+/// Every error counter read in a capture.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorCounters {
+    /// Reads of each counter, in capture order.
+    pub series: BTreeMap<CounterKey, Vec<CounterSample>>,
+}
+
+impl ErrorCounters {
+    /// Collect error counters from every exchange in a capture.
+    pub fn new(exchanges: impl Iterator<Item = PduExchange>) -> Self {
+        let mut self_ = Self::default();
+
+        for exchange in exchanges {
+            self_.push(&exchange);
+        }
+
+        self_
+    }
+
+    /// Update counters with a single exchange.
+    pub fn push(&mut self, exchange: &PduExchange) {
+        if exchange.response.working_counter == 0 {
+            return;
+        }
+
+        match exchange.request.command {
+            Command::Read(Reads::Fprd { address, register }) => {
+                for key in CounterKey::all(address) {
+                    let Some(raw) =
+                        register_range(register, &exchange.response.data, key.register(), 1)
+                    else {
+                        continue;
+                    };
+
+                    let series = self.series.entry(key).or_default();
+
+                    let increments = match series.last() {
+                        Some(last) if raw[0] >= last.value => {
+                            last.increments + u64::from(raw[0] - last.value)
+                        }
+                        // Cleared since the last read
+                        Some(last) => last.increments + u64::from(raw[0]),
+                        None => 0,
+                    };
+
+                    series.push(CounterSample {
+                        time: exchange.tx_time,
+                        packet_number: exchange.tx_packet_number,
+                        value: raw[0],
+                        increments,
+                    });
+                }
+            }
+            Command::Write(Writes::Fpwr { address, register }) => {
+                // Widened so writes running up to the end of the register space don't overflow
+                let end = u32::from(register) + exchange.request.data.len() as u32;
+                let written = |group: RangeInclusive<u16>| {
+                    register <= *group.end() && end > u32::from(*group.start())
+                };
+
+                // Writing to any counter clears every counter in its group. Record this as a zero
+                // read so later reads are counted in full.
+                for key in CounterKey::all(address) {
+                    if !written(key.clear_group()) {
+                        continue;
+                    }
+
+                    if let Some(series) = self.series.get_mut(&key) {
+                        let increments = series.last().map_or(0, |last| last.increments);
+
+                        series.push(CounterSample {
+                            time: exchange.tx_time,
+                            packet_number: exchange.tx_packet_number,
+                            value: 0,
+                            increments,
+                        });
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Total increments of every counter that increased during the capture, largest first.
+    pub fn totals(&self) -> Vec<(CounterKey, u64)> {
+        let mut totals = self
+            .series
+            .iter()
+            .filter_map(|(key, series)| {
+                let total = series.last()?.increments;
+
+                (total > 0).then_some((*key, total))
+            })
+            .collect::<Vec<_>>();
+
+        totals.sort_by(|(a_key, a), (b_key, b)| b.cmp(a).then(a_key.cmp(b_key)));
+
+        totals
+    }
+
+    /// The port with the most link error increments, if any increased during the capture.
+    pub fn most_degraded(&self) -> Option<(u16, u8, u64)> {
+        let mut by_port = BTreeMap::<(u16, u8), u64>::new();
+
+        for (key, total) in self.totals() {
+            if let (true, Some(port)) = (key.kind.is_link_error(), key.port) {
+                *by_port.entry((key.address, port)).or_default() += total;
+            }
+        }
+
+        by_port
+            .into_iter()
+            .max_by_key(|(_, total)| *total)
+            .map(|((address, port), total)| (address, port, total))
+    }
+}
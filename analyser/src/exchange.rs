@@ -0,0 +1,181 @@
+//! Pair PDUs sent by the MainDevice with their responses from the network.
+
+use crate::pdu::{Frame, Pdu};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+/// A single PDU sent by the MainDevice, along with the response returned by the network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PduExchange {
+    /// PDU as sent by the MainDevice.
+    ///
+    /// For writes, this holds the data written to the SubDevice(s).
+    pub request: Pdu,
+
+    /// PDU as returned from the network.
+    ///
+    /// For reads, this holds the data read from the SubDevice(s).
+    pub response: Pdu,
+
+    /// Time the request was sent, relative to the first EtherCAT frame in the capture.
+    pub tx_time: Duration,
+
+    /// Time the response was received, relative to the first EtherCAT frame in the capture.
+    pub rx_time: Duration,
+
+    /// Wireshark packet number of the frame containing the request.
+    pub tx_packet_number: usize,
+
+    /// Wireshark packet number of the frame containing the response.
+    pub rx_packet_number: usize,
+
+    /// Number of frames with at least one PDU sent by the MainDevice before the frame containing
+    /// the request.
+    pub cycle: usize,
+}
+
+/// Pairs PDUs sent by the MainDevice with their responses, one frame at a time.
+///
+/// Every PDU in a frame is considered, not just the first.
+#[derive(Debug, Default)]
+pub struct ExchangeMatcher {
+    /// Timestamp of the first frame, used to make times relative to the start of the capture.
+    start: Option<Duration>,
+
+    /// Sent PDUs waiting for a response, keyed by PDU index.
+    pending: HashMap<u8, (Pdu, Duration, usize, usize)>,
+
+    /// Number of frames with at least one PDU sent by the MainDevice so far.
+    sent: usize,
+
+    /// Number of sent PDUs that were superseded by another PDU with the same index before a
+    /// response was seen, plus those still waiting when [`finish`](Self::finish) was called.
+    pub lost: usize,
+}
+
+impl ExchangeMatcher {
+    /// Time of a frame relative to the first frame pushed, or to this frame if it's the first.
+    pub fn time(&mut self, frame: &Frame) -> Duration {
+        let start = *self.start.get_or_insert(frame.time);
+
+        frame.time.saturating_sub(start)
+    }
+
+    /// Number of frames with at least one PDU sent by the MainDevice before the next one pushed.
+    pub fn cycle(&self) -> usize {
+        self.sent
+    }
+
+    /// Process a single frame, calling `completed` for every exchange it completes in PDU order.
+    pub fn push(&mut self, frame: Frame, mut completed: impl FnMut(PduExchange)) {
+        let time = self.time(&frame);
+
+        let cycle = self.sent;
+
+        if frame.from_master && !frame.pdus.is_empty() {
+            self.sent += 1;
+        }
+
+        for pdu in frame.pdus {
+            if frame.from_master {
+                let prev = self
+                    .pending
+                    .insert(pdu.index, (pdu, time, frame.wireshark_packet_number, cycle));
+
+                if prev.is_some() {
+                    self.lost += 1;
+                }
+            }
+            // Responses without a request are ignored. This happens for captures started
+            // _during_ an EtherCAT session.
+            else if let Some((request, tx_time, tx_packet_number, cycle)) =
+                self.pending.remove(&pdu.index)
+            {
+                completed(PduExchange {
+                    request,
+                    response: pdu,
+                    tx_time,
+                    rx_time: time,
+                    tx_packet_number,
+                    rx_packet_number: frame.wireshark_packet_number,
+                    cycle,
+                });
+            }
+        }
+    }
+
+    /// Count every request still waiting for a response as lost, at the end of a capture.
+    pub fn finish(&mut self) {
+        self.lost += self.pending.len();
+
+        self.pending.clear();
+    }
+}
+
+/// Iterator adapter that turns a stream of frames into a stream of [`PduExchange`]s.
+///
+/// Requests that never receive a response are dropped and counted in [`Exchanges::lost`], either
+/// when their index is reused or when the frames run out.
+pub struct Exchanges<I> {
+    frames: I,
+
+    matcher: ExchangeMatcher,
+
+    /// Exchanges completed by the last processed frame that haven't been yielded yet.
+    ready: VecDeque<PduExchange>,
+}
+
+impl<I> Exchanges<I>
+where
+    I: Iterator<Item = Frame>,
+{
+    pub fn new(frames: I) -> Self {
+        Self {
+            frames,
+            matcher: ExchangeMatcher::default(),
+            ready: VecDeque::new(),
+        }
+    }
+
+    /// Number of sent PDUs that never received a response. Requests still waiting are only
+    /// counted once every frame has been processed.
+    pub fn lost(&self) -> usize {
+        self.matcher.lost
+    }
+}
+
+impl<I> Iterator for Exchanges<I>
+where
+    I: Iterator<Item = Frame>,
+{
+    type Item = PduExchange;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(exchange) = self.ready.pop_front() {
+                return Some(exchange);
+            }
+
+            let Some(frame) = self.frames.next() else {
+                self.matcher.finish();
+
+                return None;
+            };
+
+            self.matcher
+                .push(frame, |exchange| self.ready.push_back(exchange));
+        }
+    }
+}
+
+/// Get the bytes of `len` bytes long register `register` from a PDU accessing `data.len()` bytes
+/// starting at `start`.
+///
+/// Returns `None` if the PDU doesn't cover the whole register.
+pub(crate) fn register_range(start: u16, data: &[u8], register: u16, len: usize) -> Option<&[u8]> {
+    let offset = usize::from(register.checked_sub(start)?);
+
+    data.get(offset..offset + len)
+}
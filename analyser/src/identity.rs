@@ -0,0 +1,143 @@
+//! Extract SubDevice identity (vendor, product, revision, serial) from a capture.
+//!
+//! Identity values come from SII reads of words `0x0008` to `0x000f` and from CoE uploads of
+//! object `0x1018`, whichever are present. CoE values take precedence as they reflect the running
+//! firmware rather than the EEPROM contents.
+
+use crate::{
+    coe::{SdoDirection, SdoReader, SdoResult, SdoTransaction},
+    exchange::PduExchange,
+    sii::{SiiAccess, SiiDevice, SiiReader},
+};
+use std::collections::BTreeMap;
+
+/// SII word address of the vendor ID. Product code, revision and serial number follow as
+/// consecutive `u32`s.
+const SII_VENDOR_ID: u16 = 0x0008;
+
+/// CoE identity object.
+const COE_IDENTITY: u16 = 0x1018;
+
+/// Identity of a single SubDevice. Fields are `None` if they weren't found in the capture.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Identity {
+    pub vendor_id: Option<u32>,
+    pub product_code: Option<u32>,
+    pub revision: Option<u32>,
+    pub serial: Option<u32>,
+}
+
+impl Identity {
+    /// Set a field by its sub-index in object `0x1018`.
+    fn set(&mut self, sub_index: u8, value: u32) {
+        match sub_index {
+            1 => self.vendor_id = Some(value),
+            2 => self.product_code = Some(value),
+            3 => self.revision = Some(value),
+            4 => self.serial = Some(value),
+            _ => (),
+        }
+    }
+
+    /// Format a field as hex, or `-` if it wasn't captured.
+    pub fn display_field(field: Option<u32>) -> String {
+        field
+            .map(|value| format!("{:#010x}", value))
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+impl std::fmt::Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "vendor {}, product {}, revision {}, serial {}",
+            Self::display_field(self.vendor_id),
+            Self::display_field(self.product_code),
+            Self::display_field(self.revision),
+            Self::display_field(self.serial)
+        )
+    }
+}
+
+/// Identities of every SubDevice in a capture.
+#[derive(Debug, Clone, Default)]
+pub struct Identities {
+    /// Identity by configured station address.
+    pub by_address: BTreeMap<u16, Identity>,
+
+    sii: SiiReader,
+
+    sdo: SdoReader,
+}
+
+impl Identities {
+    /// Extract identities from every exchange in a capture.
+    pub fn new(exchanges: impl Iterator<Item = PduExchange>) -> Self {
+        let mut self_ = Self::default();
+
+        for exchange in exchanges {
+            self_.push(&exchange);
+        }
+
+        self_
+    }
+
+    /// Update identities with a single exchange.
+    pub fn push(&mut self, exchange: &PduExchange) {
+        for access in self.sii.push(exchange) {
+            self.push_sii(&access);
+        }
+
+        for transaction in self.sdo.push(exchange) {
+            self.push_coe(&transaction);
+        }
+    }
+
+    fn push_sii(&mut self, access: &SiiAccess) {
+        let SiiDevice::Configured(address) = access.device else {
+            return;
+        };
+
+        for (sub_index, word) in (1..=4).zip((SII_VENDOR_ID..).step_by(2)) {
+            let Some(offset) = word.checked_sub(access.word_address) else {
+                continue;
+            };
+
+            let offset = usize::from(offset) * 2;
+
+            if let Some(raw) = access.data.get(offset..offset + 4) {
+                self.by_address
+                    .entry(address)
+                    .or_default()
+                    .set(sub_index, u32::from_le_bytes(raw.try_into().unwrap()));
+            }
+        }
+    }
+
+    fn push_coe(&mut self, upload: &SdoTransaction) {
+        if upload.direction != SdoDirection::Upload
+            || upload.result != SdoResult::Ok
+            || upload.index != COE_IDENTITY
+        {
+            return;
+        }
+
+        let identity = self.by_address.entry(upload.address).or_default();
+
+        if upload.complete_access {
+            // Sub-index 0 is sent as a `u16` when reading the complete object.
+            let data = if upload.sub_index == 0 {
+                upload.data.get(2..).unwrap_or_default()
+            } else {
+                upload.data.as_slice()
+            };
+
+            for (sub_index, raw) in (upload.sub_index.max(1)..).zip(data.chunks_exact(4)) {
+                identity.set(sub_index, u32::from_le_bytes(raw.try_into().unwrap()));
+            }
+        } else if let Ok(raw) = upload.data.as_slice().try_into() {
+            identity.set(upload.sub_index, u32::from_le_bytes(raw));
+        }
+    }
+}
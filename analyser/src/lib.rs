@@ -1,33 +1,57 @@
+pub mod coe;
+pub mod columnar;
+pub mod command;
+pub mod dc;
+pub mod error_counters;
+pub mod exchange;
+pub mod identity;
+pub mod mailbox;
+pub mod pdo;
 pub mod pdu;
-
-use clap::Parser;
+pub mod process_image;
+pub mod register_state;
+pub mod registers;
+pub mod significance;
+pub mod sii;
+pub mod sii_decode;
+pub mod sqlite;
+pub mod stats;
+pub mod topology;
+pub mod working_counter;
+
+use exchange::{ExchangeMatcher, Exchanges};
 use pcap_file::pcapng::blocks::interface_description::InterfaceDescriptionOption;
 use pcap_file::pcapng::blocks::section_header::SectionHeaderOption;
 use pcap_file::pcapng::{Block, PcapNgReader};
-use pdu::{parse_pdu, Frame};
+use pdu::{parse_pdu, Command, Frame, Pdu};
 use serde_with::serde_as;
+use serde_with::DisplayFromStr;
 use serde_with::DurationNanoSeconds;
-use smoltcp::wire::{EthernetFrame, EthernetProtocol};
+use smoltcp::wire::{EthernetAddress, EthernetFrame, EthernetProtocol};
 use std::path::Path;
-use std::path::PathBuf;
 use std::{fs::File, time::Duration};
+use working_counter::WorkingCounters;
 
 const ETHERCAT_ETHERTYPE_RAW: u16 = 0x88a4;
 const ETHERCAT_ETHERTYPE: EthernetProtocol = EthernetProtocol::Unknown(ETHERCAT_ETHERTYPE_RAW);
 
-/// Wireshark EtherCAT dump analyser
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
-pub struct Args {
-    /// Path to pcapng file.
-    pub file: PathBuf,
-
-    /// Number of PDUs per process data cycle, both requests and responses from the network.
-    #[arg(long)]
-    pub cycle_packets: usize,
+/// How frames sent by the MainDevice are told apart from frames returned by the network.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DirectionDetection {
+    /// SubDevices set the locally administered bit in the source MAC address of every frame they
+    /// return, so any frame without it came from the MainDevice.
+    #[default]
+    LocallyAdministered,
+
+    /// Frames with this source MAC address came from the MainDevice. Useful for captures where the
+    /// MainDevice itself uses a locally administered address.
+    MainDeviceMac(EthernetAddress),
 }
 
 /// A single PDU cycle, also a single CSV row.
+///
+/// Only the first PDU of each frame is considered. Columns added after the original timing columns
+/// are appended at the end so existing CSV consumers keep working.
 #[serde_as]
 #[derive(Debug, serde::Serialize)]
 pub struct PduStat {
@@ -41,6 +65,12 @@ pub struct PduStat {
 
     pub command: String,
 
+    /// Name of the register accessed, empty for logical addressing.
+    pub register: String,
+
+    /// Decoded register data: written data for writes, read data for reads.
+    pub value: String,
+
     #[serde_as(as = "DurationNanoSeconds")]
     #[serde(rename = "tx_time_ns")]
     pub tx_time: Duration,
@@ -52,6 +82,85 @@ pub struct PduStat {
     #[serde_as(as = "DurationNanoSeconds")]
     #[serde(rename = "delta_time_ns")]
     pub delta_time: Duration,
+
+    /// Wireshark packet number of the response, if one was seen.
+    pub rx_packet_number: Option<usize>,
+
+    /// Number of frames with at least one PDU sent by the MainDevice before this one.
+    pub cycle: usize,
+
+    /// Addressing mode, `None` for NOP.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub addressing: Option<command::Addressing>,
+
+    /// Auto increment or configured station address of register commands.
+    pub station_address: Option<u16>,
+
+    /// Start of the logical address range of logical commands.
+    pub logical_address: Option<u32>,
+
+    /// Start address of the register accessed.
+    pub register_address: Option<u16>,
+
+    /// Length of the PDU data in bytes.
+    pub data_len: usize,
+
+    /// Working counter of the response, if one was seen.
+    pub working_counter: Option<u16>,
+
+    /// Working counter the response would have if every addressed SubDevice processed the PDU,
+    /// given the topology and FMMU configuration seen so far. See [`working_counter`].
+    pub expected_working_counter: Option<u16>,
+
+    /// Number of PDUs in the sent frame.
+    pub pdus_in_frame: usize,
+
+    /// Length of the sent Ethernet frame in bytes.
+    pub frame_size: usize,
+
+    /// Source MAC address of the sent frame.
+    #[serde_as(as = "DisplayFromStr")]
+    pub tx_source: EthernetAddress,
+
+    /// Source MAC address of the response frame, if one was seen. SubDevices set the locally
+    /// administered bit on frames they pass back to the MainDevice.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub rx_source: Option<EthernetAddress>,
+}
+
+impl PduStat {
+    /// Every column name, in the order they're serialized.
+    pub const COLUMNS: &'static [&'static str] = &[
+        "scenario",
+        "packet_number",
+        "index",
+        "command",
+        "register",
+        "value",
+        "tx_time_ns",
+        "rx_time_ns",
+        "delta_time_ns",
+        "rx_packet_number",
+        "cycle",
+        "addressing",
+        "station_address",
+        "logical_address",
+        "register_address",
+        "data_len",
+        "working_counter",
+        "expected_working_counter",
+        "pdus_in_frame",
+        "frame_size",
+        "tx_source",
+        "rx_source",
+    ];
+}
+
+/// Decode the data of a register PDU, or an empty string for logical addressing.
+fn describe_pdu(pdu: &Pdu) -> String {
+    command::register(&pdu.command)
+        .map(|register| registers::describe_range(register, &pdu.data).join("; "))
+        .unwrap_or_default()
 }
 
 pub struct PcapFile {
@@ -69,6 +178,8 @@ pub struct PcapFile {
     pub timestamp_resolution: u8,
 
     pub os: String,
+
+    pub direction: DirectionDetection,
 }
 
 impl std::fmt::Debug for PcapFile {
@@ -80,6 +191,7 @@ impl std::fmt::Debug for PcapFile {
             .field("if_name", &self.if_name)
             .field("timestamp_resolution", &self.timestamp_resolution)
             .field("os", &self.os)
+            .field("direction", &self.direction)
             .finish()
     }
 }
@@ -94,7 +206,7 @@ impl Iterator for PcapFile {
 
 impl PcapFile {
     pub fn new(path: &Path) -> Self {
-        let file = File::open(&path)
+        let file = File::open(path)
             .map_err(|e| {
                 log::error!("Failed to open PCAP file {}: {}", path.display(), e);
 
@@ -164,9 +276,17 @@ impl PcapFile {
             os,
             if_name,
             timestamp_resolution,
+            direction: DirectionDetection::default(),
         }
     }
 
+    /// Use a different method to tell frames sent by the MainDevice apart from responses.
+    pub fn with_direction(mut self, direction: DirectionDetection) -> Self {
+        self.direction = direction;
+
+        self
+    }
+
     pub fn next_line(&mut self) -> Option<Frame> {
         while let Some(block) = self.capture_file.next_block() {
             self.packet_number += 1;
@@ -176,9 +296,7 @@ impl PcapFile {
 
             let (raw, timestamp) = match block {
                 Block::EnhancedPacket(block) => {
-                    let buf = block.data.to_owned();
-
-                    let buf = buf.iter().copied().collect::<Vec<_>>();
+                    let buf = block.data.to_vec();
 
                     (
                         EthernetFrame::new_checked(buf).expect("Failed to parse block"),
@@ -198,6 +316,10 @@ impl PcapFile {
 
             let mut frame = parse_pdu(raw).expect("Faild to parse frame");
 
+            if let DirectionDetection::MainDeviceMac(main_device) = self.direction {
+                frame.from_master = frame.source == main_device;
+            }
+
             frame.time = timestamp;
             frame.wireshark_packet_number = self.packet_number;
 
@@ -207,67 +329,79 @@ impl PcapFile {
         None
     }
 
+    /// Consume the capture, pairing every PDU sent by the MainDevice with its response.
+    pub fn exchanges(self) -> Exchanges<Self> {
+        Exchanges::new(self)
+    }
+
     pub fn match_tx_rx(&mut self) -> Vec<PduStat> {
-        let mut start_offset = None;
+        let mut pairs = Vec::<PduStat>::new();
 
-        let mut pairs = Vec::new();
+        let mut matcher = ExchangeMatcher::default();
 
-        while let Some(packet) = self.next_line() {
-            let start_offset = *start_offset.get_or_insert(packet.time);
+        // Every PDU, not just the first of each frame, is needed to follow the network state used
+        // to compute expected working counters.
+        let mut working_counters = WorkingCounters::default();
 
+        while let Some(packet) = self.next_line() {
             // TODO: Support multiple PDUs
-            let Some(first_pdu) = packet.pdus.first() else {
-                continue;
-            };
-
-            // Newly sent PDU
-            if packet.from_master {
+            if let Some(first_pdu) = packet.pdus.first().filter(|_| packet.from_master) {
                 pairs.push(PduStat {
                     scenario: self.scenario.clone(),
                     packet_number: packet.wireshark_packet_number,
                     index: first_pdu.index,
-                    tx_time: packet.time - start_offset,
+                    tx_time: matcher.time(&packet),
                     rx_time: Duration::default(),
                     delta_time: Duration::default(),
                     command: first_pdu.command.to_string(),
+                    register: command::register(&first_pdu.command)
+                        .map(registers::name)
+                        .unwrap_or_default(),
+                    value: describe_pdu(first_pdu),
+                    rx_packet_number: None,
+                    cycle: matcher.cycle(),
+                    addressing: command::addressing(&first_pdu.command),
+                    station_address: command::subdevice_address(&first_pdu.command),
+                    logical_address: command::logical_address(&first_pdu.command),
+                    register_address: command::register(&first_pdu.command),
+                    data_len: first_pdu.data.len(),
+                    working_counter: None,
+                    expected_working_counter: working_counters.expected(first_pdu),
+                    pdus_in_frame: packet.pdus.len(),
+                    frame_size: packet.size,
+                    tx_source: packet.source,
+                    rx_source: None,
                 });
             }
-            // Response to existing sent PDU
-            else {
-                // Find last sent PDU with this receive PDU's same index
-                let Some(sent) = pairs
-                    .iter_mut()
-                    .rev()
-                    .find(|stat| stat.index == first_pdu.index)
-                else {
-                    // First packet has nothing before it. This will ignore failures for captures
-                    // started _during_ an EtherCAT session.
-                    if packet.wireshark_packet_number == 1 {
-                        continue;
-                    }
-
-                    let search_idx = first_pdu.index;
 
-                    let n = 64;
+            let rx_source = packet.source;
 
-                    let prevs = pairs
-                        .iter_mut()
-                        .rev()
-                        .take(n)
-                        .map(|stat| stat.index.to_string())
-                        .collect::<Vec<_>>()
-                        .join(",");
+            matcher.push(packet, |exchange| {
+                working_counters.push(&exchange);
 
-                    panic!(
-                        "Packet number {}: Could not find sent packet, looking for index {}, prev {} indices: {:?}",
-                        packet.wireshark_packet_number, search_idx, n, prevs
-                    );
+                // Rows are sorted by packet number, and only hold the first PDU of each frame
+                let Ok(idx) = pairs
+                    .binary_search_by_key(&exchange.tx_packet_number, |stat| stat.packet_number)
+                else {
+                    return;
                 };
 
-                sent.rx_time = packet.time - start_offset;
+                let sent = &mut pairs[idx];
 
+                if sent.index != exchange.request.index {
+                    return;
+                }
+
+                sent.rx_time = exchange.rx_time;
                 sent.delta_time = sent.rx_time - sent.tx_time;
-            }
+                sent.rx_packet_number = Some(exchange.rx_packet_number);
+                sent.working_counter = Some(exchange.response.working_counter);
+                sent.rx_source = Some(rx_source);
+
+                if matches!(exchange.response.command, Command::Read(_)) {
+                    sent.value = describe_pdu(&exchange.response);
+                }
+            });
         }
 
         pairs
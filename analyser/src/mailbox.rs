@@ -0,0 +1,413 @@
+//! Recover mailbox messages from SyncManager 0/1 traffic.
+//!
+//! The mailbox locations are taken from the SyncManager configuration written by the MainDevice,
+//! so captures must include SubDevice initialisation for any messages to be found.
+
+use crate::{
+    exchange::{register_range, PduExchange},
+    pdu::{Command, Reads, Writes},
+};
+use ethercrab::RegisterAddress;
+use std::{collections::HashMap, time::Duration};
+
+/// Length of the mailbox header at the start of every mailbox message.
+pub const MAILBOX_HEADER_LEN: usize = 6;
+
+/// Mailbox protocol, defined in ETG1000.6 Table 29.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[repr(u8)]
+pub enum MailboxType {
+    /// Error.
+    Err = 0x00,
+    /// ADS over EtherCAT.
+    Aoe = 0x01,
+    /// Ethernet over EtherCAT.
+    Eoe = 0x02,
+    /// CAN application protocol over EtherCAT.
+    Coe = 0x03,
+    /// File access over EtherCAT.
+    Foe = 0x04,
+    /// Servo profile over EtherCAT.
+    Soe = 0x05,
+    /// Vendor specific.
+    VendorSpecific = 0x0f,
+    #[num_enum(catch_all)]
+    Unknown(u8),
+}
+
+/// Mailbox header, defined in ETG1000.6 Table 29.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MailboxHeader {
+    /// Length of the data following the header.
+    pub length: u16,
+
+    /// Station address of the source for requests, or destination for responses.
+    pub address: u16,
+
+    pub channel: u8,
+
+    pub priority: u8,
+
+    pub mailbox_type: MailboxType,
+
+    /// Mailbox counter from 1 to 7 inclusive. 0 is reserved.
+    pub counter: u8,
+}
+
+impl MailboxHeader {
+    /// Parse a mailbox header from the start of the given buffer.
+    pub fn parse(i: &[u8]) -> Option<Self> {
+        let i = i.get(0..MAILBOX_HEADER_LEN)?;
+
+        Some(Self {
+            length: u16::from_le_bytes([i[0], i[1]]),
+            address: u16::from_le_bytes([i[2], i[3]]),
+            channel: i[4] & 0b0011_1111,
+            priority: i[4] >> 6,
+            mailbox_type: MailboxType::from(i[5] & 0x0f),
+            counter: (i[5] >> 4) & 0b111,
+        })
+    }
+}
+
+/// Which way a mailbox message travelled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MailboxDirection {
+    /// Written by the MainDevice into the SubDevice's write mailbox (SM0).
+    ToSubDevice,
+
+    /// Read by the MainDevice from the SubDevice's read mailbox (SM1).
+    FromSubDevice,
+}
+
+/// A single complete mailbox message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailboxMessage {
+    /// Configured station address of the SubDevice.
+    pub address: u16,
+
+    pub direction: MailboxDirection,
+
+    pub header: MailboxHeader,
+
+    /// Message data following the mailbox header, trimmed to the length given in the header.
+    pub payload: Vec<u8>,
+
+    /// Time the mailbox was written or read, relative to the start of the capture.
+    pub time: Duration,
+
+    /// Wireshark packet number of the frame containing the message.
+    pub packet_number: usize,
+}
+
+/// Physical memory location of a SubDevice's mailboxes.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MailboxConfig {
+    /// Write mailbox (SM0) start address and length.
+    pub write: Option<(u16, u16)>,
+
+    /// Read mailbox (SM1) start address and length.
+    pub read: Option<(u16, u16)>,
+}
+
+/// Tracks mailbox configuration of each SubDevice to turn mailbox reads and writes into
+/// [`MailboxMessage`]s.
+#[derive(Debug, Clone, Default)]
+pub struct MailboxReader {
+    /// Mailbox configuration by configured station address.
+    pub configs: HashMap<u16, MailboxConfig>,
+}
+
+impl MailboxReader {
+    /// Process a single exchange, returning any mailbox message it contains.
+    pub fn push(&mut self, exchange: &PduExchange) -> Option<MailboxMessage> {
+        // Every mailbox access must be accepted by the SubDevice. Reads of an empty mailbox will
+        // also return a working counter of zero.
+        if exchange.response.working_counter != 1 {
+            return None;
+        }
+
+        let (address, direction, data) = match exchange.request.command {
+            Command::Write(Writes::Fpwr { address, register }) => {
+                let config = self.configs.entry(address).or_default();
+
+                let sm0 = register_range(
+                    register,
+                    &exchange.request.data,
+                    RegisterAddress::Sm0.into(),
+                    4,
+                );
+                let sm1 = register_range(
+                    register,
+                    &exchange.request.data,
+                    RegisterAddress::Sm1.into(),
+                    4,
+                );
+
+                if let Some(sm0) = sm0 {
+                    config.write = Some(sm_location(sm0));
+                }
+
+                if let Some(sm1) = sm1 {
+                    config.read = Some(sm_location(sm1));
+                }
+
+                if sm0.is_some() || sm1.is_some() {
+                    log::trace!("{:#06x} mailbox config {:?}", address, config);
+
+                    return None;
+                }
+
+                match config.write {
+                    Some((start, _len)) if start == register => (
+                        address,
+                        MailboxDirection::ToSubDevice,
+                        &exchange.request.data,
+                    ),
+                    _ => return None,
+                }
+            }
+            Command::Read(Reads::Fprd { address, register }) => {
+                match self.configs.get(&address).and_then(|config| config.read) {
+                    Some((start, _len)) if start == register => (
+                        address,
+                        MailboxDirection::FromSubDevice,
+                        &exchange.response.data,
+                    ),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        let header = MailboxHeader::parse(data)?;
+
+        let Some(payload) =
+            data.get(MAILBOX_HEADER_LEN..MAILBOX_HEADER_LEN + usize::from(header.length))
+        else {
+            log::warn!(
+                "Packet {}: mailbox message for {:#06x} is truncated, header length {}, data length {}",
+                exchange.rx_packet_number,
+                address,
+                header.length,
+                data.len()
+            );
+
+            return None;
+        };
+
+        let (time, packet_number) = match direction {
+            MailboxDirection::ToSubDevice => (exchange.tx_time, exchange.tx_packet_number),
+            MailboxDirection::FromSubDevice => (exchange.rx_time, exchange.rx_packet_number),
+        };
+
+        Some(MailboxMessage {
+            address,
+            direction,
+            header,
+            payload: payload.to_vec(),
+            time,
+            packet_number,
+        })
+    }
+}
+
+/// Get the physical start address and length from the first 4 bytes of a SyncManager
+/// configuration.
+fn sm_location(raw: &[u8]) -> (u16, u16) {
+    (
+        u16::from_le_bytes([raw[0], raw[1]]),
+        u16::from_le_bytes([raw[2], raw[3]]),
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::pdu::Pdu;
+
+    /// Configured station address of the SubDevice in every test exchange.
+    pub(crate) const ADDRESS: u16 = 0x1001;
+
+    const WRITE_START: u16 = 0x1000;
+    const READ_START: u16 = 0x1080;
+    const MAILBOX_LEN: usize = 0x80;
+
+    fn exchange(
+        command: Command,
+        request: Vec<u8>,
+        response: Vec<u8>,
+        working_counter: u16,
+        packet_number: usize,
+    ) -> PduExchange {
+        PduExchange {
+            request: Pdu {
+                command,
+                data: request,
+                ..Pdu::default()
+            },
+            response: Pdu {
+                command,
+                data: response,
+                working_counter,
+                ..Pdu::default()
+            },
+            tx_time: Duration::from_micros(packet_number as u64 * 100),
+            rx_time: Duration::from_micros(packet_number as u64 * 100 + 10),
+            tx_packet_number: packet_number,
+            rx_packet_number: packet_number + 1,
+            cycle: 0,
+        }
+    }
+
+    /// Mailbox header followed by `payload`, padded to the mailbox length.
+    fn message(mailbox_type: MailboxType, payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        data.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.push(0);
+        data.push(u8::from(mailbox_type) | 1 << 4);
+        data.extend_from_slice(payload);
+        data.resize(MAILBOX_LEN, 0);
+
+        data
+    }
+
+    /// Configure SM0 and SM1 as the write and read mailboxes in a single write.
+    pub(crate) fn sm_config(packet_number: usize) -> PduExchange {
+        let mut data = Vec::new();
+
+        for start in [WRITE_START, READ_START] {
+            data.extend_from_slice(&start.to_le_bytes());
+            data.extend_from_slice(&(MAILBOX_LEN as u16).to_le_bytes());
+            data.extend_from_slice(&[0x26, 0x00, 0x01, 0x00]);
+        }
+
+        exchange(
+            Command::Write(Writes::Fpwr {
+                address: ADDRESS,
+                register: RegisterAddress::Sm0.into(),
+            }),
+            data.clone(),
+            data,
+            1,
+            packet_number,
+        )
+    }
+
+    /// Write a CoE message to the SubDevice's write mailbox.
+    pub(crate) fn mailbox_write(payload: &[u8], packet_number: usize) -> PduExchange {
+        let data = message(MailboxType::Coe, payload);
+
+        exchange(
+            Command::Write(Writes::Fpwr {
+                address: ADDRESS,
+                register: WRITE_START,
+            }),
+            data.clone(),
+            data,
+            1,
+            packet_number,
+        )
+    }
+
+    /// Read a CoE message from the SubDevice's read mailbox.
+    pub(crate) fn mailbox_read(payload: &[u8], packet_number: usize) -> PduExchange {
+        exchange(
+            Command::Read(Reads::Fprd {
+                address: ADDRESS,
+                register: READ_START,
+            }),
+            vec![0; MAILBOX_LEN],
+            message(MailboxType::Coe, payload),
+            1,
+            packet_number,
+        )
+    }
+
+    #[test]
+    fn header() {
+        assert_eq!(
+            MailboxHeader::parse(&[0x0a, 0x00, 0x01, 0x10, 0x42, 0x23]),
+            Some(MailboxHeader {
+                length: 10,
+                address: 0x1001,
+                channel: 2,
+                priority: 1,
+                mailbox_type: MailboxType::Coe,
+                counter: 2,
+            })
+        );
+
+        assert_eq!(MailboxHeader::parse(&[0x0a, 0x00, 0x01, 0x10, 0x42]), None);
+    }
+
+    #[test]
+    fn config() {
+        let mut reader = MailboxReader::default();
+
+        // Mailbox traffic is ignored until the SyncManagers are configured
+        assert_eq!(reader.push(&mailbox_write(&[0x00, 0x20], 1)), None);
+
+        assert_eq!(reader.push(&sm_config(3)), None);
+
+        assert_eq!(
+            reader.configs.get(&ADDRESS),
+            Some(&MailboxConfig {
+                write: Some((WRITE_START, MAILBOX_LEN as u16)),
+                read: Some((READ_START, MAILBOX_LEN as u16)),
+            })
+        );
+    }
+
+    #[test]
+    fn messages() {
+        let mut reader = MailboxReader::default();
+
+        reader.push(&sm_config(1));
+
+        let write = reader.push(&mailbox_write(&[0x00, 0x20, 0x40], 3)).unwrap();
+
+        assert_eq!(write.address, ADDRESS);
+        assert_eq!(write.direction, MailboxDirection::ToSubDevice);
+        assert_eq!(write.header.mailbox_type, MailboxType::Coe);
+        assert_eq!(write.payload, [0x00, 0x20, 0x40]);
+        assert_eq!(write.packet_number, 3);
+        assert_eq!(write.time, Duration::from_micros(300));
+
+        let read = reader.push(&mailbox_read(&[0x00, 0x30, 0x43], 5)).unwrap();
+
+        assert_eq!(read.direction, MailboxDirection::FromSubDevice);
+        assert_eq!(read.payload, [0x00, 0x30, 0x43]);
+        assert_eq!(read.packet_number, 6);
+        assert_eq!(read.time, Duration::from_micros(510));
+    }
+
+    #[test]
+    fn empty_mailbox() {
+        let mut reader = MailboxReader::default();
+
+        reader.push(&sm_config(1));
+
+        let mut read = mailbox_read(&[0x00, 0x30], 3);
+
+        read.response.working_counter = 0;
+
+        assert_eq!(reader.push(&read), None);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut reader = MailboxReader::default();
+
+        reader.push(&sm_config(1));
+
+        let mut read = mailbox_read(&[0x00, 0x30], 3);
+
+        // Header length past the end of the mailbox
+        read.response.data[0..2].copy_from_slice(&(MAILBOX_LEN as u16).to_le_bytes());
+
+        assert_eq!(reader.push(&read), None);
+    }
+}
@@ -0,0 +1,537 @@
+//! Reconstruct PDO mapping and FMMU configuration from SubDevice startup traffic.
+//!
+//! PDO assignment (`0x1C10` + SyncManager index) and mapping (`0x1600` and `0x1A00` ranges)
+//! objects are taken from CoE uploads and downloads, falling back to the PDOs in the SII for
+//! SubDevices without CoE. Combined with the SyncManager and FMMU registers written during
+//! initialisation, each PDO entry can be located in the logical process image.
+
+use crate::{
+    coe::{SdoReader, SdoResult, SdoTransaction},
+    exchange::{register_range, PduExchange},
+    pdu::{Command, Writes},
+    sii::{SiiDevice, SiiImage, SiiReader},
+    sii_decode::{CategoryContents, SiiContents},
+};
+use ethercrab::RegisterAddress;
+use serde::Serialize;
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+/// Number of FMMUs an ESC can have.
+pub const MAX_FMMUS: u8 = 16;
+
+/// Number of SyncManagers an ESC can have.
+pub const MAX_SYNC_MANAGERS: u8 = 32;
+
+/// Length of a single FMMU's configuration registers.
+const FMMU_LEN: usize = 16;
+
+/// Length of a single SyncManager's configuration registers.
+const SM_LEN: usize = 8;
+
+/// PDO assignment objects, one per SyncManager.
+const ASSIGNMENT_OBJECTS: RangeInclusive<u16> = 0x1c10..=0x1c2f;
+
+/// RxPDO mapping objects.
+const RXPDO_OBJECTS: RangeInclusive<u16> = 0x1600..=0x17ff;
+
+/// TxPDO mapping objects.
+const TXPDO_OBJECTS: RangeInclusive<u16> = 0x1a00..=0x1bff;
+
+/// Which way process data travels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum ProcessDataDirection {
+    /// Written by the MainDevice (RxPDOs).
+    Outputs,
+
+    /// Read by the MainDevice (TxPDOs).
+    Inputs,
+}
+
+impl std::fmt::Display for ProcessDataDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Outputs => f.pad("outputs"),
+            Self::Inputs => f.pad("inputs"),
+        }
+    }
+}
+
+/// FMMU type register, ETG1000.4 Table 57.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum FmmuType {
+    Unused,
+    Read,
+    Write,
+    ReadWrite,
+    Unknown(u8),
+}
+
+impl From<u8> for FmmuType {
+    fn from(raw: u8) -> Self {
+        match raw {
+            0x00 => Self::Unused,
+            0x01 => Self::Read,
+            0x02 => Self::Write,
+            0x03 => Self::ReadWrite,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// A single FMMU configuration, ETG1000.4 Table 57.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct Fmmu {
+    pub logical_start: u32,
+
+    /// Length in bytes, counting partially used first and last bytes.
+    pub length: u16,
+
+    pub logical_start_bit: u8,
+
+    pub logical_end_bit: u8,
+
+    pub physical_start: u16,
+
+    pub physical_start_bit: u8,
+
+    pub fmmu_type: FmmuType,
+
+    pub enabled: bool,
+}
+
+impl Fmmu {
+    fn parse(raw: &[u8]) -> Self {
+        Self {
+            logical_start: u32::from_le_bytes(raw[0..4].try_into().unwrap()),
+            length: u16::from_le_bytes([raw[4], raw[5]]),
+            logical_start_bit: raw[6] & 0b111,
+            logical_end_bit: raw[7] & 0b111,
+            physical_start: u16::from_le_bytes([raw[8], raw[9]]),
+            physical_start_bit: raw[10] & 0b111,
+            fmmu_type: FmmuType::from(raw[11]),
+            enabled: raw[12] & 0x01 != 0,
+        }
+    }
+
+    /// Logical bit address of the given physical bit address, if it's covered by this FMMU.
+    ///
+    /// Returns `None` if the FMMU doesn't cover any bits.
+    pub fn to_logical(&self, physical_bit: u64) -> Option<u64> {
+        let start = u64::from(self.physical_start) * 8 + u64::from(self.physical_start_bit);
+        // Zero length or inconsistent start and end bits, e.g. from a partially captured write
+        let bits = (u64::from(self.length) * 8)
+            .checked_sub(u64::from(self.logical_start_bit))?
+            .checked_sub(7 - u64::from(self.logical_end_bit))?;
+
+        let offset = physical_bit.checked_sub(start)?;
+
+        (offset < bits)
+            .then(|| u64::from(self.logical_start) * 8 + u64::from(self.logical_start_bit) + offset)
+    }
+}
+
+/// A single SyncManager configuration, ETG1000.4 Table 58.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct SyncManager {
+    pub start: u16,
+
+    pub length: u16,
+
+    pub control: u8,
+
+    pub enabled: bool,
+}
+
+impl SyncManager {
+    fn parse(raw: &[u8]) -> Self {
+        Self {
+            start: u16::from_le_bytes([raw[0], raw[1]]),
+            length: u16::from_le_bytes([raw[2], raw[3]]),
+            control: raw[4],
+            enabled: raw[6] & 0x01 != 0,
+        }
+    }
+
+    /// Whether the MainDevice writes or reads this SyncManager.
+    pub fn direction(&self) -> ProcessDataDirection {
+        if (self.control >> 2) & 0b11 == 0x01 {
+            ProcessDataDirection::Outputs
+        } else {
+            ProcessDataDirection::Inputs
+        }
+    }
+}
+
+/// Where a PDO's mapping was found.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum PdoSource {
+    Coe,
+    Sii,
+}
+
+/// A single object mapped into a PDO.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PdoEntry {
+    /// Object index, or zero for padding.
+    pub index: u16,
+
+    pub sub_index: u8,
+
+    pub bit_len: u8,
+
+    /// Entry name from the SII, if known.
+    pub name: Option<String>,
+}
+
+impl PdoEntry {
+    fn from_raw(raw: u32) -> Self {
+        Self {
+            index: (raw >> 16) as u16,
+            sub_index: (raw >> 8) as u8,
+            bit_len: raw as u8,
+            name: None,
+        }
+    }
+}
+
+/// A PDO entry located in a SubDevice's SyncManager and the logical process image.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MappedEntry {
+    /// Configured station address of the SubDevice.
+    pub address: u16,
+
+    pub direction: ProcessDataDirection,
+
+    pub sync_manager: u8,
+
+    /// PDO the entry belongs to.
+    pub pdo: u16,
+
+    pub source: PdoSource,
+
+    pub entry: PdoEntry,
+
+    /// Offset in bits from the start of the SyncManager.
+    pub sm_bit_offset: u32,
+
+    /// Bit address in the logical process image, if an FMMU maps the entry.
+    pub logical_bit: Option<u64>,
+}
+
+/// Values written to or read from the sub-indices of a CoE array or record.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ObjectValues {
+    /// Number of entries, from sub-index 0.
+    count: Option<u8>,
+
+    /// Value of each sub-index from 1 upwards.
+    values: BTreeMap<u8, u32>,
+}
+
+impl ObjectValues {
+    /// Apply a successful SDO transaction for an object with entries of `width` bytes.
+    fn push(&mut self, transaction: &SdoTransaction, width: usize) {
+        let data = transaction.data.as_slice();
+
+        let read = |raw: &[u8]| {
+            let mut buf = [0u8; 4];
+
+            buf[..raw.len()].copy_from_slice(raw);
+
+            u32::from_le_bytes(buf)
+        };
+
+        if transaction.complete_access {
+            // Sub-index 0 is sent as a `u16` when transferring the complete object.
+            let (first, data) = if transaction.sub_index == 0 {
+                let Some(count) = data.first() else {
+                    return;
+                };
+
+                self.count = Some(*count);
+
+                (1, data.get(2..).unwrap_or_default())
+            } else {
+                (transaction.sub_index, data)
+            };
+
+            for (sub_index, raw) in (first..).zip(data.chunks_exact(width)) {
+                self.values.insert(sub_index, read(raw));
+            }
+        } else if transaction.sub_index == 0 {
+            self.count = data.first().copied();
+        } else if data.len() >= width {
+            self.values
+                .insert(transaction.sub_index, read(&data[0..width]));
+        }
+    }
+
+    /// Entries from sub-index 1 to the count in sub-index 0.
+    fn entries(&self) -> Option<Vec<u32>> {
+        (1..=self.count?)
+            .map(|sub_index| self.values.get(&sub_index).copied())
+            .collect()
+    }
+}
+
+/// Process data configuration of a single SubDevice.
+#[derive(Debug, Clone, Default)]
+pub struct SubDeviceMapping {
+    pub fmmus: BTreeMap<u8, Fmmu>,
+
+    pub sync_managers: BTreeMap<u8, SyncManager>,
+
+    /// Recovered SII contents, used for PDOs when the SubDevice has no CoE mapping.
+    pub sii: Option<SiiImage>,
+
+    /// CoE PDO assignment objects by SyncManager index.
+    assignments: BTreeMap<u8, ObjectValues>,
+
+    /// CoE PDO mapping objects by index.
+    mappings: BTreeMap<u16, ObjectValues>,
+}
+
+impl SubDeviceMapping {
+    /// PDOs assigned to each SyncManager over CoE.
+    pub fn coe_assignments(&self) -> BTreeMap<u8, Vec<u16>> {
+        self.assignments
+            .iter()
+            .filter_map(|(sm, values)| {
+                let pdos = values
+                    .entries()?
+                    .into_iter()
+                    .map(|pdo| pdo as u16)
+                    .collect();
+
+                Some((*sm, pdos))
+            })
+            .collect()
+    }
+
+    /// Entries of each PDO mapping object read or written over CoE.
+    pub fn coe_mappings(&self) -> BTreeMap<u16, Vec<PdoEntry>> {
+        self.mappings
+            .iter()
+            .filter_map(|(index, values)| {
+                let entries = values
+                    .entries()?
+                    .into_iter()
+                    .map(PdoEntry::from_raw)
+                    .collect();
+
+                Some((*index, entries))
+            })
+            .collect()
+    }
+
+    /// PDOs by SyncManager index from the recovered SII, if any were captured.
+    pub fn sii_pdos(&self) -> BTreeMap<u8, Vec<(u16, Vec<PdoEntry>)>> {
+        let mut by_sm = BTreeMap::<u8, Vec<(u16, Vec<PdoEntry>)>>::new();
+
+        let Some(image) = &self.sii else {
+            return by_sm;
+        };
+
+        for category in SiiContents::decode(image).categories {
+            let pdos = match category.contents {
+                Some(CategoryContents::RxPdos(pdos) | CategoryContents::TxPdos(pdos)) => pdos,
+                _ => continue,
+            };
+
+            for pdo in pdos {
+                let entries = pdo
+                    .entries
+                    .into_iter()
+                    .map(|entry| PdoEntry {
+                        index: entry.index,
+                        sub_index: entry.sub_index,
+                        bit_len: entry.bit_len,
+                        name: entry.name,
+                    })
+                    .collect();
+
+                by_sm
+                    .entry(pdo.sync_manager)
+                    .or_default()
+                    .push((pdo.index, entries));
+            }
+        }
+
+        by_sm
+    }
+
+    /// Locate every PDO entry of this SubDevice in its SyncManagers and the logical process
+    /// image.
+    pub fn entries(&self, address: u16) -> Vec<MappedEntry> {
+        let assignments = self.coe_assignments();
+        let mappings = self.coe_mappings();
+        let sii = self.sii_pdos();
+
+        let mut entries = Vec::new();
+
+        for (sm_index, sm) in self.sync_managers.iter() {
+            let pdos = match assignments.get(sm_index) {
+                Some(assigned) => assigned
+                    .iter()
+                    .filter_map(|pdo| {
+                        mappings
+                            .get(pdo)
+                            .map(|entries| (*pdo, PdoSource::Coe, entries.clone()))
+                            .or_else(|| {
+                                sii.values().flatten().find(|(index, _)| index == pdo).map(
+                                    |(index, entries)| (*index, PdoSource::Sii, entries.clone()),
+                                )
+                            })
+                    })
+                    .collect::<Vec<_>>(),
+                None => sii
+                    .get(sm_index)
+                    .into_iter()
+                    .flatten()
+                    .map(|(index, entries)| (*index, PdoSource::Sii, entries.clone()))
+                    .collect(),
+            };
+
+            let mut sm_bit_offset = 0u32;
+
+            for (pdo, source, pdo_entries) in pdos {
+                for entry in pdo_entries {
+                    let physical_bit = u64::from(sm.start) * 8 + u64::from(sm_bit_offset);
+
+                    let logical_bit = self
+                        .fmmus
+                        .values()
+                        .filter(|fmmu| fmmu.enabled)
+                        .find_map(|fmmu| fmmu.to_logical(physical_bit));
+
+                    let bit_len = entry.bit_len;
+
+                    entries.push(MappedEntry {
+                        address,
+                        direction: sm.direction(),
+                        sync_manager: *sm_index,
+                        pdo,
+                        source,
+                        entry,
+                        sm_bit_offset,
+                        logical_bit,
+                    });
+
+                    sm_bit_offset += u32::from(bit_len);
+                }
+            }
+        }
+
+        entries
+    }
+}
+
+/// PDO mapping and FMMU configuration of every SubDevice in a capture.
+#[derive(Debug, Clone, Default)]
+pub struct PdoMapping {
+    /// Mapping by configured station address.
+    pub subdevices: BTreeMap<u16, SubDeviceMapping>,
+
+    sdo: SdoReader,
+
+    sii: SiiReader,
+}
+
+impl PdoMapping {
+    /// Reconstruct the mapping from every exchange in a capture.
+    pub fn new(exchanges: impl Iterator<Item = PduExchange>) -> Self {
+        let mut self_ = Self::default();
+
+        for exchange in exchanges {
+            self_.push(&exchange);
+        }
+
+        self_
+    }
+
+    /// Update the mapping with a single exchange.
+    pub fn push(&mut self, exchange: &PduExchange) {
+        for access in self.sii.push(exchange) {
+            if let SiiDevice::Configured(address) = access.device {
+                self.subdevices
+                    .entry(address)
+                    .or_default()
+                    .sii
+                    .get_or_insert_with(SiiImage::default)
+                    .write(access.word_address, &access.data);
+            }
+        }
+
+        for transaction in self.sdo.push(exchange) {
+            self.push_sdo(&transaction);
+        }
+
+        if exchange.response.working_counter == 0 {
+            return;
+        }
+
+        let Command::Write(Writes::Fpwr { address, register }) = exchange.request.command else {
+            return;
+        };
+
+        let data = &exchange.request.data;
+
+        for index in 0..MAX_FMMUS {
+            let start = u16::from(RegisterAddress::Fmmu0) + u16::from(index) * FMMU_LEN as u16;
+
+            if let Some(raw) = register_range(register, data, start, FMMU_LEN) {
+                self.subdevices
+                    .entry(address)
+                    .or_default()
+                    .fmmus
+                    .insert(index, Fmmu::parse(raw));
+            }
+        }
+
+        for index in 0..MAX_SYNC_MANAGERS {
+            let start = u16::from(RegisterAddress::Sm0) + u16::from(index) * SM_LEN as u16;
+
+            if let Some(raw) = register_range(register, data, start, SM_LEN) {
+                self.subdevices
+                    .entry(address)
+                    .or_default()
+                    .sync_managers
+                    .insert(index, SyncManager::parse(raw));
+            }
+        }
+    }
+
+    fn push_sdo(&mut self, transaction: &SdoTransaction) {
+        if transaction.result != SdoResult::Ok {
+            return;
+        }
+
+        let subdevice = self.subdevices.entry(transaction.address).or_default();
+
+        if ASSIGNMENT_OBJECTS.contains(&transaction.index) {
+            let sm = (transaction.index - ASSIGNMENT_OBJECTS.start()) as u8;
+
+            subdevice
+                .assignments
+                .entry(sm)
+                .or_default()
+                .push(transaction, 2);
+        } else if RXPDO_OBJECTS.contains(&transaction.index)
+            || TXPDO_OBJECTS.contains(&transaction.index)
+        {
+            subdevice
+                .mappings
+                .entry(transaction.index)
+                .or_default()
+                .push(transaction, 4);
+        }
+    }
+
+    /// Every mapped PDO entry of every SubDevice.
+    pub fn entries(&self) -> Vec<MappedEntry> {
+        self.subdevices
+            .iter()
+            .flat_map(|(address, subdevice)| subdevice.entries(*address))
+            .collect()
+    }
+}
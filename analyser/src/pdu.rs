@@ -6,7 +6,6 @@
 use std::time::Duration;
 
 use crate::ETHERCAT_ETHERTYPE;
-use ethercrab::{Command, Reads, Writes};
 use nom::{
     bytes::complete::take,
     combinator::{map, map_res, verify},
@@ -17,7 +16,7 @@ use nom::{
     IResult,
 };
 use packed_struct::{PackedStruct, PackedStructInfo, PackedStructSlice};
-use smoltcp::wire::EthernetFrame;
+use smoltcp::wire::{EthernetAddress, EthernetFrame};
 
 const LEN_MASK: u16 = 0b0000_0111_1111_1111;
 
@@ -32,6 +31,12 @@ pub struct Frame {
     pub time: Duration,
     pub wireshark_packet_number: usize,
     pub pdus: Vec<Pdu>,
+
+    /// Source MAC address.
+    pub source: EthernetAddress,
+
+    /// Length of the Ethernet frame in bytes, as captured.
+    pub size: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -90,7 +95,9 @@ pub fn parse_pdu(mut raw_packet: EthernetFrame<Vec<u8>>) -> Result<Frame, etherc
         "Not a valid EtherCAT frame"
     );
 
-    let from_master = !raw_packet.src_addr().is_local();
+    let source = raw_packet.src_addr();
+    let from_master = !source.is_local();
+    let size = raw_packet.as_ref().len();
 
     let i = raw_packet.payload_mut();
 
@@ -105,6 +112,8 @@ pub fn parse_pdu(mut raw_packet: EthernetFrame<Vec<u8>>) -> Result<Frame, etherc
 
     let (_rest, pdus) = many0(parse_pdu_inner)(i).expect("Bad parse");
 
+    let pdus = pdus.into_iter().flatten().collect();
+
     // `_i` should be empty as we `take()`d an exact amount above.
     // debug_assert_eq!(
     //     i.len(),
@@ -119,10 +128,13 @@ pub fn parse_pdu(mut raw_packet: EthernetFrame<Vec<u8>>) -> Result<Frame, etherc
         time: Duration::default(),
         wireshark_packet_number: 0,
         pdus,
+        source,
+        size,
     })
 }
 
-fn parse_pdu_inner(i: &[u8]) -> IResult<&[u8], Pdu> {
+/// Parse a single PDU, or `None` if it has an unknown command.
+fn parse_pdu_inner(i: &[u8]) -> IResult<&[u8], Option<Pdu>> {
     let (i, command_code) = u8(i)?;
     let (i, index) = u8(i)?;
 
@@ -135,13 +147,13 @@ fn parse_pdu_inner(i: &[u8]) -> IResult<&[u8], Pdu> {
 
     Ok((
         i,
-        Pdu {
+        command.map(|command| Pdu {
             index,
             command,
             flags,
             data: data.to_vec(),
             working_counter,
-        },
+        }),
     ))
 }
 
@@ -191,20 +203,204 @@ impl PackedStructInfo for PduFlags {
     }
 }
 
-const NOP: u8 = 0x00;
-const APRD: u8 = 0x01;
-const FPRD: u8 = 0x04;
-const BRD: u8 = 0x07;
-const LRD: u8 = 0x0A;
-const BWR: u8 = 0x08;
-const APWR: u8 = 0x02;
-const FPWR: u8 = 0x05;
-const FRMW: u8 = 0x0E;
-const LWR: u8 = 0x0B;
-const LRW: u8 = 0x0c;
-
-fn parse_command(command_code: u8, i: &[u8]) -> IResult<&[u8], Command> {
-    match command_code {
+/// An EtherCAT command with its address, as EtherCrab's `Command` plus ARMW and the register
+/// read/write commands which EtherCrab doesn't send.
+#[derive(Default, PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Command {
+    /// No operation.
+    #[default]
+    Nop,
+
+    /// Read commands.
+    Read(Reads),
+
+    /// Write commands.
+    Write(Writes),
+}
+
+/// Read commands.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Reads {
+    /// APRD.
+    Aprd {
+        /// Auto increment counter.
+        address: u16,
+
+        /// Memory location to read from.
+        register: u16,
+    },
+    /// FPRD.
+    Fprd {
+        /// Configured station address.
+        address: u16,
+
+        /// Memory location to read from.
+        register: u16,
+    },
+    /// Broadcast Read (BRD).
+    Brd {
+        /// Autoincremented by each slave visited.
+        address: u16,
+
+        /// Memory location to read from.
+        register: u16,
+    },
+    /// LRD.
+    Lrd {
+        /// Logical address.
+        address: u32,
+    },
+    /// FRMW.
+    Frmw {
+        /// Configured station address.
+        address: u16,
+
+        /// Memory location to read from.
+        register: u16,
+    },
+    /// ARMW.
+    Armw {
+        /// Auto increment counter.
+        address: u16,
+
+        /// Memory location to read from.
+        register: u16,
+    },
+}
+
+/// Write commands.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Writes {
+    /// BWR.
+    Bwr {
+        /// Autoincremented by each slave visited.
+        address: u16,
+
+        /// Memory location to write to.
+        register: u16,
+    },
+    /// APWR.
+    Apwr {
+        /// Auto increment counter.
+        address: u16,
+
+        /// Memory location to write to.
+        register: u16,
+    },
+    /// FPWR.
+    Fpwr {
+        /// Configured station address.
+        address: u16,
+
+        /// Memory location to write to.
+        register: u16,
+    },
+    /// LWR.
+    Lwr {
+        /// Logical address.
+        address: u32,
+    },
+    /// LRW.
+    Lrw {
+        /// Logical address.
+        address: u32,
+    },
+    /// APRW.
+    Aprw {
+        /// Auto increment counter.
+        address: u16,
+
+        /// Memory location to read from and write to.
+        register: u16,
+    },
+    /// FPRW.
+    Fprw {
+        /// Configured station address.
+        address: u16,
+
+        /// Memory location to read from and write to.
+        register: u16,
+    },
+    /// BRW.
+    Brw {
+        /// Autoincremented by each slave visited.
+        address: u16,
+
+        /// Memory location to read from and write to.
+        register: u16,
+    },
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Nop => write!(f, "NOP"),
+
+            Command::Read(read) => match read {
+                Reads::Aprd { address, register } => {
+                    write!(f, "APRD(addr {}, reg {})", address, register)
+                }
+                Reads::Fprd { address, register } => {
+                    write!(f, "FPRD(addr {}, reg {})", address, register)
+                }
+                Reads::Brd { address, register } => {
+                    write!(f, "BRD(addr {}, reg {})", address, register)
+                }
+                Reads::Lrd { address } => write!(f, "LRD(addr {})", address),
+                Reads::Frmw { address, register } => {
+                    write!(f, "FRMW(addr {}, reg {})", address, register)
+                }
+                Reads::Armw { address, register } => {
+                    write!(f, "ARMW(addr {}, reg {})", address, register)
+                }
+            },
+
+            Command::Write(write) => match write {
+                Writes::Bwr { address, register } => {
+                    write!(f, "BWR(addr {}, reg {})", address, register)
+                }
+                Writes::Apwr { address, register } => {
+                    write!(f, "APWR(addr {}, reg {})", address, register)
+                }
+                Writes::Fpwr { address, register } => {
+                    write!(f, "FPWR(addr {}, reg {})", address, register)
+                }
+
+                Writes::Lwr { address } => write!(f, "LWR(addr {})", address),
+                Writes::Lrw { address } => write!(f, "LRW(addr {})", address),
+                Writes::Aprw { address, register } => {
+                    write!(f, "APRW(addr {}, reg {})", address, register)
+                }
+                Writes::Fprw { address, register } => {
+                    write!(f, "FPRW(addr {}, reg {})", address, register)
+                }
+                Writes::Brw { address, register } => {
+                    write!(f, "BRW(addr {}, reg {})", address, register)
+                }
+            },
+        }
+    }
+}
+
+pub(crate) const NOP: u8 = 0x00;
+pub(crate) const APRD: u8 = 0x01;
+pub(crate) const FPRD: u8 = 0x04;
+pub(crate) const BRD: u8 = 0x07;
+pub(crate) const LRD: u8 = 0x0A;
+pub(crate) const BWR: u8 = 0x08;
+pub(crate) const APWR: u8 = 0x02;
+pub(crate) const FPWR: u8 = 0x05;
+pub(crate) const FRMW: u8 = 0x0E;
+pub(crate) const LWR: u8 = 0x0B;
+pub(crate) const LRW: u8 = 0x0c;
+pub(crate) const ARMW: u8 = 0x0D;
+pub(crate) const APRW: u8 = 0x03;
+pub(crate) const FPRW: u8 = 0x06;
+pub(crate) const BRW: u8 = 0x09;
+
+/// Parse the address fields of a command, or `None` for an unknown command code.
+pub(crate) fn parse_command(command_code: u8, i: &[u8]) -> IResult<&[u8], Option<Command>> {
+    let (i, command) = match command_code {
         NOP => Ok((i, Command::Nop)),
 
         APRD => map(pair(le_u16, le_u16), |(address, register)| {
@@ -234,13 +430,29 @@ fn parse_command(command_code: u8, i: &[u8]) -> IResult<&[u8], Command> {
 
         LRW => map(le_u32, |address| Command::Write(Writes::Lrw { address }))(i),
 
+        ARMW => map(pair(le_u16, le_u16), |(address, register)| {
+            Command::Read(Reads::Armw { address, register })
+        })(i),
+
+        APRW => map(pair(le_u16, le_u16), |(address, register)| {
+            Command::Write(Writes::Aprw { address, register })
+        })(i),
+        FPRW => map(pair(le_u16, le_u16), |(address, register)| {
+            Command::Write(Writes::Fprw { address, register })
+        })(i),
+        BRW => map(pair(le_u16, le_u16), |(address, register)| {
+            Command::Write(Writes::Brw { address, register })
+        })(i),
+
+        // Every command has a 4 byte address, so the rest of the PDU can still be parsed
         other => {
-            log::error!("Invalid command code {:#02x}", other);
+            log::warn!("Skipping PDU with unknown command code {:#04x}", other);
 
-            Err(nom::Err::Failure(nom::error::Error {
-                input: i,
-                code: nom::error::ErrorKind::Tag,
-            }))
+            let (i, _address) = take(4usize)(i)?;
+
+            return Ok((i, None));
         }
-    }
+    }?;
+
+    Ok((i, Some(command)))
 }
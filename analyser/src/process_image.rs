@@ -0,0 +1,387 @@
+//! Decode LRW/LRD/LWR payloads into per-SubDevice process data time series.
+//!
+//! Logical PDUs are split using the FMMU configuration captured at startup. Every mapped byte of
+//! each SubDevice is available as a signal, along with each PDO entry when the PDO mapping is
+//! known.
+
+use crate::{
+    exchange::PduExchange,
+    pdo::{FmmuType, PdoMapping, ProcessDataDirection},
+    pdu::{Command, Reads, Writes},
+};
+use std::{collections::BTreeMap, time::Duration};
+
+/// Largest PDO entry that can be decoded into a single value.
+const MAX_VALUE_BITS: u8 = 64;
+
+/// A single logical read and/or write.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogicalFrame {
+    /// Time the request was sent, relative to the start of the capture.
+    pub time: Duration,
+
+    /// Wireshark packet number of the frame containing the request.
+    pub packet_number: usize,
+
+    /// Number of frames sent by the MainDevice before this one, see [`PduExchange::cycle`].
+    pub cycle: usize,
+
+    /// Logical address of the first byte of the PDU.
+    pub logical_address: u32,
+
+    /// Data written by the MainDevice for LRW and LWR.
+    pub outputs: Option<Vec<u8>>,
+
+    /// Data returned by the network for LRW and LRD, if at least one SubDevice responded.
+    pub inputs: Option<Vec<u8>>,
+}
+
+impl LogicalFrame {
+    fn from_exchange(exchange: &PduExchange) -> Option<Self> {
+        let (logical_address, write, read) = match exchange.request.command {
+            Command::Write(Writes::Lrw { address }) => (address, true, true),
+            Command::Write(Writes::Lwr { address }) => (address, true, false),
+            Command::Read(Reads::Lrd { address }) => (address, false, true),
+            _ => return None,
+        };
+
+        Some(Self {
+            time: exchange.tx_time,
+            packet_number: exchange.tx_packet_number,
+            cycle: exchange.cycle,
+            logical_address,
+            outputs: write.then(|| exchange.request.data.clone()),
+            inputs: (read && exchange.response.working_counter > 0)
+                .then(|| exchange.response.data.clone()),
+        })
+    }
+
+    /// Read `bit_len` bits starting at the given logical bit address.
+    fn value(&self, direction: ProcessDataDirection, logical_bit: u64, bit_len: u8) -> Option<u64> {
+        let data = match direction {
+            ProcessDataDirection::Outputs => self.outputs.as_ref(),
+            ProcessDataDirection::Inputs => self.inputs.as_ref(),
+        }?;
+
+        let start = logical_bit.checked_sub(u64::from(self.logical_address) * 8)?;
+
+        let mut value = 0u64;
+
+        for i in 0..u64::from(bit_len) {
+            let bit = start + i;
+            let byte = *data.get(usize::try_from(bit / 8).ok()?)?;
+
+            if (byte >> (bit % 8)) & 1 == 1 {
+                value |= 1 << i;
+            }
+        }
+
+        Some(value)
+    }
+}
+
+/// What a [`Signal`] represents.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SignalKind {
+    /// A single byte of the SubDevice's process data, counted from the start of its first FMMU in
+    /// this direction.
+    Byte { offset: u32 },
+
+    /// A single PDO entry.
+    Entry {
+        pdo: u16,
+        index: u16,
+        sub_index: u8,
+        name: Option<String>,
+    },
+
+    /// An arbitrary bit range chosen by the user.
+    Range,
+}
+
+/// A single value in the process image.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Signal {
+    /// Configured station address of the SubDevice.
+    pub address: u16,
+
+    pub direction: ProcessDataDirection,
+
+    pub kind: SignalKind,
+
+    /// Bit address of the first bit in the logical process image.
+    pub logical_bit: u64,
+
+    pub bit_len: u8,
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#06x} {} ", self.address, self.direction)?;
+
+        match &self.kind {
+            SignalKind::Byte { offset } => write!(f, "byte {}", offset),
+            SignalKind::Entry {
+                index,
+                sub_index,
+                name,
+                ..
+            } => {
+                write!(f, "{:#06x}:{}", index, sub_index)?;
+
+                if let Some(name) = name {
+                    write!(f, " {}", name)?;
+                }
+
+                Ok(())
+            }
+            SignalKind::Range => write!(
+                f,
+                "bits {:#010x}.{}:{}",
+                self.logical_bit / 8,
+                self.logical_bit % 8,
+                self.bit_len
+            ),
+        }
+    }
+}
+
+/// A single value of a signal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sample {
+    /// Time of the logical PDU, relative to the start of the capture.
+    pub time: Duration,
+
+    /// Wireshark packet number of the logical PDU.
+    pub packet_number: usize,
+
+    /// Number of frames sent by the MainDevice before the one containing this sample. Samples of
+    /// different signals from the same frame have the same cycle.
+    pub cycle: usize,
+
+    pub value: u64,
+}
+
+/// Values of a single signal over time.
+///
+/// Only the first sample and samples where the value changed are stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeSeries {
+    pub samples: Vec<Sample>,
+
+    /// Time of the last PDU containing this signal.
+    pub end_time: Duration,
+
+    /// Number of PDUs containing this signal.
+    pub count: usize,
+}
+
+impl TimeSeries {
+    fn push(&mut self, sample: Sample) {
+        self.end_time = sample.time;
+        self.count += 1;
+
+        if self.samples.last().map(|last| last.value) != Some(sample.value) {
+            self.samples.push(sample);
+        }
+    }
+}
+
+/// Process data configuration and every logical PDU in a capture.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessImage {
+    pub mapping: PdoMapping,
+
+    pub frames: Vec<LogicalFrame>,
+}
+
+impl ProcessImage {
+    /// Collect the process image from every exchange in a capture.
+    pub fn new(exchanges: impl Iterator<Item = PduExchange>) -> Self {
+        let mut self_ = Self::default();
+
+        for exchange in exchanges {
+            self_.push(&exchange);
+        }
+
+        self_
+    }
+
+    /// Update the process image with a single exchange.
+    pub fn push(&mut self, exchange: &PduExchange) {
+        self.mapping.push(exchange);
+
+        if let Some(frame) = LogicalFrame::from_exchange(exchange) {
+            self.frames.push(frame);
+        }
+    }
+
+    /// Every byte and PDO entry mapped into the logical process image.
+    pub fn signals(&self) -> Vec<Signal> {
+        let mut signals = Vec::new();
+
+        for (address, subdevice) in self.mapping.subdevices.iter() {
+            let mut offsets = BTreeMap::<ProcessDataDirection, u32>::new();
+
+            for fmmu in subdevice.fmmus.values().filter(|fmmu| fmmu.enabled) {
+                let directions: &[ProcessDataDirection] = match fmmu.fmmu_type {
+                    FmmuType::Write => &[ProcessDataDirection::Outputs],
+                    FmmuType::Read => &[ProcessDataDirection::Inputs],
+                    FmmuType::ReadWrite => {
+                        &[ProcessDataDirection::Outputs, ProcessDataDirection::Inputs]
+                    }
+                    _ => &[],
+                };
+
+                for direction in directions {
+                    let offset = offsets.entry(*direction).or_default();
+
+                    for byte in 0..u32::from(fmmu.length) {
+                        signals.push(Signal {
+                            address: *address,
+                            direction: *direction,
+                            kind: SignalKind::Byte { offset: *offset },
+                            logical_bit: (u64::from(fmmu.logical_start) + u64::from(byte)) * 8,
+                            bit_len: 8,
+                        });
+
+                        *offset += 1;
+                    }
+                }
+            }
+
+            for entry in subdevice.entries(*address) {
+                let Some(logical_bit) = entry.logical_bit else {
+                    continue;
+                };
+
+                // Skip padding, and entries too large to decode into a single value.
+                if entry.entry.index == 0 || entry.entry.bit_len > MAX_VALUE_BITS {
+                    continue;
+                }
+
+                signals.push(Signal {
+                    address: *address,
+                    direction: entry.direction,
+                    kind: SignalKind::Entry {
+                        pdo: entry.pdo,
+                        index: entry.entry.index,
+                        sub_index: entry.entry.sub_index,
+                        name: entry.entry.name,
+                    },
+                    logical_bit,
+                    bit_len: entry.entry.bit_len,
+                });
+            }
+        }
+
+        signals
+    }
+
+    /// A signal for an arbitrary bit range in the logical process image.
+    ///
+    /// Returns `None` if the range isn't completely covered by a single FMMU.
+    pub fn range_signal(&self, logical_bit: u64, bit_len: u8) -> Option<Signal> {
+        if bit_len == 0 || bit_len > MAX_VALUE_BITS {
+            return None;
+        }
+
+        let first = logical_bit / 8;
+        let last = (logical_bit + u64::from(bit_len) - 1) / 8;
+
+        self.mapping
+            .subdevices
+            .iter()
+            .flat_map(|(address, subdevice)| {
+                subdevice.fmmus.values().map(move |fmmu| (*address, fmmu))
+            })
+            .filter(|(_, fmmu)| fmmu.enabled)
+            .find_map(|(address, fmmu)| {
+                let start = u64::from(fmmu.logical_start);
+                let end = start + u64::from(fmmu.length);
+
+                if first < start || last >= end {
+                    return None;
+                }
+
+                let direction = match fmmu.fmmu_type {
+                    FmmuType::Write => ProcessDataDirection::Outputs,
+                    FmmuType::Read | FmmuType::ReadWrite => ProcessDataDirection::Inputs,
+                    _ => return None,
+                };
+
+                Some(Signal {
+                    address,
+                    direction,
+                    kind: SignalKind::Range,
+                    logical_bit,
+                    bit_len,
+                })
+            })
+    }
+
+    /// Time series of every signal in the process image.
+    pub fn series(&self) -> BTreeMap<Signal, TimeSeries> {
+        self.series_of(self.signals())
+    }
+
+    /// Time series of the given signals.
+    pub fn series_of(&self, signals: Vec<Signal>) -> BTreeMap<Signal, TimeSeries> {
+        let mut series = vec![TimeSeries::default(); signals.len()];
+
+        for frame in self.frames.iter() {
+            for (signal, series) in signals.iter().zip(series.iter_mut()) {
+                if let Some(value) =
+                    frame.value(signal.direction, signal.logical_bit, signal.bit_len)
+                {
+                    series.push(Sample {
+                        time: frame.time,
+                        packet_number: frame.packet_number,
+                        cycle: frame.cycle,
+                        value,
+                    });
+                }
+            }
+        }
+
+        signals.into_iter().zip(series).collect()
+    }
+}
+
+/// A single change in value of a signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeEvent {
+    pub signal: Signal,
+
+    /// Value before the change.
+    pub previous: u64,
+
+    /// Sample holding the new value.
+    pub sample: Sample,
+}
+
+/// Every change in value of the given signals, in capture order.
+///
+/// The first value of each signal is not counted as a change.
+pub fn change_events(series: &BTreeMap<Signal, TimeSeries>) -> Vec<ChangeEvent> {
+    let mut events = series
+        .iter()
+        .flat_map(|(signal, series)| {
+            series.samples.windows(2).map(|pair| {
+                let [previous, sample] = pair else {
+                    unreachable!()
+                };
+
+                ChangeEvent {
+                    signal: signal.clone(),
+                    previous: previous.value,
+                    sample: *sample,
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    events.sort_by_key(|event| event.sample.packet_number);
+
+    events
+}
@@ -0,0 +1,281 @@
+//! Last known value of every ESC register of every SubDevice.
+//!
+//! Values are tracked per byte from register reads and writes that reached the SubDevice, i.e. had
+//! a non-zero working counter. Auto increment accesses are attributed to the SubDevice's configured
+//! station address once it's known. Broadcast writes update every SubDevice found so far, while
+//! broadcast reads are ignored as they hold the logical OR of every SubDevice's value.
+
+use crate::{
+    command::{self, Access, Addressing},
+    exchange::PduExchange,
+    registers::split_range,
+    topology::Topology,
+};
+use std::{collections::BTreeMap, time::Duration};
+
+/// How a SubDevice was addressed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Station {
+    /// Configured station address.
+    Address(u16),
+
+    /// Auto increment position, used until the SubDevice's configured address is known.
+    Position(u16),
+}
+
+impl std::fmt::Display for Station {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Address(address) => f.pad(&format!("{:#06x}", address)),
+            Self::Position(position) => f.pad(&format!("#{}", position)),
+        }
+    }
+}
+
+/// A single byte of register memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KnownByte {
+    pub value: u8,
+
+    /// Whether the value was last written by the MainDevice rather than read from the SubDevice.
+    pub written: bool,
+
+    /// Wireshark packet number of the request that last touched this byte.
+    pub packet_number: usize,
+
+    /// Time of the request that last touched this byte, relative to the start of the capture.
+    pub time: Duration,
+}
+
+/// The last known value of a single register, or run of bytes outside the register catalogue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterSnapshot {
+    pub station: Station,
+
+    /// Register address of the first byte.
+    pub address: u16,
+
+    /// Register name, empty for bytes outside the register catalogue.
+    pub name: String,
+
+    pub data: Vec<u8>,
+
+    /// Value decoded by register width and field layout.
+    pub value: String,
+
+    /// Whether the most recently updated byte was written by the MainDevice.
+    pub written: bool,
+
+    /// Packet number of the most recent access to any byte.
+    pub packet_number: usize,
+
+    /// Time of the most recent access to any byte.
+    pub time: Duration,
+}
+
+/// Register memory of every SubDevice seen in a capture.
+#[derive(Debug, Clone, Default)]
+pub struct RegisterState {
+    /// Used to attribute auto increment accesses to configured station addresses.
+    topology: Topology,
+
+    /// Known bytes of each SubDevice, keyed by register address.
+    pub stations: BTreeMap<Station, BTreeMap<u16, KnownByte>>,
+}
+
+impl RegisterState {
+    /// Collect register state from every exchange in a capture.
+    pub fn new(exchanges: impl Iterator<Item = PduExchange>) -> Self {
+        let mut self_ = Self::default();
+
+        for exchange in exchanges {
+            self_.push(&exchange);
+        }
+
+        self_
+    }
+
+    /// Update state with a single exchange.
+    pub fn push(&mut self, exchange: &PduExchange) {
+        self.topology.push(exchange);
+
+        self.merge_positions();
+
+        if exchange.response.working_counter == 0 {
+            return;
+        }
+
+        let request = &exchange.request;
+        let response = &exchange.response;
+
+        let command = &request.command;
+
+        let Some(register) = command::register(command) else {
+            return;
+        };
+
+        let (stations, data, written) =
+            match (command::addressing(command), command::access(command)) {
+                (Some(Addressing::Configured), Some(Access::Read | Access::ReadMultipleWrite)) => (
+                    vec![Station::Address(
+                        command::configured_address(command).expect("Configured"),
+                    )],
+                    &response.data,
+                    false,
+                ),
+                (Some(Addressing::Configured), Some(Access::Write | Access::ReadWrite)) => (
+                    vec![Station::Address(
+                        command::configured_address(command).expect("Configured"),
+                    )],
+                    &request.data,
+                    true,
+                ),
+                (
+                    Some(Addressing::AutoIncrement),
+                    Some(Access::Read | Access::ReadMultipleWrite),
+                ) => (
+                    vec![self.resolve(command::position(command).expect("Auto increment"))],
+                    &response.data,
+                    false,
+                ),
+                (Some(Addressing::AutoIncrement), Some(Access::Write | Access::ReadWrite)) => (
+                    vec![self.resolve(command::position(command).expect("Auto increment"))],
+                    &request.data,
+                    true,
+                ),
+                (Some(Addressing::Broadcast), Some(Access::Write | Access::ReadWrite)) => {
+                    let stations = self
+                        .topology
+                        .subdevices
+                        .values()
+                        .map(|sd| {
+                            sd.configured_address
+                                .map_or(Station::Position(sd.position), Station::Address)
+                        })
+                        .collect();
+
+                    (stations, &request.data, true)
+                }
+                _ => return,
+            };
+
+        for station in stations {
+            let memory = self.stations.entry(station).or_default();
+
+            for (offset, value) in data.iter().enumerate() {
+                memory.insert(
+                    register.wrapping_add(offset as u16),
+                    KnownByte {
+                        value: *value,
+                        written,
+                        packet_number: exchange.tx_packet_number,
+                        time: exchange.tx_time,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Find the station at an auto increment position.
+    fn resolve(&self, position: u16) -> Station {
+        self.topology
+            .subdevices
+            .get(&position)
+            .and_then(|sd| sd.configured_address)
+            .map_or(Station::Position(position), Station::Address)
+    }
+
+    /// Move any state collected by auto increment position to the SubDevice's configured station
+    /// address once it's known.
+    fn merge_positions(&mut self) {
+        let resolved = self
+            .stations
+            .keys()
+            .filter_map(|station| match station {
+                Station::Position(position) => {
+                    let address = self.topology.subdevices.get(position)?.configured_address?;
+
+                    Some((*position, address))
+                }
+                Station::Address(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        for (position, address) in resolved {
+            let Some(by_position) = self.stations.remove(&Station::Position(position)) else {
+                continue;
+            };
+
+            let memory = self.stations.entry(Station::Address(address)).or_default();
+
+            // Keep the most recent value of each byte
+            for (register, byte) in by_position {
+                memory
+                    .entry(register)
+                    .and_modify(|known| {
+                        if byte.packet_number > known.packet_number {
+                            *known = byte;
+                        }
+                    })
+                    .or_insert(byte);
+            }
+        }
+    }
+
+    /// Last known value of every register of every SubDevice, sorted by station and address.
+    ///
+    /// Each run of contiguous known bytes is split by register, so registers only partially
+    /// accessed are shown as raw bytes.
+    pub fn snapshot(&self) -> Vec<RegisterSnapshot> {
+        let mut out = Vec::new();
+
+        for (station, memory) in self.stations.iter() {
+            let mut runs: Vec<(u16, Vec<&KnownByte>)> = Vec::new();
+
+            for (register, byte) in memory.iter() {
+                match runs.last_mut() {
+                    Some((start, bytes))
+                        if u32::from(*start) + bytes.len() as u32 == u32::from(*register) =>
+                    {
+                        bytes.push(byte)
+                    }
+                    _ => runs.push((*register, vec![byte])),
+                }
+            }
+
+            for (start, bytes) in runs {
+                let data = bytes.iter().map(|byte| byte.value).collect::<Vec<_>>();
+
+                let mut offset = 0;
+
+                for chunk in split_range(start, &data) {
+                    let touched = &bytes[offset..offset + chunk.data.len()];
+
+                    let latest = touched
+                        .iter()
+                        .max_by_key(|byte| byte.packet_number)
+                        .expect("Empty chunk");
+
+                    out.push(RegisterSnapshot {
+                        station: *station,
+                        address: chunk.address,
+                        name: chunk
+                            .register
+                            .as_ref()
+                            .map(|register| register.name.to_string())
+                            .unwrap_or_default(),
+                        data: chunk.data.to_vec(),
+                        value: chunk.value(),
+                        written: latest.written,
+                        packet_number: latest.packet_number,
+                        time: latest.time,
+                    });
+
+                    offset += chunk.data.len();
+                }
+            }
+        }
+
+        out
+    }
+}
@@ -0,0 +1,638 @@
+//! Catalogue of ESC registers, ETG1000.4 section 6, used to name and decode register accesses.
+
+use std::borrow::Cow;
+
+/// Base address of the FMMU registers, 16 bytes per FMMU.
+const FMMU_BASE: u16 = 0x0600;
+
+/// Size of a single FMMU's registers.
+const FMMU_SIZE: u16 = 16;
+
+/// Base address of the SyncManager registers, 8 bytes per SyncManager.
+const SM_BASE: u16 = 0x0800;
+
+/// Size of a single SyncManager's registers.
+const SM_SIZE: u16 = 8;
+
+/// Start of process data RAM.
+const PROCESS_DATA_RAM: u16 = 0x1000;
+
+/// How the value of a register field is shown.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FieldFormat {
+    /// Single bit, shown by name only when set.
+    Flag,
+
+    /// Unsigned decimal number.
+    Decimal,
+
+    /// Hexadecimal number.
+    Hex,
+
+    /// One of a list of named values.
+    Enum(&'static [(u64, &'static str)]),
+
+    /// One of a list of named values, prefixed with the field name.
+    Setting(&'static [(u64, &'static str)]),
+}
+
+/// A bit field inside a register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+
+    /// Position of the least significant bit of this field in the register.
+    pub bit: u8,
+
+    /// Width of the field in bits.
+    pub width: u8,
+
+    pub format: FieldFormat,
+}
+
+impl Field {
+    const fn new(name: &'static str, bit: u8, width: u8, format: FieldFormat) -> Self {
+        Self {
+            name,
+            bit,
+            width,
+            format,
+        }
+    }
+
+    const fn flag(name: &'static str, bit: u8) -> Self {
+        Self::new(name, bit, 1, FieldFormat::Flag)
+    }
+
+    /// Extract this field from a register value.
+    pub fn value(&self, register: u128) -> u64 {
+        let mask = if self.width >= 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.width) - 1
+        };
+
+        (register >> self.bit) as u64 & mask
+    }
+
+    /// Describe this field's value, or `None` for flags that aren't set.
+    pub fn describe(&self, register: u128) -> Option<String> {
+        let value = self.value(register);
+
+        match self.format {
+            FieldFormat::Flag => (value != 0).then(|| self.name.to_string()),
+            FieldFormat::Decimal => Some(format!("{} {}", self.name, value)),
+            FieldFormat::Hex => Some(format!("{} {:#06x}", self.name, value)),
+            FieldFormat::Enum(values) => Some(
+                values
+                    .iter()
+                    .find(|(v, _)| *v == value)
+                    .map(|(_, text)| text.to_string())
+                    .unwrap_or_else(|| format!("{} unknown ({:#x})", self.name, value)),
+            ),
+            FieldFormat::Setting(values) => Some(
+                values
+                    .iter()
+                    .find(|(v, _)| *v == value)
+                    .map(|(_, text)| format!("{} {}", self.name, text))
+                    .unwrap_or_else(|| format!("{} unknown ({:#x})", self.name, value)),
+            ),
+        }
+    }
+}
+
+/// A single ESC register, or a group of registers with a common layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Register {
+    pub address: u16,
+
+    /// Size in bytes.
+    pub size: u16,
+
+    pub name: Cow<'static, str>,
+
+    /// Bit fields, empty if the register holds a single number.
+    pub fields: &'static [Field],
+}
+
+impl Register {
+    const fn new(address: u16, size: u16, name: &'static str, fields: &'static [Field]) -> Self {
+        Self {
+            address,
+            size,
+            name: Cow::Borrowed(name),
+            fields,
+        }
+    }
+
+    /// Whether a register address falls inside this register.
+    pub fn contains(&self, address: u16) -> bool {
+        address >= self.address
+            && u32::from(address) < u32::from(self.address) + u32::from(self.size)
+    }
+
+    /// Decode a complete value of this register, e.g. `OP, no error` for AL status.
+    ///
+    /// Registers without fields are shown as a number if they're at most 8 bytes long, and as raw
+    /// bytes otherwise.
+    pub fn describe(&self, data: &[u8]) -> String {
+        if data.len() > 16 || (self.fields.is_empty() && data.len() > 8) {
+            return format!("{:02x?}", data);
+        }
+
+        let mut raw = [0u8; 16];
+
+        raw[0..data.len()].copy_from_slice(data);
+
+        let value = u128::from_le_bytes(raw);
+
+        if self.fields.is_empty() {
+            return value.to_string();
+        }
+
+        let fields = self
+            .fields
+            .iter()
+            .filter_map(|field| field.describe(value))
+            .collect::<Vec<_>>();
+
+        if fields.is_empty() {
+            "(none)".to_string()
+        } else {
+            fields.join(", ")
+        }
+    }
+}
+
+impl std::fmt::Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({:#06x})", self.name, self.address)
+    }
+}
+
+const AL_STATES: &[(u64, &str)] = &[
+    (0x01, "INIT"),
+    (0x02, "PRE-OP"),
+    (0x03, "BOOT"),
+    (0x04, "SAFE-OP"),
+    (0x08, "OP"),
+];
+
+/// AL status codes, ETG1000.6 Table 11.
+const AL_STATUS_CODES: &[(u64, &str)] = &[
+    (0x0000, "No error"),
+    (0x0001, "Unspecified error"),
+    (0x0002, "No memory"),
+    (0x0003, "Invalid device setup"),
+    (0x0011, "Invalid requested state change"),
+    (0x0012, "Unknown requested state"),
+    (0x0013, "Bootstrap not supported"),
+    (0x0014, "No valid firmware"),
+    (0x0015, "Invalid mailbox configuration (BOOT)"),
+    (0x0016, "Invalid mailbox configuration (PRE-OP)"),
+    (0x0017, "Invalid sync manager configuration"),
+    (0x0018, "No valid inputs available"),
+    (0x0019, "No valid outputs"),
+    (0x001A, "Synchronization error"),
+    (0x001B, "Sync manager watchdog"),
+    (0x001C, "Invalid sync manager types"),
+    (0x001D, "Invalid output configuration"),
+    (0x001E, "Invalid input configuration"),
+    (0x001F, "Invalid watchdog configuration"),
+    (0x0020, "SubDevice needs cold start"),
+    (0x0021, "SubDevice needs INIT"),
+    (0x0022, "SubDevice needs PRE-OP"),
+    (0x0023, "SubDevice needs SAFE-OP"),
+    (0x0024, "Invalid input mapping"),
+    (0x0025, "Invalid output mapping"),
+    (0x0026, "Inconsistent settings"),
+    (0x0027, "Free-run not supported"),
+    (0x0028, "Synchronization not supported"),
+    (0x0029, "Free-run needs 3 buffer mode"),
+    (0x002A, "Background watchdog"),
+    (0x002B, "No valid inputs and outputs"),
+    (0x002C, "Fatal sync error"),
+    (0x002D, "No sync error"),
+    (0x0030, "Invalid DC SYNC configuration"),
+    (0x0031, "Invalid DC latch configuration"),
+    (0x0032, "PLL error"),
+    (0x0033, "DC sync IO error"),
+    (0x0034, "DC sync timeout error"),
+    (0x0035, "DC invalid sync cycle time"),
+    (0x0036, "DC SYNC0 cycle time"),
+    (0x0037, "DC SYNC1 cycle time"),
+    (0x0041, "MBX_AOE"),
+    (0x0042, "MBX_EOE"),
+    (0x0043, "MBX_COE"),
+    (0x0044, "MBX_FOE"),
+    (0x0045, "MBX_SOE"),
+    (0x004F, "MBX_VOE"),
+    (0x0050, "EEPROM no access"),
+    (0x0051, "EEPROM error"),
+    (0x0060, "SubDevice restarted locally"),
+    (0x0061, "Device identification value updated"),
+    (0x00F0, "Application controller available"),
+];
+
+const LOOP_CONTROL: &[(u64, &str)] = &[
+    (0, "auto"),
+    (1, "auto close"),
+    (2, "always open"),
+    (3, "always closed"),
+];
+
+const DL_CONTROL: &[Field] = &[
+    Field::flag("drop non-EtherCAT frames", 0),
+    Field::flag("temporary loop control", 1),
+    Field::new("port 0 loop", 8, 2, FieldFormat::Setting(LOOP_CONTROL)),
+    Field::new("port 1 loop", 10, 2, FieldFormat::Setting(LOOP_CONTROL)),
+    Field::new("port 2 loop", 12, 2, FieldFormat::Setting(LOOP_CONTROL)),
+    Field::new("port 3 loop", 14, 2, FieldFormat::Setting(LOOP_CONTROL)),
+];
+
+const DL_STATUS: &[Field] = &[
+    Field::flag("PDI operational", 0),
+    Field::flag("PDI watchdog OK", 1),
+    Field::flag("enhanced link detection", 2),
+    Field::flag("port 0 link", 4),
+    Field::flag("port 1 link", 5),
+    Field::flag("port 2 link", 6),
+    Field::flag("port 3 link", 7),
+    Field::flag("port 0 loop closed", 8),
+    Field::flag("port 0 signal", 9),
+    Field::flag("port 1 loop closed", 10),
+    Field::flag("port 1 signal", 11),
+    Field::flag("port 2 loop closed", 12),
+    Field::flag("port 2 signal", 13),
+    Field::flag("port 3 loop closed", 14),
+    Field::flag("port 3 signal", 15),
+];
+
+const AL_CONTROL: &[Field] = &[
+    Field::new("state", 0, 4, FieldFormat::Enum(AL_STATES)),
+    Field::flag("error acknowledge", 4),
+    Field::flag("request device identification", 5),
+];
+
+const AL_STATUS: &[Field] = &[
+    Field::new("state", 0, 4, FieldFormat::Enum(AL_STATES)),
+    Field::new(
+        "error",
+        4,
+        1,
+        FieldFormat::Enum(&[(0, "no error"), (1, "error")]),
+    ),
+    Field::flag("device identification loaded", 5),
+];
+
+const AL_STATUS_CODE: &[Field] = &[Field::new(
+    "code",
+    0,
+    16,
+    FieldFormat::Enum(AL_STATUS_CODES),
+)];
+
+const RX_ERROR_COUNTER: &[Field] = &[
+    Field::new("invalid frames", 0, 8, FieldFormat::Decimal),
+    Field::new("RX errors", 8, 8, FieldFormat::Decimal),
+];
+
+const WATCHDOG_STATUS: &[Field] = &[Field::new(
+    "watchdog",
+    0,
+    1,
+    FieldFormat::Enum(&[(0, "expired"), (1, "active or disabled")]),
+)];
+
+const SII_CONTROL: &[Field] = &[
+    Field::flag("write enable", 0),
+    Field::flag("emulated", 5),
+    Field::new(
+        "read size",
+        6,
+        1,
+        FieldFormat::Enum(&[(0, "4 byte reads"), (1, "8 byte reads")]),
+    ),
+    Field::new(
+        "address algorithm",
+        7,
+        1,
+        FieldFormat::Enum(&[(0, "1 byte address"), (1, "2 byte address")]),
+    ),
+    Field::new(
+        "command",
+        8,
+        3,
+        FieldFormat::Enum(&[(0, "idle"), (1, "read"), (2, "write"), (4, "reload")]),
+    ),
+    Field::flag("checksum error", 11),
+    Field::flag("device info error", 12),
+    Field::flag("command error", 13),
+    Field::flag("write error", 14),
+    Field::flag("busy", 15),
+];
+
+const SYNC_ACTIVATION: &[Field] = &[
+    Field::flag("cyclic operation", 0),
+    Field::flag("SYNC0", 1),
+    Field::flag("SYNC1", 2),
+];
+
+/// Layout of a single FMMU, ETG1000.4 Table 57.
+const FMMU: &[Field] = &[
+    Field::new("logical start", 0, 32, FieldFormat::Hex),
+    Field::new("length", 32, 16, FieldFormat::Decimal),
+    Field::new("logical start bit", 48, 3, FieldFormat::Decimal),
+    Field::new("logical end bit", 56, 3, FieldFormat::Decimal),
+    Field::new("physical start", 64, 16, FieldFormat::Hex),
+    Field::new("physical start bit", 80, 3, FieldFormat::Decimal),
+    Field::new(
+        "type",
+        88,
+        2,
+        FieldFormat::Setting(&[(0, "unused"), (1, "read"), (2, "write"), (3, "read/write")]),
+    ),
+    Field::flag("enabled", 96),
+];
+
+/// Layout of a single SyncManager, ETG1000.4 Table 59.
+const SYNC_MANAGER: &[Field] = &[
+    Field::new("start", 0, 16, FieldFormat::Hex),
+    Field::new("length", 16, 16, FieldFormat::Decimal),
+    Field::new(
+        "mode",
+        32,
+        2,
+        FieldFormat::Enum(&[(0, "buffered"), (2, "mailbox")]),
+    ),
+    Field::new(
+        "direction",
+        34,
+        2,
+        FieldFormat::Enum(&[(0, "read by MainDevice"), (1, "written by MainDevice")]),
+    ),
+    Field::flag("ECAT event", 36),
+    Field::flag("PDI event", 37),
+    Field::flag("watchdog", 38),
+    Field::flag("mailbox full", 43),
+    Field::flag("enabled", 48),
+    Field::flag("repeat", 49),
+    Field::flag("PDI disabled", 56),
+];
+
+/// Every fixed register, sorted by address. FMMU, SyncManager and process data RAM are handled by
+/// [`lookup`].
+static REGISTERS: &[Register] = &[
+    Register::new(0x0000, 1, "Type", &[]),
+    Register::new(0x0001, 1, "Revision", &[]),
+    Register::new(0x0002, 2, "Build", &[]),
+    Register::new(0x0004, 1, "FMMUs supported", &[]),
+    Register::new(0x0005, 1, "SyncManagers supported", &[]),
+    Register::new(0x0006, 1, "RAM size", &[]),
+    Register::new(0x0007, 1, "Port descriptor", &[]),
+    Register::new(0x0008, 2, "ESC features supported", &[]),
+    Register::new(0x0010, 2, "Configured station address", &[]),
+    Register::new(0x0012, 2, "Configured station alias", &[]),
+    Register::new(0x0020, 1, "Register write enable", &[]),
+    Register::new(0x0021, 1, "Register write protection", &[]),
+    Register::new(0x0030, 1, "ESC write enable", &[]),
+    Register::new(0x0031, 1, "ESC write protection", &[]),
+    Register::new(0x0040, 1, "ESC reset ECAT", &[]),
+    Register::new(0x0041, 1, "ESC reset PDI", &[]),
+    Register::new(0x0100, 4, "DL control", DL_CONTROL),
+    Register::new(0x0108, 2, "Physical read/write offset", &[]),
+    Register::new(0x0110, 2, "DL status", DL_STATUS),
+    Register::new(0x0120, 2, "AL control", AL_CONTROL),
+    Register::new(0x0130, 2, "AL status", AL_STATUS),
+    Register::new(0x0134, 2, "AL status code", AL_STATUS_CODE),
+    Register::new(0x0138, 1, "RUN LED override", &[]),
+    Register::new(0x0139, 1, "ERR LED override", &[]),
+    Register::new(0x0140, 1, "PDI control", &[]),
+    Register::new(0x0141, 1, "ESC configuration", &[]),
+    Register::new(0x014e, 2, "PDI information", &[]),
+    Register::new(0x0150, 4, "PDI configuration", &[]),
+    Register::new(0x0200, 2, "ECAT event mask", &[]),
+    Register::new(0x0204, 4, "AL event mask", &[]),
+    Register::new(0x0210, 2, "ECAT event request", &[]),
+    Register::new(0x0220, 4, "AL event request", &[]),
+    Register::new(0x0300, 2, "RX error counter port 0", RX_ERROR_COUNTER),
+    Register::new(0x0302, 2, "RX error counter port 1", RX_ERROR_COUNTER),
+    Register::new(0x0304, 2, "RX error counter port 2", RX_ERROR_COUNTER),
+    Register::new(0x0306, 2, "RX error counter port 3", RX_ERROR_COUNTER),
+    Register::new(0x0308, 1, "Forwarded RX error counter port 0", &[]),
+    Register::new(0x0309, 1, "Forwarded RX error counter port 1", &[]),
+    Register::new(0x030a, 1, "Forwarded RX error counter port 2", &[]),
+    Register::new(0x030b, 1, "Forwarded RX error counter port 3", &[]),
+    Register::new(0x030c, 1, "ECAT processing unit error counter", &[]),
+    Register::new(0x030d, 1, "PDI error counter", &[]),
+    Register::new(0x030e, 1, "PDI error code", &[]),
+    Register::new(0x0310, 1, "Lost link counter port 0", &[]),
+    Register::new(0x0311, 1, "Lost link counter port 1", &[]),
+    Register::new(0x0312, 1, "Lost link counter port 2", &[]),
+    Register::new(0x0313, 1, "Lost link counter port 3", &[]),
+    Register::new(0x0400, 2, "Watchdog divider", &[]),
+    Register::new(0x0410, 2, "Watchdog time PDI", &[]),
+    Register::new(0x0420, 2, "Watchdog time process data", &[]),
+    Register::new(0x0440, 2, "Watchdog status process data", WATCHDOG_STATUS),
+    Register::new(0x0442, 1, "Watchdog counter process data", &[]),
+    Register::new(0x0443, 1, "Watchdog counter PDI", &[]),
+    Register::new(0x0500, 1, "SII configuration", &[]),
+    Register::new(0x0501, 1, "SII PDI access state", &[]),
+    Register::new(0x0502, 2, "SII control/status", SII_CONTROL),
+    Register::new(0x0504, 4, "SII address", &[]),
+    Register::new(0x0508, 8, "SII data", &[]),
+    Register::new(0x0510, 2, "MII management control/status", &[]),
+    Register::new(0x0512, 1, "PHY address", &[]),
+    Register::new(0x0513, 1, "PHY register address", &[]),
+    Register::new(0x0514, 2, "PHY data", &[]),
+    Register::new(0x0516, 1, "MII management ECAT access state", &[]),
+    Register::new(0x0517, 1, "MII management PDI access state", &[]),
+    Register::new(0x0900, 4, "Receive time port 0", &[]),
+    Register::new(0x0904, 4, "Receive time port 1", &[]),
+    Register::new(0x0908, 4, "Receive time port 2", &[]),
+    Register::new(0x090c, 4, "Receive time port 3", &[]),
+    Register::new(0x0910, 8, "System time", &[]),
+    Register::new(0x0918, 8, "Receive time ECAT processing unit", &[]),
+    Register::new(0x0920, 8, "System time offset", &[]),
+    Register::new(0x0928, 4, "System time delay", &[]),
+    Register::new(0x092c, 4, "System time difference", &[]),
+    Register::new(0x0930, 2, "Speed counter start", &[]),
+    Register::new(0x0932, 2, "Speed counter difference", &[]),
+    Register::new(0x0934, 1, "System time difference filter depth", &[]),
+    Register::new(0x0935, 1, "Speed counter filter depth", &[]),
+    Register::new(0x0980, 1, "Cyclic unit control", &[]),
+    Register::new(0x0981, 1, "Sync activation", SYNC_ACTIVATION),
+    Register::new(0x0982, 2, "Pulse length of SYNC signals", &[]),
+    Register::new(0x0984, 1, "Activation status", &[]),
+    Register::new(0x098e, 1, "SYNC0 status", &[]),
+    Register::new(0x098f, 1, "SYNC1 status", &[]),
+    Register::new(0x0990, 8, "Start time cyclic operation", &[]),
+    Register::new(0x0998, 8, "Next SYNC1 pulse", &[]),
+    Register::new(0x09a0, 4, "SYNC0 cycle time", &[]),
+    Register::new(0x09a4, 4, "SYNC1 cycle time", &[]),
+    Register::new(0x09a8, 1, "Latch0 control", &[]),
+    Register::new(0x09a9, 1, "Latch1 control", &[]),
+    Register::new(0x09ae, 1, "Latch0 status", &[]),
+    Register::new(0x09af, 1, "Latch1 status", &[]),
+    Register::new(0x09b0, 8, "Latch0 time positive edge", &[]),
+    Register::new(0x09b8, 8, "Latch0 time negative edge", &[]),
+    Register::new(0x09c0, 8, "Latch1 time positive edge", &[]),
+    Register::new(0x09c8, 8, "Latch1 time negative edge", &[]),
+    Register::new(0x0f00, 4, "Digital I/O output data", &[]),
+    Register::new(0x0f10, 8, "General purpose outputs", &[]),
+    Register::new(0x0f18, 8, "General purpose inputs", &[]),
+    Register::new(0x0f80, 128, "User RAM", &[]),
+];
+
+/// Find the register containing a register address.
+pub fn lookup(address: u16) -> Option<Register> {
+    match address {
+        FMMU_BASE..SM_BASE => {
+            let n = (address - FMMU_BASE) / FMMU_SIZE;
+
+            Some(Register {
+                address: FMMU_BASE + n * FMMU_SIZE,
+                size: FMMU_SIZE,
+                name: Cow::Owned(format!("FMMU {}", n)),
+                fields: FMMU,
+            })
+        }
+        SM_BASE..0x0880 => {
+            let n = (address - SM_BASE) / SM_SIZE;
+
+            Some(Register {
+                address: SM_BASE + n * SM_SIZE,
+                size: SM_SIZE,
+                name: Cow::Owned(format!("SyncManager {}", n)),
+                fields: SYNC_MANAGER,
+            })
+        }
+        PROCESS_DATA_RAM.. => Some(Register {
+            address: PROCESS_DATA_RAM,
+            size: u16::MAX - PROCESS_DATA_RAM + 1,
+            name: Cow::Borrowed("Process data RAM"),
+            fields: &[],
+        }),
+        _ => REGISTERS
+            .iter()
+            .find(|register| register.contains(address))
+            .cloned(),
+    }
+}
+
+/// Name a register address, e.g. `AL status (0x0130)`.
+///
+/// Addresses inside a register are given as an offset from its start, and unknown addresses are
+/// shown in hex only.
+pub fn name(address: u16) -> String {
+    match lookup(address) {
+        Some(register) if register.address == address => register.to_string(),
+        Some(register) => format!(
+            "{} + {} ({:#06x})",
+            register.name,
+            address - register.address,
+            address
+        ),
+        None => format!("{:#06x}", address),
+    }
+}
+
+/// Part of a register access covering a single register, or a run of bytes outside any decoded
+/// register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk<'a> {
+    /// Register address of the first byte of this chunk.
+    pub address: u16,
+
+    /// Register containing this chunk, or `None` for gaps between registers and process data RAM.
+    pub register: Option<Register>,
+
+    pub data: &'a [u8],
+}
+
+impl Chunk<'_> {
+    /// Whether this chunk holds the whole of its register.
+    pub fn is_complete(&self) -> bool {
+        self.register.as_ref().is_some_and(|register| {
+            register.address == self.address && self.data.len() == usize::from(register.size)
+        })
+    }
+
+    /// Whether a register address falls inside this chunk.
+    pub fn contains(&self, address: u16) -> bool {
+        address >= self.address
+            && u32::from(address) < u32::from(self.address) + self.data.len() as u32
+    }
+
+    /// Decode this chunk's value, or show it as raw bytes if it only covers part of a register.
+    pub fn value(&self) -> String {
+        match &self.register {
+            Some(register) if self.is_complete() => register.describe(self.data),
+            _ => format!("{:02x?}", self.data),
+        }
+    }
+}
+
+impl std::fmt::Display for Chunk<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", name(self.address), self.value())
+    }
+}
+
+/// Split the data of a register access starting at `start` into one chunk per register touched.
+///
+/// Process data RAM and addresses outside the catalogue are returned as a single chunk per run of
+/// bytes.
+pub fn split_range(start: u16, data: &[u8]) -> Vec<Chunk<'_>> {
+    let decoded = |offset: usize| {
+        lookup(start.wrapping_add(offset as u16)).filter(|r| r.address < PROCESS_DATA_RAM)
+    };
+
+    let mut out = Vec::new();
+    let mut offset = 0usize;
+
+    while offset < data.len() {
+        let address = start.wrapping_add(offset as u16);
+
+        let Some(register) = decoded(offset) else {
+            let end = (offset + 1..data.len())
+                .find(|o| decoded(*o).is_some())
+                .unwrap_or(data.len());
+
+            out.push(Chunk {
+                address,
+                register: None,
+                data: &data[offset..end],
+            });
+
+            offset = end;
+
+            continue;
+        };
+
+        let len = (usize::from(register.address) + usize::from(register.size)
+            - usize::from(address))
+        .min(data.len() - offset);
+
+        out.push(Chunk {
+            address,
+            register: Some(register),
+            data: &data[offset..offset + len],
+        });
+
+        offset += len;
+    }
+
+    out
+}
+
+/// Describe the data of a register access starting at `start`, one entry per register touched.
+///
+/// Registers only partially covered by the access are shown as raw bytes. Accesses to process data
+/// RAM are not decoded.
+pub fn describe_range(start: u16, data: &[u8]) -> Vec<String> {
+    split_range(start, data)
+        .iter()
+        .map(|chunk| chunk.to_string())
+        .collect()
+}
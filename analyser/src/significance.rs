@@ -0,0 +1,396 @@
+//! Statistical significance of differences between two captures' times.
+//!
+//! The Mann-Whitney U test checks whether times from one capture tend to be larger than the
+//! other's, while the two sample Kolmogorov-Smirnov test checks for any difference in
+//! distribution, e.g. a longer tail with the same median. Neither assumes times are normally
+//! distributed, which latency rarely is. Both use large sample approximations of their p-values,
+//! which are accurate for the thousands of PDUs in a typical capture.
+//!
+//! Bootstrap confidence intervals give a range for how much a percentile like P99 differs between
+//! captures.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use statrs::{
+    distribution::{ContinuousCDF, Normal},
+    statistics::{Data, OrderStatistics},
+};
+
+/// Result of a hypothesis test.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TestResult {
+    /// Test statistic: `U` of the first sample for Mann-Whitney, `D` for Kolmogorov-Smirnov.
+    pub statistic: f64,
+
+    /// Two sided p-value. Small values mean the difference is unlikely to be down to chance.
+    pub p_value: f64,
+}
+
+/// A bootstrapped estimate with its confidence interval.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConfidenceInterval {
+    /// Estimate from the original samples.
+    pub estimate: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl ConfidenceInterval {
+    /// Whether the interval excludes zero, i.e. a difference is significant at the interval's
+    /// confidence level.
+    pub fn excludes_zero(&self) -> bool {
+        self.lower > 0.0 || self.upper < 0.0
+    }
+}
+
+/// Percentile bootstrap settings.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bootstrap {
+    /// Number of resamples. More resamples give more stable intervals, but each one takes time
+    /// proportional to the number of samples.
+    pub resamples: usize,
+
+    /// Confidence level from 0 to 1, e.g. `0.95`.
+    pub confidence: f64,
+
+    /// Random number generator seed, so repeated runs give identical intervals.
+    pub seed: u64,
+}
+
+impl Default for Bootstrap {
+    fn default() -> Self {
+        Self {
+            resamples: 1000,
+            confidence: 0.95,
+            seed: 0,
+        }
+    }
+}
+
+impl Bootstrap {
+    /// Confidence interval of a single percentile from 0 to 100.
+    pub fn percentile(&self, values: &[f64], percentile: f64) -> Option<ConfidenceInterval> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut scratch = Vec::with_capacity(values.len());
+
+        let estimates = (0..self.resamples)
+            .map(|_| resampled_percentile(&mut rng, values, percentile, &mut scratch))
+            .collect::<Vec<_>>();
+
+        Some(self.interval(quantile(values, percentile / 100.0), estimates))
+    }
+
+    /// Confidence interval of the difference of a percentile from 0 to 100, `other` minus
+    /// `reference`.
+    pub fn percentile_difference(
+        &self,
+        reference: &[f64],
+        other: &[f64],
+        percentile: f64,
+    ) -> Option<ConfidenceInterval> {
+        if reference.is_empty() || other.is_empty() {
+            return None;
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut scratch = Vec::with_capacity(reference.len().max(other.len()));
+
+        let estimates = (0..self.resamples)
+            .map(|_| {
+                let reference = resampled_percentile(&mut rng, reference, percentile, &mut scratch);
+                let other = resampled_percentile(&mut rng, other, percentile, &mut scratch);
+
+                other - reference
+            })
+            .collect::<Vec<_>>();
+
+        let estimate =
+            quantile(other, percentile / 100.0) - quantile(reference, percentile / 100.0);
+
+        Some(self.interval(estimate, estimates))
+    }
+
+    fn interval(&self, estimate: f64, estimates: Vec<f64>) -> ConfidenceInterval {
+        let mut estimates = Data::new(estimates);
+        let tail = (1.0 - self.confidence) / 2.0;
+
+        ConfidenceInterval {
+            estimate,
+            lower: estimates.quantile(tail),
+            upper: estimates.quantile(1.0 - tail),
+        }
+    }
+}
+
+/// Quantile using the same definition as [`DumpFileStats`](crate::stats::DumpFileStats).
+fn quantile(values: &[f64], tau: f64) -> f64 {
+    Data::new(values.to_vec()).quantile(tau)
+}
+
+/// Percentile of a resample with replacement of `values`.
+fn resampled_percentile(
+    rng: &mut StdRng,
+    values: &[f64],
+    percentile: f64,
+    scratch: &mut Vec<f64>,
+) -> f64 {
+    scratch.clear();
+    scratch.extend((0..values.len()).map(|_| values[rng.gen_range(0..values.len())]));
+
+    Data::new(scratch.as_mut_slice()).quantile(percentile / 100.0)
+}
+
+/// Two sided Mann-Whitney U test, using the normal approximation with tie and continuity
+/// correction.
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> Option<TestResult> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    let n1 = a.len() as f64;
+    let n2 = b.len() as f64;
+    let n = n1 + n2;
+
+    let mut all = a
+        .iter()
+        .map(|value| (*value, true))
+        .chain(b.iter().map(|value| (*value, false)))
+        .collect::<Vec<_>>();
+
+    all.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    // Sum of ranks of the first sample, giving tied values their average rank
+    let mut rank_sum = 0.0;
+    let mut tie_term = 0.0;
+    let mut start = 0;
+
+    while start < all.len() {
+        let end = start
+            + all[start..]
+                .iter()
+                .take_while(|(value, _)| *value == all[start].0)
+                .count();
+
+        let ties = (end - start) as f64;
+        let rank = (start + end + 1) as f64 / 2.0;
+
+        rank_sum += rank * all[start..end].iter().filter(|(_, from_a)| *from_a).count() as f64;
+        tie_term += ties.powi(3) - ties;
+
+        start = end;
+    }
+
+    let u = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let std_dev = (n1 * n2 / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)))).sqrt();
+
+    let p_value = if std_dev > 0.0 {
+        let z = ((u - mean).abs() - 0.5).max(0.0) / std_dev;
+
+        (2.0 * (1.0 - standard_normal().cdf(z))).min(1.0)
+    } else {
+        1.0
+    };
+
+    Some(TestResult {
+        statistic: u,
+        p_value,
+    })
+}
+
+/// Two sample Kolmogorov-Smirnov test, using the asymptotic Kolmogorov distribution.
+pub fn kolmogorov_smirnov(a: &[f64], b: &[f64]) -> Option<TestResult> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+
+    a.sort_by(f64::total_cmp);
+    b.sort_by(f64::total_cmp);
+
+    let n1 = a.len() as f64;
+    let n2 = b.len() as f64;
+
+    // Largest distance between the two empirical CDFs
+    let mut d = 0.0f64;
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        let value = a[i].min(b[j]);
+
+        while i < a.len() && a[i] <= value {
+            i += 1;
+        }
+
+        while j < b.len() && b[j] <= value {
+            j += 1;
+        }
+
+        d = d.max((i as f64 / n1 - j as f64 / n2).abs());
+    }
+
+    let en = (n1 * n2 / (n1 + n2)).sqrt();
+    let lambda = (en + 0.12 + 0.11 / en) * d;
+
+    Some(TestResult {
+        statistic: d,
+        p_value: kolmogorov_q(lambda),
+    })
+}
+
+/// Complementary CDF of the Kolmogorov distribution.
+fn kolmogorov_q(lambda: f64) -> f64 {
+    if lambda < 0.2 {
+        return 1.0;
+    }
+
+    let mut sum = 0.0;
+
+    for j in 1..=100 {
+        let j = f64::from(j);
+        let term = (-2.0 * j * j * lambda * lambda).exp();
+
+        sum += if j % 2.0 == 1.0 { term } else { -term };
+
+        if term < 1e-12 {
+            break;
+        }
+    }
+
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+fn standard_normal() -> Normal {
+    Normal::new(0.0, 1.0).expect("Standard normal")
+}
+
+/// Every significance measure between a reference capture's times and another capture's.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Comparison {
+    pub mann_whitney_u: TestResult,
+    pub kolmogorov_smirnov: TestResult,
+
+    /// Percentile the difference is computed for, from 0 to 100.
+    pub percentile: f64,
+
+    /// Difference of the percentile, other minus reference.
+    pub percentile_difference: ConfidenceInterval,
+}
+
+impl Comparison {
+    /// Compare two captures' times, or `None` if either has no times.
+    pub fn new(
+        reference: &[f64],
+        other: &[f64],
+        percentile: f64,
+        bootstrap: &Bootstrap,
+    ) -> Option<Self> {
+        Some(Self {
+            mann_whitney_u: mann_whitney_u(reference, other)?,
+            kolmogorov_smirnov: kolmogorov_smirnov(reference, other)?,
+            percentile,
+            percentile_difference: bootstrap.percentile_difference(reference, other, percentile)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn kolmogorov_critical_values() {
+        // Critical values of the Kolmogorov distribution for 10%, 5% and 1% significance
+        for (lambda, p) in [(1.2238, 0.10), (1.3581, 0.05), (1.6276, 0.01)] {
+            assert!(
+                (kolmogorov_q(lambda) - p).abs() < 1e-4,
+                "Q({}) = {}, expected {}",
+                lambda,
+                kolmogorov_q(lambda),
+                p
+            );
+        }
+
+        assert_close(kolmogorov_q(0.5), 0.963_945_243_7);
+        assert_close(kolmogorov_q(1.0), 0.269_999_671_7);
+        assert_eq!(kolmogorov_q(0.1), 1.0);
+    }
+
+    #[test]
+    fn mann_whitney_separated() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+
+        let result = mann_whitney_u(&a, &b).expect("Result");
+
+        // z = (12.5 - 0.5) / sqrt(5 * 5 * 11 / 12)
+        assert_eq!(result.statistic, 0.0);
+        assert_close(result.p_value, 0.012_185_780_4);
+
+        // Symmetric
+        let result = mann_whitney_u(&b, &a).expect("Result");
+
+        assert_eq!(result.statistic, 25.0);
+        assert_close(result.p_value, 0.012_185_780_4);
+    }
+
+    #[test]
+    fn mann_whitney_ties() {
+        let a = [1.0, 2.0, 2.0, 3.0];
+        let b = [2.0, 3.0, 3.0, 4.0, 5.0];
+
+        let result = mann_whitney_u(&a, &b).expect("Result");
+
+        // Rank sum 13 with average ranks for the three 2s and three 3s, tie term 2 * (3^3 - 3)
+        assert_eq!(result.statistic, 3.0);
+        assert_close(result.p_value, 0.099_342_247_9);
+    }
+
+    #[test]
+    fn mann_whitney_identical() {
+        let a = [5.0; 10];
+
+        let result = mann_whitney_u(&a, &a).expect("Result");
+
+        assert_eq!(result.p_value, 1.0);
+        assert_eq!(mann_whitney_u(&a, &[]), None);
+    }
+
+    #[test]
+    fn kolmogorov_smirnov_separated() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+
+        let result = kolmogorov_smirnov(&a, &b).expect("Result");
+
+        // lambda = (sqrt(2.5) + 0.12 + 0.11 / sqrt(2.5)) * 1
+        assert_eq!(result.statistic, 1.0);
+        assert_close(result.p_value, 0.003_781_354_1);
+    }
+
+    #[test]
+    fn kolmogorov_smirnov_interleaved() {
+        let a = [1.0, 3.0, 5.0, 7.0];
+        let b = [2.0, 4.0, 6.0, 8.0];
+
+        let result = kolmogorov_smirnov(&a, &b).expect("Result");
+
+        assert_eq!(result.statistic, 0.25);
+        assert_close(result.p_value, 0.996_875_688_5);
+        assert_eq!(kolmogorov_smirnov(&[], &b), None);
+    }
+}
@@ -0,0 +1,676 @@
+//! Recover SII (EEPROM) data from the register traffic used to read and write it.
+//!
+//! This won't be a full dump - it will only be the segments that were actually accessed during
+//! the capture, but maybe that's enough to aid debugging.
+//!
+//! The SII register interface (ETG1000.4 section 6.4) is modelled per SubDevice: the MainDevice
+//! sets a word address and issues a read, write or reload command through the control register.
+//! For reads, the data register then holds 4 or 8 bytes starting at that address, depending on
+//! the read size bit in the control/status register. For writes, the word in the data register is
+//! written once the command completes without error.
+
+use crate::{
+    command::{self, Access, Addressing},
+    exchange::{register_range, PduExchange},
+};
+use ethercrab::RegisterAddress;
+use std::collections::{BTreeMap, HashMap};
+
+/// SII control/status register bits.
+const CONTROL_READ_SIZE_8: u16 = 1 << 6;
+const CONTROL_COMMAND_MASK: u16 = 0b111 << 8;
+const CONTROL_READ: u16 = 1 << 8;
+const CONTROL_WRITE: u16 = 1 << 9;
+const CONTROL_RELOAD: u16 = 1 << 10;
+const CONTROL_COMMAND_ERROR: u16 = 1 << 13;
+const CONTROL_WRITE_ERROR: u16 = 1 << 14;
+const CONTROL_BUSY: u16 = 1 << 15;
+
+/// Maximum number of bytes held in the SII data register.
+const MAX_READ_SIZE: usize = 8;
+
+/// How a SubDevice was addressed when its SII was accessed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SiiDevice {
+    /// Configured station address.
+    Configured(u16),
+
+    /// Auto increment position, for SubDevices accessed before their configured station address
+    /// was seen.
+    Position(u16),
+
+    /// Broadcast access. Only reads answered by a single SubDevice are recorded.
+    Broadcast,
+}
+
+impl std::fmt::Display for SiiDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SiiDevice::Configured(address) => write!(f, "{:#06x}", address),
+            SiiDevice::Position(position) => write!(f, "position-{}", position),
+            SiiDevice::Broadcast => f.write_str("broadcast"),
+        }
+    }
+}
+
+/// Whether an [`SiiAccess`] read from or wrote to the EEPROM.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SiiAccessKind {
+    Read,
+    Write,
+}
+
+/// A chunk of SII data read from or written to a SubDevice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiiAccess {
+    pub device: SiiDevice,
+
+    pub kind: SiiAccessKind,
+
+    /// SII address of the first word in `data`.
+    pub word_address: u16,
+
+    /// Raw data read from or written to the EEPROM.
+    pub data: Vec<u8>,
+
+    /// Wireshark packet number of the frame that completed the access.
+    pub packet_number: usize,
+}
+
+/// State of a single SubDevice's SII interface.
+#[derive(Debug, Clone, Default)]
+struct SiiState {
+    /// Word address register.
+    address: u16,
+
+    /// Data register contents last written by the MainDevice, used by write commands.
+    write_data: [u8; 2],
+
+    /// Number of bytes loaded by a read command, taken from the control/status register.
+    read_size: Option<usize>,
+
+    /// Busy flag from the last status read.
+    busy: bool,
+
+    /// Word address of the data loaded into the data register by the last read command.
+    loaded: Option<u16>,
+
+    /// A write command that hasn't been confirmed by a status read yet.
+    pending_write: Option<(u16, [u8; 2])>,
+}
+
+/// Tracks the SII interface state of each SubDevice to turn register accesses into
+/// [`SiiAccess`]es.
+#[derive(Debug, Clone, Default)]
+pub struct SiiReader {
+    states: HashMap<SiiDevice, SiiState>,
+
+    /// Configured station address by auto increment position.
+    positions: HashMap<u16, u16>,
+}
+
+impl SiiReader {
+    /// Process a single exchange, returning any SII data it read or wrote.
+    pub fn push(&mut self, exchange: &PduExchange) -> Vec<SiiAccess> {
+        let wkc = exchange.response.working_counter;
+
+        let command = &exchange.request.command;
+
+        let Some(register) = command::register(command) else {
+            return Vec::new();
+        };
+
+        let (device, written) = match (command::addressing(command), command::access(command)) {
+            (Some(Addressing::AutoIncrement), Some(Access::Write)) => {
+                let position = command::position(command).expect("Auto increment");
+
+                if let Some(configured) = register_range(
+                    register,
+                    &exchange.request.data,
+                    RegisterAddress::ConfiguredStationAddress.into(),
+                    2,
+                )
+                .filter(|_| wkc == 1)
+                {
+                    self.positions
+                        .insert(position, u16::from_le_bytes(configured.try_into().unwrap()));
+                }
+
+                (self.position(position), true)
+            }
+            (Some(Addressing::Configured), Some(Access::Write)) => (
+                SiiDevice::Configured(command::configured_address(command).expect("Configured")),
+                true,
+            ),
+            (Some(Addressing::Broadcast), Some(Access::Write)) => (SiiDevice::Broadcast, true),
+            (Some(Addressing::AutoIncrement), Some(Access::Read)) => (
+                self.position(command::position(command).expect("Auto increment")),
+                false,
+            ),
+            (Some(Addressing::Configured), Some(Access::Read)) => (
+                SiiDevice::Configured(command::configured_address(command).expect("Configured")),
+                false,
+            ),
+            // Responses from more than one SubDevice are ORed together so are meaningless.
+            (Some(Addressing::Broadcast), Some(Access::Read)) if wkc == 1 => {
+                (SiiDevice::Broadcast, false)
+            }
+            _ => return Vec::new(),
+        };
+
+        let control = u16::from(RegisterAddress::SiiControl);
+
+        let len = exchange.request.data.len() as u16;
+
+        // Skip anything that doesn't touch the control, address or data registers
+        if wkc == 0
+            || register >= control + 6 + MAX_READ_SIZE as u16
+            || register.saturating_add(len) <= control
+        {
+            return Vec::new();
+        }
+
+        if written {
+            let data = &exchange.request.data;
+
+            // Broadcast writes reach every SubDevice
+            if device == SiiDevice::Broadcast {
+                self.states.entry(device).or_default();
+
+                let mut accesses = Vec::new();
+
+                for (device, state) in self.states.iter_mut() {
+                    accesses.extend(state.write(*device, register, data, exchange));
+                }
+
+                return accesses;
+            }
+
+            self.states
+                .entry(device)
+                .or_default()
+                .write(device, register, data, exchange)
+                .into_iter()
+                .collect()
+        } else {
+            self.states.entry(device).or_default().read(
+                device,
+                register,
+                &exchange.response.data,
+                exchange,
+            )
+        }
+    }
+
+    /// Resolve an auto increment position to a configured address if one has been assigned.
+    fn position(&self, position: u16) -> SiiDevice {
+        self.positions
+            .get(&position)
+            .map(|configured| SiiDevice::Configured(*configured))
+            .unwrap_or(SiiDevice::Position(position))
+    }
+}
+
+impl SiiState {
+    /// Apply a write from the MainDevice to the SII registers.
+    fn write(
+        &mut self,
+        device: SiiDevice,
+        register: u16,
+        data: &[u8],
+        exchange: &PduExchange,
+    ) -> Option<SiiAccess> {
+        // The address and data registers are latched before the command is executed, so a single
+        // write can set all three.
+        if let Some(address) = register_range(register, data, RegisterAddress::SiiAddress.into(), 2)
+        {
+            self.address = u16::from_le_bytes(address.try_into().unwrap());
+        }
+
+        if let Some(write_data) = register_range(register, data, RegisterAddress::SiiData.into(), 2)
+        {
+            self.write_data = write_data.try_into().unwrap();
+        }
+
+        let control = register_range(register, data, RegisterAddress::SiiControl.into(), 2)?;
+        let control = u16::from_le_bytes(control.try_into().unwrap());
+
+        let command = control & CONTROL_COMMAND_MASK;
+
+        if command == 0 {
+            // Error reset or access configuration
+            return None;
+        }
+
+        // A new command can only be issued once the previous one completed, so an unconfirmed
+        // write must have succeeded.
+        let confirmed = self.confirm_write(device, exchange.tx_packet_number);
+
+        self.loaded = None;
+
+        match command {
+            CONTROL_READ => {
+                log::trace!("{} SII read at {:#06x}", device, self.address);
+
+                self.loaded = Some(self.address);
+            }
+            CONTROL_WRITE => {
+                log::trace!(
+                    "{} SII write {:02x?} at {:#06x}",
+                    device,
+                    self.write_data,
+                    self.address
+                );
+
+                self.pending_write = Some((self.address, self.write_data));
+            }
+            CONTROL_RELOAD => {
+                log::debug!("{} SII reload", device);
+            }
+            other => {
+                log::warn!(
+                    "Packet {}: {} invalid SII command {:#06x}",
+                    exchange.tx_packet_number,
+                    device,
+                    other
+                );
+            }
+        }
+
+        confirmed
+    }
+
+    /// Apply a read by the MainDevice of the SII registers.
+    fn read(
+        &mut self,
+        device: SiiDevice,
+        register: u16,
+        data: &[u8],
+        exchange: &PduExchange,
+    ) -> Vec<SiiAccess> {
+        let mut accesses = Vec::new();
+
+        if let Some(status) = register_range(register, data, RegisterAddress::SiiControl.into(), 2)
+        {
+            let status = u16::from_le_bytes(status.try_into().unwrap());
+
+            self.read_size = Some(if status & CONTROL_READ_SIZE_8 != 0 {
+                8
+            } else {
+                4
+            });
+
+            self.busy = status & CONTROL_BUSY != 0;
+
+            if !self.busy {
+                if status & (CONTROL_COMMAND_ERROR | CONTROL_WRITE_ERROR) != 0 {
+                    if let Some((address, data)) = self.pending_write.take() {
+                        log::warn!(
+                            "Packet {}: {} SII write {:02x?} at {:#06x} failed, status {:#06x}",
+                            exchange.rx_packet_number,
+                            device,
+                            data,
+                            address,
+                            status
+                        );
+                    }
+
+                    if self.loaded.take().is_some() {
+                        log::warn!(
+                            "Packet {}: {} SII read failed, status {:#06x}",
+                            exchange.rx_packet_number,
+                            device,
+                            status
+                        );
+                    }
+                }
+
+                accesses.extend(self.confirm_write(device, exchange.rx_packet_number));
+            }
+        }
+
+        let data_register = u16::from(RegisterAddress::SiiData);
+
+        let (Some(word_address), false) = (self.loaded, self.busy) else {
+            return accesses;
+        };
+
+        // Only the part of the read overlapping the loaded data is valid
+        let start = register.max(data_register);
+        let end = (register + data.len() as u16)
+            .min(data_register + self.read_size.unwrap_or(MAX_READ_SIZE) as u16);
+
+        let offset = start - data_register;
+
+        if end <= start || offset % 2 != 0 {
+            return accesses;
+        }
+
+        let loaded = &data[usize::from(start - register)..usize::from(end - register)];
+
+        // Only whole words
+        let loaded = &loaded[0..loaded.len() / 2 * 2];
+
+        log::debug!(
+            "{} EEPROM data at {:#06x} {:02x?} {:?}",
+            device,
+            word_address + offset / 2,
+            loaded,
+            loaded
+                .iter()
+                .map(|byte| char::from_u32(u32::from(*byte))
+                    .filter(|c| c.is_alphanumeric() || c.is_ascii_punctuation())
+                    .unwrap_or('.'))
+                .collect::<String>()
+        );
+
+        accesses.push(SiiAccess {
+            device,
+            kind: SiiAccessKind::Read,
+            word_address: word_address + offset / 2,
+            data: loaded.to_vec(),
+            packet_number: exchange.rx_packet_number,
+        });
+
+        accesses
+    }
+
+    /// Turn a pending write into an [`SiiAccess`].
+    fn confirm_write(&mut self, device: SiiDevice, packet_number: usize) -> Option<SiiAccess> {
+        let (word_address, data) = self.pending_write.take()?;
+
+        Some(SiiAccess {
+            device,
+            kind: SiiAccessKind::Write,
+            word_address,
+            data: data.to_vec(),
+            packet_number,
+        })
+    }
+}
+
+/// SII data map for a single SubDevice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiiImage {
+    /// Raw SII contents, indexed by byte address.
+    pub data: Vec<u8>,
+
+    /// Whether each word of `data` was seen in the capture, indexed by word address.
+    pub captured: Vec<bool>,
+}
+
+impl Default for SiiImage {
+    fn default() -> Self {
+        // Allocate 64KiB for each SubDevice's EEPROM, the most a 16 bit byte address can reach.
+        // The actual data could be much smaller, but it's small enough to not bother tracking how
+        // big the vec should be.
+        Self {
+            data: vec![0u8; 0x10000],
+            captured: vec![false; 0x8000],
+        }
+    }
+}
+
+impl SiiImage {
+    /// Copy a chunk of read data into the image.
+    pub fn write(&mut self, word_address: u16, data: &[u8]) {
+        let start = usize::from(word_address) * 2;
+
+        let Some(dest) = self.data.get_mut(start..start + data.len()) else {
+            log::warn!(
+                "SII read of {} bytes at word {:#06x} is out of range",
+                data.len(),
+                word_address
+            );
+
+            return;
+        };
+
+        dest.copy_from_slice(data);
+
+        let start = usize::from(word_address);
+
+        for captured in self.captured.iter_mut().skip(start).take(data.len() / 2) {
+            *captured = true;
+        }
+    }
+
+    /// Get `len` bytes starting at the given word address, if they were all captured.
+    pub fn get(&self, word_address: u16, len: usize) -> Option<&[u8]> {
+        let start = usize::from(word_address);
+
+        let captured = self.captured.get(start..start + len.div_ceil(2))?;
+
+        if !captured.iter().all(|captured| *captured) {
+            return None;
+        }
+
+        self.data.get(start * 2..start * 2 + len)
+    }
+
+    /// Get the `u16` at the given word address, if it was captured.
+    pub fn get_u16(&self, word_address: u16) -> Option<u16> {
+        self.get(word_address, 2)
+            .map(|raw| u16::from_le_bytes(raw.try_into().unwrap()))
+    }
+
+    /// Get the `u32` starting at the given word address, if it was captured.
+    pub fn get_u32(&self, word_address: u16) -> Option<u32> {
+        self.get(word_address, 4)
+            .map(|raw| u32::from_le_bytes(raw.try_into().unwrap()))
+    }
+
+    /// Address of the last captured word, or `None` if nothing was captured.
+    pub fn last_captured(&self) -> Option<u16> {
+        self.captured
+            .iter()
+            .rposition(|captured| *captured)
+            .map(|word| word as u16)
+    }
+
+    /// Ranges of words that were never captured, up to and including word `end`.
+    ///
+    /// Ranges are inclusive.
+    pub fn uncaptured_ranges(&self, end: u16) -> Vec<(u16, u16)> {
+        let mut ranges = Vec::new();
+        let mut start = None;
+
+        for word in 0..=end {
+            let captured = self
+                .captured
+                .get(usize::from(word))
+                .copied()
+                .unwrap_or(false);
+
+            match (captured, start) {
+                (false, None) => start = Some(word),
+                (true, Some(s)) => {
+                    ranges.push((s, word - 1));
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(s) = start {
+            ranges.push((s, end));
+        }
+
+        ranges
+    }
+
+    /// Ranges of words that were captured. Ranges are inclusive.
+    pub fn captured_ranges(&self) -> Vec<(u16, u16)> {
+        let Some(last) = self.last_captured() else {
+            return Vec::new();
+        };
+
+        let mut ranges = Vec::new();
+        let mut next = 0u16;
+
+        for (start, end) in self.uncaptured_ranges(last) {
+            if start > next {
+                ranges.push((next, start - 1));
+            }
+
+            next = end + 1;
+        }
+
+        ranges.push((next, last));
+
+        ranges
+    }
+
+    /// Image data up to and including the last captured word.
+    ///
+    /// Words that weren't captured are left as zero.
+    pub fn trimmed(&self) -> &[u8] {
+        let len = self
+            .last_captured()
+            .map(|word| (usize::from(word) + 1) * 2)
+            .unwrap_or(0);
+
+        &self.data[0..len]
+    }
+
+    /// Encode the captured ranges of the image as Intel HEX. Words that weren't captured are
+    /// omitted.
+    pub fn to_intel_hex(&self) -> String {
+        /// Maximum number of data bytes in a single record.
+        const RECORD_LEN: usize = 16;
+
+        let mut out = String::new();
+
+        for (start, end) in self.captured_ranges() {
+            let start = usize::from(start) * 2;
+            let end = (usize::from(end) + 1) * 2;
+
+            for (chunk_idx, chunk) in self.data[start..end].chunks(RECORD_LEN).enumerate() {
+                let address = (start + chunk_idx * RECORD_LEN) as u16;
+
+                out.push_str(&intel_hex_record(address, 0x00, chunk));
+            }
+        }
+
+        // End of file
+        out.push_str(&intel_hex_record(0x0000, 0x01, &[]));
+
+        out
+    }
+
+    /// Summarise which parts of the image were captured.
+    pub fn coverage(&self) -> Coverage {
+        let captured = self.captured_ranges();
+
+        Coverage {
+            captured_words: self.captured.iter().filter(|captured| **captured).count(),
+            last_captured: self.last_captured(),
+            uncaptured: self
+                .last_captured()
+                .map(|last| self.uncaptured_ranges(last))
+                .unwrap_or_default(),
+            captured,
+        }
+    }
+}
+
+/// Summary of which words of an [`SiiImage`] were captured.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Coverage {
+    /// Total number of words captured.
+    pub captured_words: usize,
+
+    /// Address of the highest captured word.
+    pub last_captured: Option<u16>,
+
+    /// Inclusive ranges of captured words.
+    pub captured: Vec<(u16, u16)>,
+
+    /// Inclusive ranges of words below `last_captured` that were never captured.
+    pub uncaptured: Vec<(u16, u16)>,
+}
+
+impl std::fmt::Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(last) = self.last_captured else {
+            return writeln!(f, "No words captured");
+        };
+
+        writeln!(
+            f,
+            "{} of {} words captured up to word {:#06x}",
+            self.captured_words,
+            usize::from(last) + 1,
+            last
+        )?;
+
+        for (heading, ranges) in [
+            ("Captured", &self.captured),
+            ("Not captured", &self.uncaptured),
+        ] {
+            writeln!(f, "{}", heading)?;
+
+            for (start, end) in ranges.iter() {
+                match end - start + 1 {
+                    1 => writeln!(f, "    {:#06x} (1 word)", start)?,
+                    n => writeln!(f, "    {:#06x} - {:#06x} ({} words)", start, end, n)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Format a single Intel HEX record, including the trailing newline.
+fn intel_hex_record(address: u16, record_type: u8, data: &[u8]) -> String {
+    let [address_hi, address_lo] = address.to_be_bytes();
+
+    let header = [data.len() as u8, address_hi, address_lo, record_type];
+
+    let checksum = header
+        .iter()
+        .chain(data.iter())
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg();
+
+    let mut record = String::from(":");
+
+    for byte in header.iter().chain(data.iter()).chain([checksum].iter()) {
+        record.push_str(&format!("{:02X}", byte));
+    }
+
+    record.push('\n');
+
+    record
+}
+
+/// Recover the SII image of every SubDevice in a capture.
+///
+/// Both reads and writes are applied to the image in the order they happened, so the image
+/// reflects the EEPROM contents at the end of the capture.
+pub fn sii_images(exchanges: impl Iterator<Item = PduExchange>) -> BTreeMap<SiiDevice, SiiImage> {
+    let mut reader = SiiReader::default();
+    let mut images = BTreeMap::<SiiDevice, SiiImage>::new();
+
+    for exchange in exchanges {
+        for access in reader.push(&exchange) {
+            if access.kind == SiiAccessKind::Write {
+                log::info!(
+                    "Packet {}: {} EEPROM write at {:#06x}: {:02x?}",
+                    access.packet_number,
+                    access.device,
+                    access.word_address,
+                    access.data
+                );
+            }
+
+            images
+                .entry(access.device)
+                .or_default()
+                .write(access.word_address, &access.data);
+        }
+    }
+
+    images
+}
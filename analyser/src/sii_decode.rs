@@ -0,0 +1,891 @@
+//! Decode the contents of a recovered SII image.
+//!
+//! The layout is defined in ETG2010. Any field containing words that were not captured is left
+//! as `None`, and categories whose bodies were only partially captured are listed without being
+//! decoded.
+
+use crate::sii::SiiImage;
+use serde::Serialize;
+use std::fmt;
+
+/// Word address of the first category header.
+const FIRST_CATEGORY: u16 = 0x0040;
+
+/// Category type marking the end of the category list.
+const CATEGORY_END: u16 = 0xffff;
+
+/// A mailbox area defined in the SII header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct MailboxArea {
+    pub offset: u16,
+    pub size: u16,
+}
+
+/// Fixed SII header, words `0x0000` to `0x003f`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SiiHeader {
+    pub pdi_control: Option<u16>,
+    pub pdi_config: Option<u16>,
+    pub sync_impulse_len: Option<u16>,
+    pub pdi_config2: Option<u16>,
+    pub station_alias: Option<u16>,
+    pub checksum: Option<u16>,
+    pub vendor_id: Option<u32>,
+    pub product_code: Option<u32>,
+    pub revision: Option<u32>,
+    pub serial: Option<u32>,
+    pub execution_delay: Option<u16>,
+    pub port0_delay: Option<u16>,
+    pub port1_delay: Option<u16>,
+    pub bootstrap_rx_mailbox: Option<MailboxArea>,
+    pub bootstrap_tx_mailbox: Option<MailboxArea>,
+    pub standard_rx_mailbox: Option<MailboxArea>,
+    pub standard_tx_mailbox: Option<MailboxArea>,
+    /// Supported mailbox protocols bitmap.
+    pub mailbox_protocols: Option<u16>,
+    /// EEPROM size in KiBit, minus one.
+    pub size: Option<u16>,
+    pub version: Option<u16>,
+}
+
+impl SiiHeader {
+    fn decode(image: &SiiImage) -> Self {
+        let mailbox = |word| {
+            Some(MailboxArea {
+                offset: image.get_u16(word)?,
+                size: image.get_u16(word + 1)?,
+            })
+        };
+
+        Self {
+            pdi_control: image.get_u16(0x0000),
+            pdi_config: image.get_u16(0x0001),
+            sync_impulse_len: image.get_u16(0x0002),
+            pdi_config2: image.get_u16(0x0003),
+            station_alias: image.get_u16(0x0004),
+            checksum: image.get_u16(0x0007),
+            vendor_id: image.get_u32(0x0008),
+            product_code: image.get_u32(0x000a),
+            revision: image.get_u32(0x000c),
+            serial: image.get_u32(0x000e),
+            execution_delay: image.get_u16(0x0010),
+            port0_delay: image.get_u16(0x0011),
+            port1_delay: image.get_u16(0x0012),
+            bootstrap_rx_mailbox: mailbox(0x0014),
+            bootstrap_tx_mailbox: mailbox(0x0016),
+            standard_rx_mailbox: mailbox(0x0018),
+            standard_tx_mailbox: mailbox(0x001a),
+            mailbox_protocols: image.get_u16(0x001c),
+            size: image.get_u16(0x003e),
+            version: image.get_u16(0x003f),
+        }
+    }
+}
+
+/// General category, ETG2010 Table 7.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct General {
+    pub group: Option<String>,
+    pub image: Option<String>,
+    pub order: Option<String>,
+    pub name: Option<String>,
+    pub coe_details: u8,
+    pub foe_details: u8,
+    pub eoe_details: u8,
+    pub soe_channels: u8,
+    pub ds402_channels: u8,
+    pub sysman_class: u8,
+    pub flags: u8,
+    /// Current consumption from the E-Bus in mA. Negative values are current fed into the bus.
+    pub ebus_current: i16,
+    pub physical_ports: u16,
+    pub physical_memory_address: u16,
+}
+
+/// Usage of a single FMMU, ETG2010 Table 8.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum FmmuUsage {
+    Unused,
+    Outputs,
+    Inputs,
+    SyncManagerStatus,
+    Unknown(u8),
+}
+
+impl From<u8> for FmmuUsage {
+    fn from(raw: u8) -> Self {
+        match raw {
+            0x00 | 0xff => Self::Unused,
+            0x01 => Self::Outputs,
+            0x02 => Self::Inputs,
+            0x03 => Self::SyncManagerStatus,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// SyncManager type, ETG2010 Table 9.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum SyncManagerType {
+    Unused,
+    MailboxOut,
+    MailboxIn,
+    ProcessDataOutputs,
+    ProcessDataInputs,
+    Unknown(u8),
+}
+
+impl From<u8> for SyncManagerType {
+    fn from(raw: u8) -> Self {
+        match raw {
+            0x00 => Self::Unused,
+            0x01 => Self::MailboxOut,
+            0x02 => Self::MailboxIn,
+            0x03 => Self::ProcessDataOutputs,
+            0x04 => Self::ProcessDataInputs,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// A single SyncManager definition, ETG2010 Table 9.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct SiiSyncManager {
+    pub start_address: u16,
+    pub length: u16,
+    pub control: u8,
+    pub status: u8,
+    pub enable: u8,
+    pub sm_type: SyncManagerType,
+}
+
+/// A single entry in a PDO, ETG2010 Table 11.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SiiPdoEntry {
+    pub index: u16,
+    pub sub_index: u8,
+    pub name: Option<String>,
+    pub data_type: u8,
+    pub bit_len: u8,
+    pub flags: u16,
+}
+
+/// A TxPDO or RxPDO definition, ETG2010 Table 10.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SiiPdo {
+    pub index: u16,
+    pub sync_manager: u8,
+    pub dc_sync: u8,
+    pub name: Option<String>,
+    pub flags: u16,
+    pub entries: Vec<SiiPdoEntry>,
+}
+
+/// A Distributed Clocks operation mode, ETG2010 Table 12.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SiiDcSync {
+    pub cycle_time_sync0: u32,
+    pub shift_time_sync0: u32,
+    pub shift_time_sync1: u32,
+    pub sync1_cycle_factor: i16,
+    pub assign_activate: u16,
+    pub sync0_cycle_factor: i16,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Decoded category body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "data")]
+pub enum CategoryContents {
+    Strings(Vec<String>),
+    General(General),
+    Fmmu(Vec<FmmuUsage>),
+    SyncManagers(Vec<SiiSyncManager>),
+    TxPdos(Vec<SiiPdo>),
+    RxPdos(Vec<SiiPdo>),
+    Dc(Vec<SiiDcSync>),
+    /// A category this decoder doesn't understand.
+    Other,
+}
+
+/// A single category from the SII.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Category {
+    /// Word address of the category header.
+    pub word_address: u16,
+
+    /// Raw category type.
+    pub category_type: u16,
+
+    /// Length of the category body in words.
+    pub length: u16,
+
+    /// Decoded body, or `None` if the body wasn't completely captured.
+    pub contents: Option<CategoryContents>,
+}
+
+impl Category {
+    /// Human readable name of the category type.
+    pub fn type_name(&self) -> &'static str {
+        match self.category_type & 0x7fff {
+            0 => "NOP",
+            1..=9 => "Device specific",
+            10 => "Strings",
+            20 => "Data types",
+            30 => "General",
+            40 => "FMMU",
+            41 => "SyncManager",
+            42 => "FMMU extended",
+            43 => "Sync unit",
+            50 => "TxPDO",
+            51 => "RxPDO",
+            60 => "DC",
+            _ if self.category_type & 0x8000 != 0 => "Vendor specific",
+            _ => "Unknown",
+        }
+    }
+}
+
+/// Decoded SII contents of a single SubDevice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SiiContents {
+    pub header: SiiHeader,
+
+    pub categories: Vec<Category>,
+
+    /// True if the category list was followed all the way to the end marker.
+    pub complete: bool,
+
+    /// Inclusive ranges of word addresses that were never captured, up to the end of the category
+    /// list or the last captured word, whichever is further.
+    pub uncaptured: Vec<(u16, u16)>,
+}
+
+impl SiiContents {
+    /// Decode a recovered SII image.
+    pub fn decode(image: &SiiImage) -> Self {
+        let header = SiiHeader::decode(image);
+
+        // Find category headers first so string indices can be resolved regardless of category
+        // order.
+        let mut headers = Vec::new();
+        let mut word = FIRST_CATEGORY;
+        let mut complete = false;
+
+        while let (Some(category_type), Some(length)) =
+            (image.get_u16(word), image.get_u16(word.saturating_add(1)))
+        {
+            if category_type == CATEGORY_END {
+                complete = true;
+
+                break;
+            }
+
+            headers.push((word, category_type, length));
+
+            let Some(next) = word.checked_add(2).and_then(|w| w.checked_add(length)) else {
+                break;
+            };
+
+            word = next;
+        }
+
+        let body = |word: u16, length: u16| image.get(word + 2, usize::from(length) * 2);
+
+        let strings = headers
+            .iter()
+            .find(|(_, category_type, _)| *category_type == 10)
+            .and_then(|(word, _, length)| body(*word, *length))
+            .map(decode_strings)
+            .unwrap_or_default();
+
+        let categories = headers
+            .iter()
+            .map(|(word, category_type, length)| Category {
+                word_address: *word,
+                category_type: *category_type,
+                length: *length,
+                contents: body(*word, *length)
+                    .map(|raw| decode_category(*category_type, raw, &strings)),
+            })
+            .collect();
+
+        // Include the end marker in the range checked for holes
+        let end = word
+            .saturating_add(1)
+            .max(image.last_captured().unwrap_or(0));
+
+        Self {
+            header,
+            categories,
+            complete,
+            uncaptured: image.uncaptured_ranges(end),
+        }
+    }
+}
+
+/// Look up a string by its 1-based index. Index `0` means "no string".
+fn string(strings: &[String], index: u8) -> Option<String> {
+    usize::from(index)
+        .checked_sub(1)
+        .and_then(|index| strings.get(index))
+        .cloned()
+}
+
+fn decode_strings(raw: &[u8]) -> Vec<String> {
+    let Some((count, mut i)) = raw.split_first() else {
+        return Vec::new();
+    };
+
+    let mut strings = Vec::new();
+
+    for _ in 0..*count {
+        let Some((len, rest)) = i.split_first() else {
+            break;
+        };
+
+        let Some(s) = rest.get(0..usize::from(*len)) else {
+            break;
+        };
+
+        strings.push(String::from_utf8_lossy(s).to_string());
+
+        i = &rest[usize::from(*len)..];
+    }
+
+    strings
+}
+
+fn decode_category(category_type: u16, raw: &[u8], strings: &[String]) -> CategoryContents {
+    let u16_at = |i: &[u8], offset: usize| u16::from_le_bytes([i[offset], i[offset + 1]]);
+    let u32_at = |i: &[u8], offset: usize| {
+        u32::from_le_bytes([i[offset], i[offset + 1], i[offset + 2], i[offset + 3]])
+    };
+
+    match category_type {
+        10 => CategoryContents::Strings(decode_strings(raw)),
+        30 if raw.len() >= 20 => CategoryContents::General(General {
+            group: string(strings, raw[0]),
+            image: string(strings, raw[1]),
+            order: string(strings, raw[2]),
+            name: string(strings, raw[3]),
+            coe_details: raw[5],
+            foe_details: raw[6],
+            eoe_details: raw[7],
+            soe_channels: raw[8],
+            ds402_channels: raw[9],
+            sysman_class: raw[10],
+            flags: raw[11],
+            ebus_current: u16_at(raw, 12) as i16,
+            physical_ports: u16_at(raw, 16),
+            physical_memory_address: u16_at(raw, 18),
+        }),
+        40 => CategoryContents::Fmmu(raw.iter().copied().map(FmmuUsage::from).collect()),
+        41 => CategoryContents::SyncManagers(
+            raw.chunks_exact(8)
+                .map(|sm| SiiSyncManager {
+                    start_address: u16_at(sm, 0),
+                    length: u16_at(sm, 2),
+                    control: sm[4],
+                    status: sm[5],
+                    enable: sm[6],
+                    sm_type: SyncManagerType::from(sm[7]),
+                })
+                .collect(),
+        ),
+        50 => CategoryContents::TxPdos(decode_pdos(raw, strings)),
+        51 => CategoryContents::RxPdos(decode_pdos(raw, strings)),
+        60 => CategoryContents::Dc(
+            raw.chunks_exact(24)
+                .map(|dc| SiiDcSync {
+                    cycle_time_sync0: u32_at(dc, 0),
+                    shift_time_sync0: u32_at(dc, 4),
+                    shift_time_sync1: u32_at(dc, 8),
+                    sync1_cycle_factor: u16_at(dc, 12) as i16,
+                    assign_activate: u16_at(dc, 14),
+                    sync0_cycle_factor: u16_at(dc, 16) as i16,
+                    name: string(strings, dc[18]),
+                    description: string(strings, dc[19]),
+                })
+                .collect(),
+        ),
+        _ => CategoryContents::Other,
+    }
+}
+
+fn decode_pdos(mut raw: &[u8], strings: &[String]) -> Vec<SiiPdo> {
+    let mut pdos = Vec::new();
+
+    while raw.len() >= 8 {
+        let num_entries = usize::from(raw[2]);
+
+        let mut pdo = SiiPdo {
+            index: u16::from_le_bytes([raw[0], raw[1]]),
+            sync_manager: raw[3],
+            dc_sync: raw[4],
+            name: string(strings, raw[5]),
+            flags: u16::from_le_bytes([raw[6], raw[7]]),
+            entries: Vec::with_capacity(num_entries),
+        };
+
+        raw = &raw[8..];
+
+        for entry in raw.chunks_exact(8).take(num_entries) {
+            pdo.entries.push(SiiPdoEntry {
+                index: u16::from_le_bytes([entry[0], entry[1]]),
+                sub_index: entry[2],
+                name: string(strings, entry[3]),
+                data_type: entry[4],
+                bit_len: entry[5],
+                flags: u16::from_le_bytes([entry[6], entry[7]]),
+            });
+        }
+
+        raw = raw.get(num_entries * 8..).unwrap_or_default();
+
+        pdos.push(pdo);
+    }
+
+    pdos
+}
+
+/// Formats an optional value, or a placeholder if it wasn't captured.
+struct Field<T>(Option<T>);
+
+impl<T: fmt::LowerHex> fmt::Display for Field<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(value) => write!(f, "{:#0width$x}", value, width = 2 + size_of::<T>() * 2),
+            None => f.write_str("(not captured)"),
+        }
+    }
+}
+
+fn opt_str(s: &Option<String>) -> &str {
+    s.as_deref().unwrap_or("-")
+}
+
+impl fmt::Display for SiiContents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let h = &self.header;
+
+        writeln!(f, "Header")?;
+        writeln!(f, "    PDI control            {}", Field(h.pdi_control))?;
+        writeln!(f, "    PDI config             {}", Field(h.pdi_config))?;
+        writeln!(
+            f,
+            "    Sync impulse length    {}",
+            Field(h.sync_impulse_len)
+        )?;
+        writeln!(f, "    PDI config 2           {}", Field(h.pdi_config2))?;
+        writeln!(f, "    Station alias          {}", Field(h.station_alias))?;
+        writeln!(f, "    Checksum               {}", Field(h.checksum))?;
+        writeln!(f, "    Vendor ID              {}", Field(h.vendor_id))?;
+        writeln!(f, "    Product code           {}", Field(h.product_code))?;
+        writeln!(f, "    Revision               {}", Field(h.revision))?;
+        writeln!(f, "    Serial                 {}", Field(h.serial))?;
+        writeln!(f, "    Execution delay        {}", Field(h.execution_delay))?;
+        writeln!(f, "    Port 0 delay           {}", Field(h.port0_delay))?;
+        writeln!(f, "    Port 1 delay           {}", Field(h.port1_delay))?;
+
+        for (name, mailbox) in [
+            ("Bootstrap RX mailbox", h.bootstrap_rx_mailbox),
+            ("Bootstrap TX mailbox", h.bootstrap_tx_mailbox),
+            ("Standard RX mailbox ", h.standard_rx_mailbox),
+            ("Standard TX mailbox ", h.standard_tx_mailbox),
+        ] {
+            match mailbox {
+                Some(mailbox) => writeln!(
+                    f,
+                    "    {}   offset {:#06x}, size {}",
+                    name, mailbox.offset, mailbox.size
+                )?,
+                None => writeln!(f, "    {}   (not captured)", name)?,
+            }
+        }
+
+        writeln!(
+            f,
+            "    Mailbox protocols      {}",
+            Field(h.mailbox_protocols)
+        )?;
+        writeln!(f, "    Size                   {}", Field(h.size))?;
+        writeln!(f, "    Version                {}", Field(h.version))?;
+
+        for category in self.categories.iter() {
+            writeln!(
+                f,
+                "{} category ({:#06x}) at word {:#06x}, {} words",
+                category.type_name(),
+                category.category_type,
+                category.word_address,
+                category.length
+            )?;
+
+            let Some(contents) = &category.contents else {
+                writeln!(f, "    (not completely captured)")?;
+
+                continue;
+            };
+
+            match contents {
+                CategoryContents::Strings(strings) => {
+                    for (idx, s) in strings.iter().enumerate() {
+                        writeln!(f, "    {:>3}: {:?}", idx + 1, s)?;
+                    }
+                }
+                CategoryContents::General(general) => {
+                    writeln!(f, "    Group                  {}", opt_str(&general.group))?;
+                    writeln!(f, "    Image                  {}", opt_str(&general.image))?;
+                    writeln!(f, "    Order                  {}", opt_str(&general.order))?;
+                    writeln!(f, "    Name                   {}", opt_str(&general.name))?;
+                    writeln!(f, "    CoE details            {:#04x}", general.coe_details)?;
+                    writeln!(f, "    FoE details            {:#04x}", general.foe_details)?;
+                    writeln!(f, "    EoE details            {:#04x}", general.eoe_details)?;
+                    writeln!(f, "    SoE channels           {}", general.soe_channels)?;
+                    writeln!(f, "    DS402 channels         {}", general.ds402_channels)?;
+                    writeln!(f, "    Flags                  {:#04x}", general.flags)?;
+                    writeln!(f, "    E-Bus current          {} mA", general.ebus_current)?;
+                    writeln!(
+                        f,
+                        "    Physical ports         {:#06x}",
+                        general.physical_ports
+                    )?;
+                }
+                CategoryContents::Fmmu(fmmus) => {
+                    for (idx, usage) in fmmus.iter().enumerate() {
+                        writeln!(f, "    FMMU{}: {:?}", idx, usage)?;
+                    }
+                }
+                CategoryContents::SyncManagers(sms) => {
+                    for (idx, sm) in sms.iter().enumerate() {
+                        writeln!(
+                            f,
+                            "    SM{}: start {:#06x}, length {}, control {:#04x}, enable {:#04x}, {:?}",
+                            idx, sm.start_address, sm.length, sm.control, sm.enable, sm.sm_type
+                        )?;
+                    }
+                }
+                CategoryContents::TxPdos(pdos) | CategoryContents::RxPdos(pdos) => {
+                    for pdo in pdos {
+                        writeln!(
+                            f,
+                            "    {:#06x} SM{} {:?}",
+                            pdo.index,
+                            pdo.sync_manager,
+                            opt_str(&pdo.name)
+                        )?;
+
+                        for entry in pdo.entries.iter() {
+                            writeln!(
+                                f,
+                                "        {:#06x}:{:02x} {:>2} bits, type {:#04x} {:?}",
+                                entry.index,
+                                entry.sub_index,
+                                entry.bit_len,
+                                entry.data_type,
+                                opt_str(&entry.name)
+                            )?;
+                        }
+                    }
+                }
+                CategoryContents::Dc(modes) => {
+                    for mode in modes {
+                        writeln!(
+                            f,
+                            "    {:?}: cycle {} ns, SYNC0 shift {} ns, SYNC1 shift {} ns, assign activate {:#06x}",
+                            opt_str(&mode.name),
+                            mode.cycle_time_sync0,
+                            mode.shift_time_sync0,
+                            mode.shift_time_sync1,
+                            mode.assign_activate
+                        )?;
+                    }
+                }
+                CategoryContents::Other => writeln!(f, "    (not decoded)")?,
+            }
+        }
+
+        if !self.complete {
+            writeln!(f, "Category list end marker not captured")?;
+        }
+
+        if self.uncaptured.is_empty() {
+            writeln!(f, "All words captured")?;
+        } else {
+            writeln!(f, "Words not captured")?;
+
+            for (start, end) in self.uncaptured.iter() {
+                if start == end {
+                    writeln!(f, "    {:#06x}", start)?;
+                } else {
+                    writeln!(f, "    {:#06x} - {:#06x}", start, end)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Raw SII header words up to the first category, for a coupler with a CoE mailbox.
+    fn header() -> Vec<u8> {
+        let mut words = [0u16; FIRST_CATEGORY as usize];
+
+        words[0x0000] = 0x0c08;
+        words[0x0004] = 0x1234;
+        words[0x0007] = 0x00aa;
+        words[0x0008..0x000a].copy_from_slice(&[0x0002, 0x0000]);
+        words[0x000a..0x000c].copy_from_slice(&[0x2c52, 0x044c]);
+        words[0x000c..0x000e].copy_from_slice(&[0x0000, 0x0011]);
+        words[0x0018..0x001c].copy_from_slice(&[0x1000, 0x0080, 0x1080, 0x0080]);
+        words[0x001c] = 0x0004;
+        words[0x003e] = 0x000f;
+        words[0x003f] = 0x0001;
+
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    /// Append a category header with the given length in words, followed by `body` padded to a
+    /// whole number of words.
+    fn category(image: &mut Vec<u8>, category_type: u16, length: u16, body: &[u8]) {
+        image.extend_from_slice(&category_type.to_le_bytes());
+        image.extend_from_slice(&length.to_le_bytes());
+        image.extend_from_slice(body);
+
+        if !body.len().is_multiple_of(2) {
+            image.push(0);
+        }
+    }
+
+    fn strings() -> Vec<u8> {
+        let mut body = vec![3];
+
+        for s in ["EK1100", "Terminals", "Inputs"] {
+            body.push(s.len() as u8);
+            body.extend_from_slice(s.as_bytes());
+        }
+
+        body
+    }
+
+    fn general() -> Vec<u8> {
+        let mut body = vec![0u8; 32];
+
+        // Group, image, order and name string indices
+        body[0..4].copy_from_slice(&[2, 0, 1, 1]);
+        body[5] = 0x23;
+        body[12..14].copy_from_slice(&(-2000i16).to_le_bytes());
+        body[16..18].copy_from_slice(&0x0011u16.to_le_bytes());
+
+        body
+    }
+
+    fn image(raw: &[u8]) -> SiiImage {
+        let mut image = SiiImage::default();
+
+        image.write(0, raw);
+
+        image
+    }
+
+    #[test]
+    fn decode() {
+        let mut raw = header();
+
+        category(&mut raw, 10, 13, &strings());
+        category(&mut raw, 30, 16, &general());
+        category(&mut raw, 40, 2, &[0x01, 0x02, 0x03, 0xff]);
+        category(
+            &mut raw,
+            41,
+            8,
+            &[
+                0x00, 0x10, 0x80, 0x00, 0x26, 0x00, 0x01, 0x01, //
+                0x80, 0x10, 0x80, 0x00, 0x22, 0x00, 0x01, 0x02,
+            ],
+        );
+        category(
+            &mut raw,
+            50,
+            8,
+            &[
+                0x00, 0x1a, 1, 3, 0, 3, 0x00, 0x00, //
+                0x00, 0x60, 0x01, 0, 0x01, 1, 0x00, 0x00,
+            ],
+        );
+        category(&mut raw, CATEGORY_END, 0, &[]);
+
+        let contents = SiiContents::decode(&image(&raw));
+
+        assert_eq!(
+            contents.header,
+            SiiHeader {
+                pdi_control: Some(0x0c08),
+                pdi_config: Some(0),
+                sync_impulse_len: Some(0),
+                pdi_config2: Some(0),
+                station_alias: Some(0x1234),
+                checksum: Some(0x00aa),
+                vendor_id: Some(0x0000_0002),
+                product_code: Some(0x044c_2c52),
+                revision: Some(0x0011_0000),
+                serial: Some(0),
+                execution_delay: Some(0),
+                port0_delay: Some(0),
+                port1_delay: Some(0),
+                bootstrap_rx_mailbox: Some(MailboxArea { offset: 0, size: 0 }),
+                bootstrap_tx_mailbox: Some(MailboxArea { offset: 0, size: 0 }),
+                standard_rx_mailbox: Some(MailboxArea {
+                    offset: 0x1000,
+                    size: 0x80
+                }),
+                standard_tx_mailbox: Some(MailboxArea {
+                    offset: 0x1080,
+                    size: 0x80
+                }),
+                mailbox_protocols: Some(0x0004),
+                size: Some(0x000f),
+                version: Some(1),
+            }
+        );
+
+        assert!(contents.complete);
+        assert_eq!(contents.uncaptured, []);
+
+        let categories = contents
+            .categories
+            .iter()
+            .map(|category| (category.word_address, category.type_name(), category.length))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            categories,
+            [
+                (0x0040, "Strings", 13),
+                (0x004f, "General", 16),
+                (0x0061, "FMMU", 2),
+                (0x0065, "SyncManager", 8),
+                (0x006f, "TxPDO", 8),
+            ]
+        );
+
+        let contents = contents
+            .categories
+            .into_iter()
+            .map(|category| category.contents.unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            contents[0],
+            CategoryContents::Strings(vec![
+                "EK1100".to_string(),
+                "Terminals".to_string(),
+                "Inputs".to_string()
+            ])
+        );
+
+        assert_eq!(
+            contents[1],
+            CategoryContents::General(General {
+                group: Some("Terminals".to_string()),
+                image: None,
+                order: Some("EK1100".to_string()),
+                name: Some("EK1100".to_string()),
+                coe_details: 0x23,
+                foe_details: 0,
+                eoe_details: 0,
+                soe_channels: 0,
+                ds402_channels: 0,
+                sysman_class: 0,
+                flags: 0,
+                ebus_current: -2000,
+                physical_ports: 0x0011,
+                physical_memory_address: 0,
+            })
+        );
+
+        assert_eq!(
+            contents[2],
+            CategoryContents::Fmmu(vec![
+                FmmuUsage::Outputs,
+                FmmuUsage::Inputs,
+                FmmuUsage::SyncManagerStatus,
+                FmmuUsage::Unused,
+            ])
+        );
+
+        assert_eq!(
+            contents[3],
+            CategoryContents::SyncManagers(vec![
+                SiiSyncManager {
+                    start_address: 0x1000,
+                    length: 0x80,
+                    control: 0x26,
+                    status: 0,
+                    enable: 1,
+                    sm_type: SyncManagerType::MailboxOut,
+                },
+                SiiSyncManager {
+                    start_address: 0x1080,
+                    length: 0x80,
+                    control: 0x22,
+                    status: 0,
+                    enable: 1,
+                    sm_type: SyncManagerType::MailboxIn,
+                },
+            ])
+        );
+
+        assert_eq!(
+            contents[4],
+            CategoryContents::TxPdos(vec![SiiPdo {
+                index: 0x1a00,
+                sync_manager: 3,
+                dc_sync: 0,
+                name: Some("Inputs".to_string()),
+                flags: 0,
+                entries: vec![SiiPdoEntry {
+                    index: 0x6000,
+                    sub_index: 1,
+                    name: None,
+                    data_type: 0x01,
+                    bit_len: 1,
+                    flags: 0,
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn truncated_category() {
+        let mut raw = header();
+
+        category(&mut raw, 10, 13, &strings());
+        // DC category of 12 words with only the first 2 captured
+        category(&mut raw, 60, 12, &[0x00, 0x00, 0x10, 0x00]);
+
+        let contents = SiiContents::decode(&image(&raw));
+
+        assert!(!contents.complete);
+
+        assert_eq!(contents.categories.len(), 2);
+        assert!(contents.categories[0].contents.is_some());
+
+        let dc = &contents.categories[1];
+
+        assert_eq!(
+            (dc.word_address, dc.type_name(), dc.length),
+            (0x004f, "DC", 12)
+        );
+        assert_eq!(dc.contents, None);
+
+        // Rest of the DC category and the header of whatever follows it
+        assert_eq!(contents.uncaptured, [(0x0053, 0x005e)]);
+    }
+}
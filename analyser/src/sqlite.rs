@@ -0,0 +1,262 @@
+//! SQLite database of decoded captures, for querying many captures at once with SQL.
+//!
+//! A database holds any number of captures, keyed by their scenario name
+//! ([`PcapFile::scenario`](crate::PcapFile::scenario)). Importing a scenario that's already in the
+//! database replaces it.
+//!
+//! Times are integer nanoseconds relative to the first EtherCAT frame in the capture, except
+//! `captures.start_time_ns` which is the capture timestamp of that frame, usually nanoseconds since
+//! the Unix epoch. See [`SCHEMA`] for every table and column.
+
+use crate::{command, exchange::PduExchange, pdu::Frame, PcapFile};
+use rusqlite::{params, Connection, Transaction};
+use std::{path::Path, time::Duration};
+
+/// Version stored in `PRAGMA user_version`, incremented whenever the schema changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Database schema.
+pub const SCHEMA: &str = "
+-- One row per imported capture.
+CREATE TABLE IF NOT EXISTS captures (
+    scenario TEXT PRIMARY KEY NOT NULL,
+    -- Path of the capture file when it was imported.
+    path TEXT NOT NULL,
+    cpu TEXT NOT NULL,
+    os TEXT NOT NULL,
+    if_name TEXT NOT NULL,
+    -- Timestamp of the first EtherCAT frame.
+    start_time_ns INTEGER,
+    -- Time from the first EtherCAT frame to the last imported frame.
+    duration_ns INTEGER NOT NULL,
+    frames INTEGER NOT NULL,
+    pdus INTEGER NOT NULL,
+    exchanges INTEGER NOT NULL,
+    -- PDUs sent by the MainDevice that never got a response, either because their index was reused
+    -- or because the capture ended first.
+    lost INTEGER NOT NULL,
+    imported_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- One row per EtherCAT frame, in either direction.
+CREATE TABLE IF NOT EXISTS frames (
+    scenario TEXT NOT NULL REFERENCES captures (scenario),
+    -- Wireshark packet number.
+    packet_number INTEGER NOT NULL,
+    time_ns INTEGER NOT NULL,
+    -- 1 if sent by the MainDevice, 0 if returned by the network.
+    from_main_device INTEGER NOT NULL,
+    -- Source MAC address.
+    source TEXT NOT NULL,
+    -- Length of the Ethernet frame in bytes.
+    size INTEGER NOT NULL,
+    pdu_count INTEGER NOT NULL,
+    PRIMARY KEY (scenario, packet_number)
+);
+
+-- One row per PDU in every frame.
+CREATE TABLE IF NOT EXISTS pdus (
+    scenario TEXT NOT NULL,
+    packet_number INTEGER NOT NULL,
+    -- Position of the PDU in its frame, from 0.
+    position INTEGER NOT NULL,
+    -- EtherCAT PDU index.
+    pdu_index INTEGER NOT NULL,
+    -- Command mnemonic, e.g. FPRD.
+    command TEXT NOT NULL,
+    -- auto increment, configured, broadcast or logical. NULL for NOP.
+    addressing TEXT,
+    -- Auto increment or configured station address of register commands.
+    station_address INTEGER,
+    register_address INTEGER,
+    logical_address INTEGER,
+    data BLOB NOT NULL,
+    working_counter INTEGER NOT NULL,
+    PRIMARY KEY (scenario, packet_number, position),
+    FOREIGN KEY (scenario, packet_number) REFERENCES frames (scenario, packet_number)
+);
+
+-- One row per PDU sent by the MainDevice and matched with its response. Join with `pdus` on
+-- scenario, packet number and PDU index to get the request or response PDU.
+CREATE TABLE IF NOT EXISTS exchanges (
+    scenario TEXT NOT NULL,
+    tx_packet_number INTEGER NOT NULL,
+    rx_packet_number INTEGER NOT NULL,
+    pdu_index INTEGER NOT NULL,
+    command TEXT NOT NULL,
+    tx_time_ns INTEGER NOT NULL,
+    rx_time_ns INTEGER NOT NULL,
+    round_trip_ns INTEGER NOT NULL,
+    working_counter INTEGER NOT NULL,
+    -- See the `working_counter` module. NULL until the network has been discovered or mapped.
+    expected_working_counter INTEGER,
+    PRIMARY KEY (scenario, tx_packet_number, pdu_index)
+);
+
+CREATE INDEX IF NOT EXISTS pdus_command ON pdus (scenario, command);
+";
+
+fn nanos(duration: Duration) -> i64 {
+    i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX)
+}
+
+/// Open or create a database, creating any missing tables. Foreign keys are enforced.
+pub fn open(path: &Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+
+    conn.pragma_update(None, "foreign_keys", true)?;
+    conn.execute_batch(SCHEMA)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+    Ok(conn)
+}
+
+/// Import of a single capture, replacing any capture with the same scenario name.
+///
+/// Nothing is committed until [`finish`](Self::finish) is called.
+pub struct Import<'conn> {
+    tx: Transaction<'conn>,
+    scenario: String,
+    end_time: Duration,
+    frames: usize,
+    pdus: usize,
+    exchanges: usize,
+}
+
+impl<'conn> Import<'conn> {
+    pub fn new(
+        conn: &'conn mut Connection,
+        capture: &PcapFile,
+        path: &Path,
+    ) -> rusqlite::Result<Self> {
+        let tx = conn.transaction()?;
+
+        for table in ["exchanges", "pdus", "frames", "captures"] {
+            tx.execute(
+                &format!("DELETE FROM {} WHERE scenario = ?1", table),
+                [&capture.scenario],
+            )?;
+        }
+
+        // Counts are filled in by `finish`
+        tx.execute(
+            "INSERT INTO captures (scenario, path, cpu, os, if_name, duration_ns, frames, pdus, exchanges, lost)
+            VALUES (?1, ?2, ?3, ?4, ?5, 0, 0, 0, 0, 0)",
+            params![
+                capture.scenario,
+                path.to_string_lossy(),
+                capture.cpu,
+                capture.os,
+                capture.if_name,
+            ],
+        )?;
+
+        Ok(Self {
+            tx,
+            scenario: capture.scenario.clone(),
+            end_time: Duration::default(),
+            frames: 0,
+            pdus: 0,
+            exchanges: 0,
+        })
+    }
+
+    /// Add a frame and every PDU in it, received at `time` relative to the first EtherCAT frame in
+    /// the capture. Frames must be added in capture order.
+    pub fn frame(&mut self, frame: &Frame, time: Duration) -> rusqlite::Result<()> {
+        self.end_time = time;
+
+        self.tx
+            .prepare_cached(
+                "INSERT INTO frames (scenario, packet_number, time_ns, from_main_device, source, size, pdu_count)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?
+            .execute(params![
+                self.scenario,
+                frame.wireshark_packet_number,
+                nanos(time),
+                frame.from_master,
+                frame.source.to_string(),
+                frame.size,
+                frame.pdus.len(),
+            ])?;
+
+        let mut insert = self.tx.prepare_cached(
+            "INSERT INTO pdus (scenario, packet_number, position, pdu_index, command, addressing, station_address, register_address, logical_address, data, working_counter)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?;
+
+        for (position, pdu) in frame.pdus.iter().enumerate() {
+            insert.execute(params![
+                self.scenario,
+                frame.wireshark_packet_number,
+                position,
+                pdu.index,
+                command::name(&pdu.command),
+                command::addressing(&pdu.command).map(|addressing| addressing.as_str()),
+                command::subdevice_address(&pdu.command),
+                command::register(&pdu.command),
+                command::logical_address(&pdu.command),
+                pdu.data,
+                pdu.working_counter,
+            ])?;
+        }
+
+        self.frames += 1;
+        self.pdus += frame.pdus.len();
+
+        Ok(())
+    }
+
+    /// Add a matched request and response.
+    pub fn exchange(
+        &mut self,
+        exchange: &PduExchange,
+        expected_working_counter: Option<u16>,
+    ) -> rusqlite::Result<()> {
+        self.tx
+            .prepare_cached(
+                "INSERT INTO exchanges (scenario, tx_packet_number, rx_packet_number, pdu_index, command, tx_time_ns, rx_time_ns, round_trip_ns, working_counter, expected_working_counter)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?
+            .execute(params![
+                self.scenario,
+                exchange.tx_packet_number,
+                exchange.rx_packet_number,
+                exchange.request.index,
+                command::name(&exchange.request.command),
+                nanos(exchange.tx_time),
+                nanos(exchange.rx_time),
+                nanos(exchange.rx_time.saturating_sub(exchange.tx_time)),
+                exchange.response.working_counter,
+                expected_working_counter,
+            ])?;
+
+        self.exchanges += 1;
+
+        Ok(())
+    }
+
+    /// Fill in the capture's start time and row counts, and commit the import.
+    ///
+    /// `start_time` is the timestamp of the first EtherCAT frame in the capture, and `lost` the
+    /// number of PDUs that never got a response.
+    pub fn finish(self, start_time: Option<Duration>, lost: usize) -> rusqlite::Result<()> {
+        self.tx.execute(
+            "UPDATE captures
+            SET start_time_ns = ?2, duration_ns = ?3, frames = ?4, pdus = ?5, exchanges = ?6, lost = ?7
+            WHERE scenario = ?1",
+            params![
+                self.scenario,
+                start_time.map(nanos),
+                nanos(self.end_time),
+                self.frames,
+                self.pdus,
+                self.exchanges,
+                lost,
+            ],
+        )?;
+
+        self.tx.commit()
+    }
+}
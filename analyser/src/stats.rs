@@ -0,0 +1,261 @@
+//! Round trip and cycle delta time statistics.
+//!
+//! Times are taken from the first PDU of each frame as matched by [`PcapFile::match_tx_rx`], and
+//! are given in microseconds. The GUI and command line tools all use these functions so they
+//! report identical numbers for the same capture.
+//!
+//! [`PcapFile::match_tx_rx`]: crate::PcapFile::match_tx_rx
+
+use crate::PduStat;
+use hdrhistogram::Histogram;
+use statrs::statistics::{Data, OrderStatistics, Statistics};
+use std::io::{self, Write};
+
+/// Percentiles computed by [`DumpFileStats::new`].
+pub const DEFAULT_PERCENTILES: [f64; 4] = [25.0, 50.0, 90.0, 99.0];
+
+/// Significant figures kept by the histograms returned by [`histogram`].
+const HISTOGRAM_SIGFIG: u8 = 3;
+
+/// Round trip time of each PDU as `[PDU number, time]` points.
+pub fn round_trip_times(pairs: &[PduStat]) -> Vec<[f64; 2]> {
+    pairs
+        .iter()
+        .enumerate()
+        .map(|(i, item)| [i as f64, item.delta_time.as_nanos() as f64 / 1000.0])
+        .collect()
+}
+
+/// Time between each PDU being sent and the next, as `[PDU number, time]` points.
+pub fn cycle_delta_times(pairs: &[PduStat]) -> Vec<[f64; 2]> {
+    pairs
+        .windows(2)
+        .enumerate()
+        .map(|(i, stats)| {
+            let [prev, curr] = stats else { unreachable!() };
+
+            let t = curr.tx_time.as_nanos() - prev.tx_time.as_nanos();
+
+            [i as f64, t as f64 / 1000.0]
+        })
+        .collect()
+}
+
+/// A single percentile of a series.
+#[derive(Debug, Copy, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Percentile {
+    /// Percentile from 0 to 100, e.g. `99.9`.
+    pub percentile: f64,
+
+    pub value: f64,
+}
+
+/// Summary statistics of a series of times. Every field is `NaN` for an empty series.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DumpFileStats {
+    pub count: usize,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub variance: f64,
+
+    /// Requested percentiles, in the order they were given.
+    pub percentiles: Vec<Percentile>,
+}
+
+impl DumpFileStats {
+    /// Statistics of the `y` values of a series, with the [`DEFAULT_PERCENTILES`].
+    pub fn new(data: &[[f64; 2]]) -> Self {
+        Self::with_percentiles(data, &DEFAULT_PERCENTILES)
+    }
+
+    /// Statistics of the `y` values of a series, with the given percentiles from 0 to 100.
+    pub fn with_percentiles(data: &[[f64; 2]], percentiles: &[f64]) -> Self {
+        let values = data.iter().map(|[_x, y]| *y).collect::<Vec<_>>();
+
+        Self::from_values(&values, percentiles)
+    }
+
+    /// Statistics of a list of values, with the given percentiles from 0 to 100.
+    pub fn from_values(values: &[f64], percentiles: &[f64]) -> Self {
+        let mut d = Data::new(values.to_vec());
+
+        let percentiles = percentiles
+            .iter()
+            .map(|percentile| Percentile {
+                percentile: *percentile,
+                value: d.quantile(percentile / 100.0),
+            })
+            .collect();
+
+        if values.is_empty() {
+            return Self {
+                count: 0,
+                std_dev: f64::NAN,
+                min: f64::NAN,
+                max: f64::NAN,
+                mean: f64::NAN,
+                variance: f64::NAN,
+                percentiles,
+            };
+        }
+
+        Self {
+            count: values.len(),
+            std_dev: values.std_dev(),
+            min: Statistics::min(values),
+            max: Statistics::max(values),
+            mean: values.mean(),
+            variance: values.variance(),
+            percentiles,
+        }
+    }
+
+    /// Value of the given percentile, if it was requested when computing these statistics.
+    pub fn percentile(&self, percentile: f64) -> Option<f64> {
+        self.percentiles
+            .iter()
+            .find(|p| p.percentile == percentile)
+            .map(|p| p.value)
+    }
+
+    /// Value of a single metric, or `None` for a percentile that wasn't computed.
+    pub fn metric(&self, metric: Metric) -> Option<f64> {
+        match metric {
+            Metric::Count => Some(self.count as f64),
+            Metric::Min => Some(self.min),
+            Metric::Max => Some(self.max),
+            Metric::Mean => Some(self.mean),
+            Metric::StdDev => Some(self.std_dev),
+            Metric::Variance => Some(self.variance),
+            Metric::Percentile(percentile) => self.percentile(percentile),
+        }
+    }
+}
+
+/// A single number from [`DumpFileStats`], e.g. to sort or compare captures by.
+///
+/// Parsed from and displayed as `count`, `min`, `max`, `mean`, `std_dev`, `variance`, or `p`
+/// followed by a percentile, e.g. `p99.9`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Metric {
+    Count,
+    Min,
+    Max,
+    Mean,
+    StdDev,
+    Variance,
+    Percentile(f64),
+}
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Count => f.pad("count"),
+            Self::Min => f.pad("min"),
+            Self::Max => f.pad("max"),
+            Self::Mean => f.pad("mean"),
+            Self::StdDev => f.pad("std_dev"),
+            Self::Variance => f.pad("variance"),
+            Self::Percentile(percentile) => f.pad(&format!("p{}", percentile)),
+        }
+    }
+}
+
+impl std::str::FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "count" => Ok(Self::Count),
+            "min" => Ok(Self::Min),
+            "max" => Ok(Self::Max),
+            "mean" => Ok(Self::Mean),
+            "std_dev" | "std-dev" | "stddev" => Ok(Self::StdDev),
+            "variance" => Ok(Self::Variance),
+            other => {
+                let percentile = other
+                    .strip_prefix('p')
+                    .and_then(|p| p.parse::<f64>().ok())
+                    .filter(|p| (0.0..=100.0).contains(p))
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown metric {:?}, expected count, min, max, mean, std_dev, \
+                             variance or a percentile like p99",
+                            s
+                        )
+                    })?;
+
+                Ok(Self::Percentile(percentile))
+            }
+        }
+    }
+}
+
+/// Histogram of the `y` values of a series, truncated to whole microseconds.
+pub fn histogram(data: &[[f64; 2]]) -> Histogram<u32> {
+    let mut histo = Histogram::new(HISTOGRAM_SIGFIG).expect("Histo");
+
+    for [_x, y] in data.iter() {
+        histo.record(*y as u64).ok();
+    }
+
+    histo
+}
+
+/// Write a histogram as an HdrHistogram percentile distribution (`.hgrm`), as read by the
+/// HdrHistogram plotter and most tools built on it.
+pub fn write_percentile_distribution(
+    histo: &Histogram<u32>,
+    mut out: impl Write,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:>12} {:>14} {:>10} {:>14}",
+        "Value", "Percentile", "TotalCount", "1/(1-Percentile)"
+    )?;
+    writeln!(out)?;
+
+    let mut total = 0;
+
+    for value in histo.iter_quantiles(5) {
+        total += value.count_since_last_iteration();
+
+        let quantile = value.quantile_iterated_to();
+
+        if quantile < 1.0 {
+            writeln!(
+                out,
+                "{:12.3} {:1.12} {:10} {:14.2}",
+                value.value_iterated_to() as f64,
+                quantile,
+                total,
+                1.0 / (1.0 - quantile)
+            )?;
+        } else {
+            writeln!(
+                out,
+                "{:12.3} {:1.12} {:10}",
+                value.value_iterated_to() as f64,
+                quantile,
+                total
+            )?;
+        }
+    }
+
+    writeln!(
+        out,
+        "#[Mean    = {:12.3}, StdDeviation   = {:12.3}]",
+        histo.mean(),
+        histo.stdev()
+    )?;
+    writeln!(
+        out,
+        "#[Max     = {:12.3}, Total count    = {:12}]",
+        histo.max() as f64,
+        histo.len()
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Reconstruct the network topology from discovery and initialisation traffic.
+//!
+//! The number of SubDevices comes from the working counter of the broadcast read of register
+//! `0x0000` sent during discovery. Auto increment positions are tied to configured station
+//! addresses by the `APWR` writes to register `0x0010`, and port link states are taken from the
+//! last read of the DL status register (`0x0110`) seen for each SubDevice.
+
+use crate::{
+    command::{self, Access, Addressing},
+    exchange::{register_range, PduExchange},
+};
+use ethercrab::RegisterAddress;
+use std::collections::BTreeMap;
+
+/// Number of ports an ESC can have.
+pub const MAX_PORTS: usize = 4;
+
+/// DL status register (`0x0110`), defined in ETG1000.4 Table 34.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DlStatus(pub u16);
+
+impl DlStatus {
+    /// True if the PDI is operational, i.e. the EEPROM was loaded correctly.
+    pub fn pdi_operational(&self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+
+    /// Link state of the given port, from `0` to `3`.
+    pub fn port(&self, port: usize) -> PortStatus {
+        assert!(port < MAX_PORTS, "Bad port number {}", port);
+
+        PortStatus {
+            link: self.0 >> (4 + port) & 0x01 != 0,
+            loop_closed: self.0 >> (8 + port * 2) & 0x01 != 0,
+            signal: self.0 >> (9 + port * 2) & 0x01 != 0,
+        }
+    }
+
+    /// Link state of all ports.
+    pub fn ports(&self) -> [PortStatus; MAX_PORTS] {
+        [self.port(0), self.port(1), self.port(2), self.port(3)]
+    }
+}
+
+/// The state of a single ESC port.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PortStatus {
+    /// A physical link is present.
+    pub link: bool,
+
+    /// The port is closed, i.e. frames are looped back instead of being forwarded out of it.
+    pub loop_closed: bool,
+
+    /// A signal is detected on the RX side of the port.
+    pub signal: bool,
+}
+
+impl std::fmt::Display for PortStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.link, self.loop_closed, self.signal) {
+            (true, false, true) => f.write_str("open"),
+            (true, false, false) => f.write_str("no signal"),
+            (true, true, _) => f.write_str("closed (link)"),
+            (false, _, _) => f.write_str("closed"),
+        }
+    }
+}
+
+/// A single SubDevice found in the capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubDevice {
+    /// Auto increment position in the network, starting at `0` for the SubDevice closest to the
+    /// MainDevice.
+    pub position: u16,
+
+    /// Configured station address assigned by the MainDevice.
+    pub configured_address: Option<u16>,
+
+    /// The last DL status read from this SubDevice.
+    pub dl_status: Option<DlStatus>,
+}
+
+impl SubDevice {
+    fn new(position: u16) -> Self {
+        Self {
+            position,
+            configured_address: None,
+            dl_status: None,
+        }
+    }
+}
+
+/// Network topology reconstructed from a capture.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Topology {
+    /// Number of SubDevices that responded to the last discovery broadcast.
+    pub discovered_count: Option<u16>,
+
+    /// Every SubDevice seen in the capture, keyed by auto increment position.
+    pub subdevices: BTreeMap<u16, SubDevice>,
+}
+
+impl Topology {
+    /// Reconstruct the topology from every exchange in a capture.
+    pub fn new(exchanges: impl Iterator<Item = PduExchange>) -> Self {
+        let mut self_ = Self::default();
+
+        for exchange in exchanges {
+            self_.push(&exchange);
+        }
+
+        self_
+    }
+
+    /// Find a SubDevice by its configured station address.
+    pub fn by_configured_address(&self, address: u16) -> Option<&SubDevice> {
+        self.subdevices
+            .values()
+            .find(|sd| sd.configured_address == Some(address))
+    }
+
+    /// Update the topology with a single exchange.
+    pub fn push(&mut self, exchange: &PduExchange) {
+        let command = &exchange.request.command;
+        let response = &exchange.response;
+
+        let Some(register) = command::register(command) else {
+            return;
+        };
+
+        match (command::addressing(command), command::access(command)) {
+            (Some(Addressing::Broadcast), Some(Access::Read))
+                if register == u16::from(RegisterAddress::Type) =>
+            {
+                let count = response.working_counter;
+
+                self.discovered_count = Some(count);
+
+                for position in 0..count {
+                    self.subdevices
+                        .entry(position)
+                        .or_insert_with(|| SubDevice::new(position));
+                }
+            }
+
+            (Some(Addressing::AutoIncrement), Some(Access::Write))
+                if response.working_counter == 1 =>
+            {
+                let Some(configured_address) = register_range(
+                    register,
+                    &exchange.request.data,
+                    RegisterAddress::ConfiguredStationAddress.into(),
+                    2,
+                ) else {
+                    return;
+                };
+
+                let position = command::position(command).expect("Auto increment");
+
+                self.subdevices
+                    .entry(position)
+                    .or_insert_with(|| SubDevice::new(position))
+                    .configured_address =
+                    Some(u16::from_le_bytes(configured_address.try_into().unwrap()));
+            }
+
+            (Some(Addressing::AutoIncrement), Some(Access::Read))
+                if response.working_counter == 1 =>
+            {
+                let position = command::position(command).expect("Auto increment");
+
+                let subdevice = self
+                    .subdevices
+                    .entry(position)
+                    .or_insert_with(|| SubDevice::new(position));
+
+                if let Some(configured_address) = register_range(
+                    register,
+                    &response.data,
+                    RegisterAddress::ConfiguredStationAddress.into(),
+                    2,
+                ) {
+                    subdevice.configured_address =
+                        Some(u16::from_le_bytes(configured_address.try_into().unwrap()));
+                }
+
+                if let Some(status) = register_range(
+                    register,
+                    &response.data,
+                    RegisterAddress::DlStatus.into(),
+                    2,
+                ) {
+                    subdevice.dl_status =
+                        Some(DlStatus(u16::from_le_bytes(status.try_into().unwrap())));
+                }
+            }
+
+            (Some(Addressing::Configured), Some(Access::Read)) if response.working_counter == 1 => {
+                let address = command::configured_address(command).expect("Configured");
+
+                let Some(status) = register_range(
+                    register,
+                    &response.data,
+                    RegisterAddress::DlStatus.into(),
+                    2,
+                ) else {
+                    return;
+                };
+
+                if let Some(subdevice) = self
+                    .subdevices
+                    .values_mut()
+                    .find(|sd| sd.configured_address == Some(address))
+                {
+                    subdevice.dl_status =
+                        Some(DlStatus(u16::from_le_bytes(status.try_into().unwrap())));
+                }
+            }
+
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Expected working counter of a PDU, given what's known about the network so far.
+//!
+//! Every SubDevice that successfully reads a PDU's data increments the working counter by one, and
+//! every SubDevice that successfully writes it by one, or by two for a write as part of a read/write
+//! command (ETG1000.4 section 5.4). The expected value is the working counter the PDU would have if
+//! every addressed SubDevice processed it:
+//!
+//! - Configured and auto increment address reads and writes: `1`, or `3` for APRW and FPRW.
+//! - Broadcasts, FRMW and ARMW: the number of SubDevices found by discovery, three times over for
+//!   BRW.
+//! - Logical commands: the SubDevices with an enabled FMMU of a matching type overlapping the
+//!   PDU's logical address range.
+
+use crate::{
+    command::{self, Access, Addressing},
+    exchange::PduExchange,
+    pdo::{FmmuType, PdoMapping},
+    pdu::Pdu,
+    topology::Topology,
+};
+
+/// Network state needed to compute expected working counters.
+#[derive(Debug, Default)]
+pub struct WorkingCounters {
+    topology: Topology,
+    mapping: PdoMapping,
+}
+
+impl WorkingCounters {
+    /// Update network state with a single exchange.
+    pub fn push(&mut self, exchange: &PduExchange) {
+        self.topology.push(exchange);
+        self.mapping.push(exchange);
+    }
+
+    /// Expected working counter of a PDU, or `None` for NOP or if the network hasn't been
+    /// discovered or mapped yet.
+    pub fn expected(&self, pdu: &Pdu) -> Option<u16> {
+        let command = &pdu.command;
+
+        match (command::addressing(command)?, command::access(command)?) {
+            (Addressing::Broadcast, Access::ReadWrite) => {
+                self.subdevice_count().map(|count| count.saturating_mul(3))
+            }
+            (Addressing::Broadcast, _) | (_, Access::ReadMultipleWrite) => self.subdevice_count(),
+            (Addressing::AutoIncrement | Addressing::Configured, Access::ReadWrite) => Some(3),
+            (Addressing::AutoIncrement | Addressing::Configured, _) => Some(1),
+            (Addressing::Logical, access) => {
+                let address = command::logical_address(command)?;
+
+                self.logical(address, pdu.data.len(), |reads, writes| match access {
+                    Access::Read => u16::from(reads),
+                    Access::Write => u16::from(writes),
+                    Access::ReadWrite => u16::from(reads) + 2 * u16::from(writes),
+                    Access::ReadMultipleWrite => unreachable!("No logical read multiple write"),
+                })
+            }
+        }
+    }
+
+    fn subdevice_count(&self) -> Option<u16> {
+        self.topology.discovered_count.or_else(|| {
+            (!self.topology.subdevices.is_empty())
+                .then(|| u16::try_from(self.topology.subdevices.len()).unwrap_or(u16::MAX))
+        })
+    }
+
+    /// Sum of a per-SubDevice working counter increment over every SubDevice with an FMMU mapped
+    /// into the given logical range. `increment` is given whether the SubDevice reads and writes
+    /// any of the range.
+    fn logical(
+        &self,
+        address: u32,
+        len: usize,
+        increment: impl Fn(bool, bool) -> u16,
+    ) -> Option<u16> {
+        if self.mapping.subdevices.is_empty() {
+            return None;
+        }
+
+        let start = u64::from(address);
+        let end = start + len as u64;
+
+        let total = self
+            .mapping
+            .subdevices
+            .values()
+            .map(|subdevice| {
+                let (mut reads, mut writes) = (false, false);
+
+                for fmmu in subdevice.fmmus.values().filter(|fmmu| fmmu.enabled) {
+                    let fmmu_start = u64::from(fmmu.logical_start);
+                    let fmmu_end = fmmu_start + u64::from(fmmu.length);
+
+                    if fmmu_start >= end || fmmu_end <= start {
+                        continue;
+                    }
+
+                    match fmmu.fmmu_type {
+                        FmmuType::Read => reads = true,
+                        FmmuType::Write => writes = true,
+                        FmmuType::ReadWrite => {
+                            reads = true;
+                            writes = true;
+                        }
+                        FmmuType::Unused | FmmuType::Unknown(_) => (),
+                    }
+                }
+
+                increment(reads, writes)
+            })
+            .sum();
+
+        Some(total)
+    }
+}